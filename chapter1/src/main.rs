@@ -1,10 +1,18 @@
-fn main() {
+use std::io::{self, Write};
+
+mod numerals;
+mod radix;
+mod template;
+
+// the literal arguments and the approximate pi are the point of the example
+#[allow(clippy::write_literal, clippy::approx_constant)]
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     // 1.1
-    // println!("Hello, world!");
-    // println!("I am a Rustacean!");
+    // writeln!(out, "Hello, world!")?;
+    // writeln!(out, "I am a Rustacean!")?;
     
     // let x = 5 + /* 90 + */ 5;
-    // println!("x = {x}");
+    // writeln!(out, "x = {x}")?;
 
     // formatted print
     // format! -> String
@@ -13,26 +21,133 @@ fn main() {
     // eprint! -> output in srderr
     // eprintln!
 
-    println!("{0} this is {1}, {1} this is {0}", "Alice", "Bob");
-    println!("{fruit} eaten by {creature}", fruit="mango", creature="monkey");
+    writeln!(out, "{0} this is {1}, {1} this is {0}", "Alice", "Bob")?;
+    writeln!(out, "{fruit} eaten by {creature}", fruit="mango", creature="monkey")?;
 
-    println!("Base 10: {}", 12345);
-    println!("Base 2: {:b}", 12345);
-    println!("Base 8: {:o}", 12345);
-    println!("Base 16: {:x}", 12345);
+    writeln!(out, "Base 10: {}", 12345)?;
+    writeln!(out, "Base 2: {:b}", 12345)?;
+    writeln!(out, "Base 8: {:o}", 12345)?;
+    writeln!(out, "Base 16: {:x}", 12345)?;
 
-    println!("Right justified:-");
-    println!("{number:>5}", number=1);
+    writeln!(out, "Right justified:-")?;
+    writeln!(out, "{number:>5}", number=1)?;
 
-    println!("Padding of zeroes:-");
-    println!("{number:0>5}", number=1);
+    writeln!(out, "Padding of zeroes:-")?;
+    writeln!(out, "{number:0>5}", number=1)?;
 
-    println!("padding of zeroes at right");
-    println!("{number:0<5}", number=5);
+    writeln!(out, "padding of zeroes at right")?;
+    writeln!(out, "{number:0<5}", number=5)?;
 
-    println!("|{:=^21}|", "heading");
+    writeln!(out, "|{:=^21}|", "heading")?;
 
-    println!("{0} is {1:.3}", "pi", 3.1415926535);
+    writeln!(out, "{0} is {1:.3}", "pi", 3.1415926535)?;
     // escape { with {{
+    Ok(())
+}
+
+// the same specifiers at runtime, e.g. for templates read from a file
+fn part2(out: &mut dyn Write) -> io::Result<()> {
+    use template::{Args, Template};
+
+    let templates = "\
+{0} this is {1}, {1} this is {0}
+{fruit} eaten by {creature}
+Base 2: {number:b}, base 8: {number:o}, base 16: {number:#x}
+|{heading:=^21}|
+pi is {pi:.3}, {{escaped}}";
+
+    let args = Args::new()
+        .arg("Alice")
+        .arg("Bob")
+        .named("fruit", "mango")
+        .named("creature", "monkey")
+        .named("number", 12345)
+        .named("heading", "heading")
+        .named("pi", std::f64::consts::PI);
+
+    for line in templates.lines() {
+        match Template::parse(line).and_then(|template| template.render(&args)) {
+            Ok(rendered) => writeln!(out, "{}", rendered)?,
+            Err(e) => writeln!(out, "error: {}", e)?,
+        }
+    }
+
+    // errors point at the offending column
+    for broken in ["{0} is {1:.3", "{fruit:>5q}", "{missing}"] {
+        if let Err(e) = template::format(broken, &args) {
+            writeln!(out, "{:<14} error: {}", broken, e)?;
+        }
+    }
+    Ok(())
+}
+
+// bases beyond 2, 8 and 16, in both directions
+fn part3(out: &mut dyn Write) -> io::Result<()> {
+    use radix::{from_radix, to_radix, Radix};
+
+    for base in [2, 3, 8, 16, 36, 37] {
+        match to_radix(12345, base) {
+            Ok(digits) => writeln!(out, "Base {}: {}", base, digits)?,
+            Err(e) => writeln!(out, "Base {}: {}", base, e)?,
+        }
+    }
+    writeln!(out, "Crockford base32: {}", Radix::crockford().format(12345))?;
+    let binary = Radix::new(2).expect("a valid base").with_prefix().grouped(4);
+    writeln!(out, "Grouped: {}", binary.format(-12345))?;
+    let hex = Radix::new(16).expect("a valid base").uppercase().with_prefix();
+    writeln!(out, "Upper hex: {}", hex.format(u128::MAX))?;
+    let base36 = Radix::new(36).expect("a valid base");
+    writeln!(out, "i128::MIN in base 36: {}", base36.format(i128::MIN))?;
+
+    let dna = Radix::custom("ACGT").expect("four distinct digits");
+    writeln!(out, "12345 as DNA: {}", dna.format(12345))?;
+
+    for (text, base) in [("0x30_39", 16), ("-0b1010", 2), ("9ix", 36), ("zz", 16)] {
+        match from_radix::<i16>(text, base) {
+            Ok(n) => writeln!(out, "{:?} in base {} is {}", text, base, n)?,
+            Err(e) => writeln!(out, "{:?} in base {}: {}", text, base, e)?,
+        }
+    }
+    // overflow is an error rather than a panic
+    match from_radix::<u8>("0x100", 16) {
+        Ok(n) => writeln!(out, "0x100 fits in a u8: {}", n)?,
+        Err(e) => writeln!(out, "0x100 as a u8: {}", e)?,
+    }
+    if let Err(e) = Radix::custom("0120") {
+        writeln!(out, "Radix::custom(\"0120\"): {}", e)?;
+    }
+    Ok(())
+}
+
+// numbers in words and Roman numerals, as Display adapters
+fn part4(out: &mut dyn Write) -> io::Result<()> {
+    use numerals::{Cardinal, Ordinal, OrdinalWords, Roman};
+
+    writeln!(out, "{}", Cardinal(12345))?;
+    writeln!(out, "{} / {}", Ordinal(3), OrdinalWords(3))?;
+    writeln!(out, "{} / {}", Ordinal(12345), OrdinalWords(12345))?;
+    // padding and alignment work as for any other value
+    writeln!(out, "|{:=^21}|", Cardinal(-40))?;
+
+    for n in [4, 1994, 2026] {
+        if let Some(roman) = Roman::new(n) {
+            writeln!(out, "{:>4} is {}", n, roman)?;
+        }
+    }
+    for text in ["MCMXCIV", "mmxxvi", "IIII", "MMMM", "XIZ"] {
+        match text.parse::<Roman>() {
+            Ok(roman) => writeln!(out, "{} is {}", text, roman.value())?,
+            Err(e) => writeln!(out, "{}: {}", text, e)?,
+        }
+    }
+    Ok(())
+}
 
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "formatted print: positional and named arguments, radix, padding, precision",
+        part2 => "the same specifiers in templates parsed at runtime",
+        part3 => "formatting and parsing numbers in any base",
+        part4 => "numbers in words, ordinals and Roman numerals",
+    ])
 }
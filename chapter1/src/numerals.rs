@@ -0,0 +1,420 @@
+/* Numbers in words and Roman numerals */
+// `Display` adapters, so they work with `{}` and honour width and
+// alignment like any other value: `format!("{:>30}", Cardinal(12345))`
+
+use std::fmt;
+use std::str::FromStr;
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+// every power of a thousand that fits an i128 or a u128
+const SCALES: [&str; 13] = [
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+    "sextillion",
+    "septillion",
+    "octillion",
+    "nonillion",
+    "decillion",
+    "undecillion",
+];
+
+/// Words for a number below a thousand, e.g. `three hundred forty-five`.
+fn hundreds(n: u128, words: &mut Vec<String>) {
+    let (hundreds, rest) = (n / 100, n % 100);
+    if hundreds > 0 {
+        words.push(ONES[hundreds as usize].to_owned());
+        words.push(String::from("hundred"));
+    }
+    match rest {
+        0 => {}
+        1..=19 => words.push(ONES[rest as usize].to_owned()),
+        _ if rest % 10 == 0 => words.push(TENS[(rest / 10) as usize].to_owned()),
+        _ => words.push(format!(
+            "{}-{}",
+            TENS[(rest / 10) as usize],
+            ONES[(rest % 10) as usize]
+        )),
+    }
+}
+
+fn cardinal_words((negative, mut magnitude): (bool, u128)) -> String {
+    if magnitude == 0 {
+        return ONES[0].to_owned();
+    }
+    let mut groups = Vec::new();
+    while magnitude > 0 {
+        groups.push(magnitude % 1000);
+        magnitude /= 1000;
+    }
+
+    let mut words = Vec::new();
+    if negative {
+        words.push(String::from("minus"));
+    }
+    for (scale, &group) in groups.iter().enumerate().rev() {
+        if group == 0 {
+            continue;
+        }
+        hundreds(group, &mut words);
+        if scale > 0 {
+            words.push(SCALES[scale].to_owned());
+        }
+    }
+    words.join(" ")
+}
+
+/// Turns the last word of a cardinal into its ordinal, e.g. `forty-five` into `forty-fifth`.
+fn ordinal_word(word: &str) -> String {
+    let (head, last) = match word.rfind('-') {
+        Some(hyphen) => word.split_at(hyphen + 1),
+        None => ("", word),
+    };
+    let last = match last {
+        "one" => String::from("first"),
+        "two" => String::from("second"),
+        "three" => String::from("third"),
+        "five" => String::from("fifth"),
+        "eight" => String::from("eighth"),
+        "nine" => String::from("ninth"),
+        "twelve" => String::from("twelfth"),
+        _ => match last.strip_suffix('y') {
+            Some(stem) => format!("{}ieth", stem),
+            None => format!("{}th", last),
+        },
+    };
+    format!("{}{}", head, last)
+}
+
+/// Integers the adapters below accept, implemented for every primitive width.
+pub trait Integer: Copy {
+    /// Sign and magnitude; the magnitude of `i128::MIN` still fits a `u128`.
+    fn split(self) -> (bool, u128);
+}
+
+macro_rules! integer {
+    (signed: $($int:ty),+) => {
+        $(
+            impl Integer for $int {
+                fn split(self) -> (bool, u128) {
+                    (self < 0, self.unsigned_abs() as u128)
+                }
+            }
+        )+
+    };
+    (unsigned: $($int:ty),+) => {
+        $(
+            impl Integer for $int {
+                fn split(self) -> (bool, u128) {
+                    (false, self as u128)
+                }
+            }
+        )+
+    };
+}
+
+integer!(signed: i8, i16, i32, i64, i128, isize);
+integer!(unsigned: u8, u16, u32, u64, u128, usize);
+
+/// A number in words: `Cardinal(12345)` is "twelve thousand three hundred forty-five".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cardinal<T>(pub T);
+
+/// An ordinal with a numeric suffix: `Ordinal(3)` is "3rd".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ordinal<T>(pub T);
+
+/// An ordinal in words: `OrdinalWords(3)` is "third".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrdinalWords<T>(pub T);
+
+impl<T: Integer> fmt::Display for Cardinal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&cardinal_words(self.0.split()))
+    }
+}
+
+impl<T: Integer> fmt::Display for Ordinal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (negative, magnitude) = self.0.split();
+        // 11th, 12th and 13th break the pattern of 1st, 2nd and 3rd
+        let suffix = match (magnitude % 10, magnitude % 100) {
+            (_, 11..=13) => "th",
+            (1, _) => "st",
+            (2, _) => "nd",
+            (3, _) => "rd",
+            _ => "th",
+        };
+        let sign = if negative { "-" } else { "" };
+        f.pad(&format!("{}{}{}", sign, magnitude, suffix))
+    }
+}
+
+impl<T: Integer> fmt::Display for OrdinalWords<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cardinal = cardinal_words(self.0.split());
+        let ordinal = match cardinal.rsplit_once(' ') {
+            Some((head, last)) => format!("{} {}", head, ordinal_word(last)),
+            None => ordinal_word(&cardinal),
+        };
+        f.pad(&ordinal)
+    }
+}
+
+const NUMERALS: [(u16, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// A number between 1 and 3999, displayed as a Roman numeral.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Roman(u16);
+
+impl Roman {
+    pub const MAX: u16 = 3999;
+
+    /// `None` outside `1..=3999`, which have no standard Roman numeral.
+    pub fn new(n: u16) -> Option<Roman> {
+        (1..=Roman::MAX).contains(&n).then_some(Roman(n))
+    }
+
+    pub fn value(self) -> u16 {
+        self.0
+    }
+}
+
+impl fmt::Display for Roman {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut rest = self.0;
+        let mut numeral = String::new();
+        for &(value, symbol) in &NUMERALS {
+            while rest >= value {
+                numeral.push_str(symbol);
+                rest -= value;
+            }
+        }
+        f.pad(&numeral)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseRomanError {
+    Empty,
+    InvalidNumeral {
+        numeral: char,
+        index: usize,
+    },
+    /// Valid symbols in a non-standard order or count, e.g. `IIII` or `IC`.
+    NotCanonical {
+        expected: String,
+    },
+    TooLarge,
+}
+
+impl fmt::Display for ParseRomanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRomanError::Empty => write!(f, "empty Roman numeral"),
+            ParseRomanError::InvalidNumeral { numeral, index } => {
+                write!(f, "{:?} at index {} is not a Roman numeral", numeral, index)
+            }
+            ParseRomanError::NotCanonical { expected } => {
+                write!(
+                    f,
+                    "not a standard Roman numeral, did you mean {}?",
+                    expected
+                )
+            }
+            ParseRomanError::TooLarge => write!(f, "Roman numerals stop at {}", Roman::MAX),
+        }
+    }
+}
+
+impl std::error::Error for ParseRomanError {}
+
+impl FromStr for Roman {
+    type Err = ParseRomanError;
+
+    /// Accepts upper or lower case, but only the standard subtractive form.
+    fn from_str(text: &str) -> Result<Roman, ParseRomanError> {
+        if text.is_empty() {
+            return Err(ParseRomanError::Empty);
+        }
+        let value_of = |c: char| match c.to_ascii_uppercase() {
+            'I' => Some(1),
+            'V' => Some(5),
+            'X' => Some(10),
+            'L' => Some(50),
+            'C' => Some(100),
+            'D' => Some(500),
+            'M' => Some(1000),
+            _ => None,
+        };
+
+        let mut values = Vec::new();
+        for (index, numeral) in text.chars().enumerate() {
+            let value =
+                value_of(numeral).ok_or(ParseRomanError::InvalidNumeral { numeral, index })?;
+            values.push(value);
+        }
+
+        // a symbol smaller than the next one is subtracted, as in IV or XC
+        let mut total: u32 = 0;
+        for (index, &value) in values.iter().enumerate() {
+            match values.get(index + 1) {
+                Some(&next) if value < next => total = total.wrapping_sub(value),
+                _ => total = total.wrapping_add(value),
+            }
+        }
+
+        let roman = u16::try_from(total)
+            .ok()
+            .and_then(Roman::new)
+            .ok_or(ParseRomanError::TooLarge)?;
+        // anything that does not read back the same way is malformed, e.g. IIII or VX
+        let canonical = roman.to_string();
+        if canonical.eq_ignore_ascii_case(text) {
+            Ok(roman)
+        } else {
+            Err(ParseRomanError::NotCanonical {
+                expected: canonical,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cardinals() {
+        assert_eq!(
+            Cardinal(12345).to_string(),
+            "twelve thousand three hundred forty-five"
+        );
+        assert_eq!(Cardinal(0).to_string(), "zero");
+        assert_eq!(Cardinal(-40).to_string(), "minus forty");
+        assert_eq!(Cardinal(1_000_001).to_string(), "one million one");
+        assert!(Cardinal(i128::MIN)
+            .to_string()
+            .starts_with("minus one hundred seventy undecillion"));
+        assert_eq!(format!("[{:>12}]", Cardinal(21_u8)), "[  twenty-one]");
+        assert!(Cardinal(u128::MAX)
+            .to_string()
+            .starts_with("three hundred forty undecillion"));
+        assert_eq!(Cardinal(7_usize).to_string(), "seven");
+        assert_eq!(Cardinal(-7_isize).to_string(), "minus seven");
+    }
+
+    #[test]
+    fn ordinals() {
+        let suffixes: Vec<String> = [1, 2, 3, 4, 11, 12, 13, 21, 22, 101, 111, 112, -3]
+            .iter()
+            .map(|&n| Ordinal(n).to_string())
+            .collect();
+        assert_eq!(
+            suffixes,
+            [
+                "1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "101st",
+                "111th", "112th", "-3rd"
+            ]
+        );
+
+        assert_eq!(OrdinalWords(3).to_string(), "third");
+        assert_eq!(OrdinalWords(12).to_string(), "twelfth");
+        assert_eq!(OrdinalWords(20).to_string(), "twentieth");
+        assert_eq!(OrdinalWords(45).to_string(), "forty-fifth");
+        assert_eq!(OrdinalWords(100).to_string(), "one hundredth");
+        assert_eq!(
+            OrdinalWords(12345).to_string(),
+            "twelve thousand three hundred forty-fifth"
+        );
+        let index: usize = 2;
+        assert_eq!(Ordinal(index).to_string(), "2nd");
+        assert_eq!(OrdinalWords(index).to_string(), "second");
+        assert_eq!(Ordinal(u128::MAX).to_string(), format!("{}th", u128::MAX));
+        assert_eq!(Ordinal(i128::MIN).to_string(), format!("{}th", i128::MIN));
+    }
+
+    #[test]
+    fn roman_numerals() {
+        let roman = |n| Roman::new(n).unwrap().to_string();
+        assert_eq!(roman(1994), "MCMXCIV");
+        assert_eq!(roman(3999), "MMMCMXCIX");
+        assert_eq!(roman(4), "IV");
+        assert_eq!(Roman::new(0), None);
+        assert_eq!(Roman::new(4000), None);
+
+        for n in 1..=Roman::MAX {
+            let parsed: Roman = roman(n).parse().unwrap();
+            assert_eq!(parsed.value(), n);
+        }
+        assert_eq!("mmxxvi".parse::<Roman>().map(Roman::value), Ok(2026));
+    }
+
+    #[test]
+    fn rejects_malformed_roman_numerals() {
+        assert_eq!("".parse::<Roman>(), Err(ParseRomanError::Empty));
+        assert_eq!(
+            "XIZ".parse::<Roman>(),
+            Err(ParseRomanError::InvalidNumeral {
+                numeral: 'Z',
+                index: 2
+            })
+        );
+        assert_eq!(
+            "IIII".parse::<Roman>(),
+            Err(ParseRomanError::NotCanonical {
+                expected: String::from("IV")
+            })
+        );
+        assert!(matches!(
+            "IC".parse::<Roman>(),
+            Err(ParseRomanError::NotCanonical { .. })
+        ));
+        assert_eq!("MMMM".parse::<Roman>(), Err(ParseRomanError::TooLarge));
+        assert!("IIV".parse::<Roman>().is_err());
+    }
+}
@@ -0,0 +1,432 @@
+/* Numbers in any base */
+// `{:b}`, `{:o}` and `{:x}` only cover bases 2, 8 and 16; `Radix` formats
+// and parses every integer type in bases 2 to 36 or with a custom alphabet
+// such as Crockford's base32, with optional prefixes and `_` grouping
+
+use std::fmt;
+
+/// Digits of the standard alphabets, `Radix::new(base)` uses the first `base` of them.
+const DIGITS: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Crockford's base32 leaves out I, L, O and U to avoid misreading.
+const CROCKFORD: &str = "0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RadixError {
+    /// No digits at all, e.g. `""`, `"-"` or `"0x"`.
+    Empty,
+    /// A character outside the alphabet; the index counts characters.
+    InvalidDigit {
+        digit: char,
+        index: usize,
+    },
+    /// The value does not fit the target integer type.
+    OutOfRange,
+    InvalidAlphabet(String),
+    /// A base outside `2..=36`.
+    InvalidBase(u32),
+}
+
+impl fmt::Display for RadixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RadixError::Empty => write!(f, "no digits to parse"),
+            RadixError::InvalidDigit { digit, index } => {
+                write!(f, "invalid digit {:?} at index {}", digit, index)
+            }
+            RadixError::OutOfRange => write!(f, "number is out of range for the target type"),
+            RadixError::InvalidAlphabet(reason) => write!(f, "invalid alphabet: {}", reason),
+            RadixError::InvalidBase(base) => {
+                write!(f, "base must be between 2 and 36, got {}", base)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RadixError {}
+
+/// Integer types `Radix` can format and parse, implemented for every primitive integer.
+pub trait RadixInt: Copy {
+    /// Sign and magnitude; the magnitude of `i128::MIN` still fits a `u128`.
+    fn split(self) -> (bool, u128);
+    /// `self * base + digit`, or `- digit` when building a negative number.
+    fn push_digit(self, base: u32, digit: u32, negative: bool) -> Option<Self>;
+    fn zero() -> Self;
+}
+
+macro_rules! radix_int {
+    (signed: $($int:ty),+) => {
+        $(
+            impl RadixInt for $int {
+                fn split(self) -> (bool, u128) {
+                    (self < 0, self.unsigned_abs() as u128)
+                }
+
+                fn push_digit(self, base: u32, digit: u32, negative: bool) -> Option<$int> {
+                    // a zero stays zero even when the base does not fit the type
+                    let shifted = match self {
+                        0 => 0,
+                        _ => self.checked_mul(<$int>::try_from(base).ok()?)?,
+                    };
+                    let digit = <$int>::try_from(digit).ok()?;
+                    if negative {
+                        shifted.checked_sub(digit)
+                    } else {
+                        shifted.checked_add(digit)
+                    }
+                }
+
+                fn zero() -> $int {
+                    0
+                }
+            }
+        )+
+    };
+    (unsigned: $($int:ty),+) => {
+        $(
+            impl RadixInt for $int {
+                fn split(self) -> (bool, u128) {
+                    (false, self as u128)
+                }
+
+                fn push_digit(self, base: u32, digit: u32, negative: bool) -> Option<$int> {
+                    let shifted = match self {
+                        0 => 0,
+                        _ => self.checked_mul(<$int>::try_from(base).ok()?)?,
+                    };
+                    let digit = <$int>::try_from(digit).ok()?;
+                    // only `-0` is a valid negative unsigned number
+                    if negative {
+                        shifted.checked_sub(digit)
+                    } else {
+                        shifted.checked_add(digit)
+                    }
+                }
+
+                fn zero() -> $int {
+                    0
+                }
+            }
+        )+
+    };
+}
+
+radix_int!(signed: i8, i16, i32, i64, i128, isize);
+radix_int!(unsigned: u8, u16, u32, u64, u128, usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Radix {
+    digits: Vec<char>,
+    // extra spellings accepted when parsing, e.g. `O` for `0` in Crockford's alphabet
+    aliases: Vec<(char, u32)>,
+    case_insensitive: bool,
+    prefix: Option<&'static str>,
+    show_prefix: bool,
+    group: Option<usize>,
+}
+
+impl Radix {
+    /// Digits `0-9` then `a-z`; parsing ignores case. Bases outside `2..=36`
+    /// are an error rather than the panic of `u32::from_str_radix`.
+    pub fn new(base: u32) -> Result<Radix, RadixError> {
+        if !(2..=36).contains(&base) {
+            return Err(RadixError::InvalidBase(base));
+        }
+        Ok(Radix {
+            digits: DIGITS.chars().take(base as usize).collect(),
+            aliases: Vec::new(),
+            case_insensitive: true,
+            prefix: match base {
+                2 => Some("0b"),
+                8 => Some("0o"),
+                16 => Some("0x"),
+                _ => None,
+            },
+            show_prefix: false,
+            group: None,
+        })
+    }
+
+    /// A custom alphabet, where the base is the number of digits; parsing is case sensitive.
+    pub fn custom(digits: &str) -> Result<Radix, RadixError> {
+        let digits: Vec<char> = digits.chars().collect();
+        if digits.len() < 2 {
+            return Err(RadixError::InvalidAlphabet(String::from(
+                "an alphabet needs at least two digits",
+            )));
+        }
+        for (index, digit) in digits.iter().enumerate() {
+            if digits[..index].contains(digit) {
+                return Err(RadixError::InvalidAlphabet(format!(
+                    "{:?} appears twice",
+                    digit
+                )));
+            }
+            if matches!(digit, '_' | '-' | '+') {
+                return Err(RadixError::InvalidAlphabet(format!(
+                    "{:?} is reserved for signs and grouping",
+                    digit
+                )));
+            }
+        }
+        Ok(Radix {
+            digits,
+            aliases: Vec::new(),
+            case_insensitive: false,
+            prefix: None,
+            show_prefix: false,
+            group: None,
+        })
+    }
+
+    /// Crockford's base32, which also reads `I`/`L` as `1` and `O` as `0` in any case.
+    pub fn crockford() -> Radix {
+        Radix {
+            digits: CROCKFORD.chars().collect(),
+            aliases: vec![('I', 1), ('L', 1), ('O', 0)],
+            case_insensitive: true,
+            prefix: None,
+            show_prefix: false,
+            group: None,
+        }
+    }
+
+    pub fn base(&self) -> u32 {
+        self.digits.len() as u32
+    }
+
+    /// Formats letters in upper case, e.g. `ff` as `FF`. A custom alphabet
+    /// holding both cases of a letter, e.g. `aA`, is left as it is, since two
+    /// of its digits would become the same.
+    pub fn uppercase(mut self) -> Radix {
+        let upper: Vec<char> = self.digits.iter().map(char::to_ascii_uppercase).collect();
+        let distinct = upper
+            .iter()
+            .enumerate()
+            .all(|(index, digit)| !upper[..index].contains(digit));
+        if distinct {
+            self.digits = upper;
+        }
+        self
+    }
+
+    /// Writes `0b`, `0o` or `0x` in front of the digits for bases 2, 8 and 16.
+    pub fn with_prefix(mut self) -> Radix {
+        self.show_prefix = true;
+        self
+    }
+
+    /// Separates groups of `size` digits with `_`, counting from the right.
+    pub fn grouped(mut self, size: usize) -> Radix {
+        self.group = (size > 0).then_some(size);
+        self
+    }
+
+    pub fn format<T: RadixInt>(&self, n: T) -> String {
+        let (negative, mut magnitude) = n.split();
+        let base = u128::from(self.base());
+
+        let mut digits = Vec::new();
+        loop {
+            digits.push(self.digits[(magnitude % base) as usize]);
+            magnitude /= base;
+            if magnitude == 0 {
+                break;
+            }
+        }
+
+        let mut formatted = String::new();
+        if negative {
+            formatted.push('-');
+        }
+        if let (true, Some(prefix)) = (self.show_prefix, self.prefix) {
+            formatted.push_str(prefix);
+        }
+        // `digits` holds the least significant digit first
+        for (index, digit) in digits.iter().enumerate().rev() {
+            formatted.push(*digit);
+            if let Some(size) = self.group {
+                if index > 0 && index % size == 0 {
+                    formatted.push('_');
+                }
+            }
+        }
+        formatted
+    }
+
+    fn digit_value(&self, c: char) -> Option<u32> {
+        let matches = |digit: char| {
+            if self.case_insensitive {
+                digit.eq_ignore_ascii_case(&c)
+            } else {
+                digit == c
+            }
+        };
+        self.digits
+            .iter()
+            .position(|&digit| matches(digit))
+            .map(|position| position as u32)
+            .or_else(|| {
+                self.aliases
+                    .iter()
+                    .find(|&&(alias, _)| matches(alias))
+                    .map(|&(_, value)| value)
+            })
+    }
+
+    /// Parses an optional sign, the optional prefix of the base, and digits
+    /// which may be separated by `_`. Overflow is an error, never a panic.
+    pub fn parse<T: RadixInt>(&self, text: &str) -> Result<T, RadixError> {
+        let chars: Vec<char> = text.chars().collect();
+        let (negative, mut start) = match chars.first() {
+            Some('-') => (true, 1),
+            Some('+') => (false, 1),
+            _ => (false, 0),
+        };
+        if let Some(prefix) = self.prefix {
+            let candidate: String = chars[start..].iter().take(prefix.len()).collect();
+            if candidate.eq_ignore_ascii_case(prefix) {
+                start += prefix.len();
+            }
+        }
+
+        let mut value = T::zero();
+        let mut any_digit = false;
+        for (offset, &c) in chars[start..].iter().enumerate() {
+            if c == '_' && any_digit {
+                continue;
+            }
+            let digit = self.digit_value(c).ok_or(RadixError::InvalidDigit {
+                digit: c,
+                index: start + offset,
+            })?;
+            value = value
+                .push_digit(self.base(), digit, negative)
+                .ok_or(RadixError::OutOfRange)?;
+            any_digit = true;
+        }
+
+        if any_digit {
+            Ok(value)
+        } else {
+            Err(RadixError::Empty)
+        }
+    }
+}
+
+/// `n` in `base`, e.g. `to_radix(12345, 36) == Ok("9ix")`; the base must be
+/// in `2..=36`.
+pub fn to_radix<T: RadixInt>(n: T, base: u32) -> Result<String, RadixError> {
+    Ok(Radix::new(base)?.format(n))
+}
+
+/// Reverse of `to_radix`, also accepting a sign, the `0b`/`0o`/`0x` prefix of
+/// the base and `_` between digits.
+pub fn from_radix<T: RadixInt>(text: &str, base: u32) -> Result<T, RadixError> {
+    Radix::new(base)?.parse(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_like_std_in_the_std_bases() {
+        for n in [0_u64, 1, 7, 12345, u64::MAX] {
+            assert_eq!(to_radix(n, 2).unwrap(), format!("{:b}", n));
+            assert_eq!(to_radix(n, 8).unwrap(), format!("{:o}", n));
+            assert_eq!(to_radix(n, 10).unwrap(), n.to_string());
+            assert_eq!(to_radix(n, 16).unwrap(), format!("{:x}", n));
+        }
+        assert_eq!(to_radix(12345, 36).unwrap(), "9ix");
+        assert_eq!(to_radix(-12345, 3).unwrap(), "-121221020");
+        assert_eq!(Radix::new(16).unwrap().uppercase().format(255_u8), "FF");
+    }
+
+    #[test]
+    fn round_trips_every_width() {
+        for base in 2..=36 {
+            assert_eq!(
+                from_radix::<i8>(&to_radix(i8::MIN, base).unwrap(), base),
+                Ok(i8::MIN)
+            );
+            assert_eq!(
+                from_radix::<u16>(&to_radix(u16::MAX, base).unwrap(), base),
+                Ok(u16::MAX)
+            );
+            assert_eq!(
+                from_radix::<i64>(&to_radix(-42_i64, base).unwrap(), base),
+                Ok(-42)
+            );
+            assert_eq!(
+                from_radix::<i128>(&to_radix(i128::MIN, base).unwrap(), base),
+                Ok(i128::MIN)
+            );
+            assert_eq!(
+                from_radix::<u128>(&to_radix(u128::MAX, base).unwrap(), base),
+                Ok(u128::MAX)
+            );
+        }
+    }
+
+    #[test]
+    fn prefixes_and_grouping() {
+        let binary = Radix::new(2).unwrap().with_prefix().grouped(4);
+        assert_eq!(binary.format(-10_i8), "-0b1010");
+        assert_eq!(binary.format(0xab_u8), "0b1010_1011");
+        assert_eq!(binary.format(0x1ab_u16), "0b1_1010_1011");
+        assert_eq!(binary.parse::<i16>("-0b1_1010_1011"), Ok(-0x1ab));
+
+        assert_eq!(from_radix::<u32>("0x30_39", 16), Ok(12345));
+        assert_eq!(from_radix::<u32>("0X3039", 16), Ok(12345));
+        assert_eq!(from_radix::<u32>("0o30071", 8), Ok(12345));
+        // `0b` is only a prefix in base 2, in base 16 it is two digits
+        assert_eq!(from_radix::<u32>("0b1", 16), Ok(0xb1));
+        assert_eq!(to_radix(0, 7).unwrap(), "0");
+    }
+
+    #[test]
+    fn errors_instead_of_panics() {
+        assert_eq!(from_radix::<u8>("256", 10), Err(RadixError::OutOfRange));
+        assert_eq!(from_radix::<i8>("-129", 10), Err(RadixError::OutOfRange));
+        assert_eq!(from_radix::<u8>("-1", 10), Err(RadixError::OutOfRange));
+        assert_eq!(from_radix::<u8>("-0", 10), Ok(0));
+        assert_eq!(from_radix::<u32>("", 10), Err(RadixError::Empty));
+        assert_eq!(from_radix::<u32>("0x", 16), Err(RadixError::Empty));
+        assert_eq!(
+            from_radix::<u32>("12a", 10),
+            Err(RadixError::InvalidDigit {
+                digit: 'a',
+                index: 2
+            })
+        );
+        assert_eq!(
+            from_radix::<u32>("_1", 10),
+            Err(RadixError::InvalidDigit {
+                digit: '_',
+                index: 0
+            })
+        );
+        assert_eq!(from_radix::<u32>("1", 37), Err(RadixError::InvalidBase(37)));
+        assert_eq!(to_radix(1, 1), Err(RadixError::InvalidBase(1)));
+        assert_eq!(Radix::new(0), Err(RadixError::InvalidBase(0)));
+    }
+
+    #[test]
+    fn custom_alphabets() {
+        let crockford = Radix::crockford();
+        assert_eq!(crockford.format(1234567_u32), "15NM7");
+        assert_eq!(crockford.parse::<u32>("15nm7"), Ok(1234567));
+        assert_eq!(crockford.parse::<u32>("IO"), crockford.parse::<u32>("10"));
+
+        let emoji = Radix::custom("○●").unwrap();
+        assert_eq!(emoji.format(5_u8), "●○●");
+        assert!(Radix::custom("aa").is_err());
+        assert!(Radix::custom("0").is_err());
+
+        // upper-casing would make `a` and `A` the same digit
+        let mixed = Radix::custom("0aA").unwrap().uppercase();
+        assert_eq!(mixed.format(5_u8), "aA");
+        assert_eq!(Radix::custom("0a").unwrap().uppercase().format(5_u8), "A0A");
+    }
+}
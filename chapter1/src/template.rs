@@ -0,0 +1,741 @@
+/* Runtime format strings */
+// the same mini-language as `format!`, but parsed at runtime so templates
+// can come from files: `{0}`, `{fruit}`, `{}`, `{:b}`, `{:o}`, `{:x}`,
+// `{:0>5}`, `{:=^21}`, `{:.3}`, `{:+}`, `{:#x}`, `{:05}`, `{:?}`, `{:e}`,
+// with `{{` and `}}` as escapes
+
+// one difference: `format!` writes a negative integer in binary, octal or
+// hex as two's complement at the width of its type, `{:x}` of `-1i32` is
+// `ffffffff`. arguments are widened to i128 and that width is lost, so a
+// negative argument to those is an error instead
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// A dynamic argument. Integers of every width are widened to `i128`, except
+/// `u128`s above `i128::MAX`, which are kept as they are.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i128),
+    UInt(u128),
+    Float(f64),
+    Str(String),
+}
+
+macro_rules! value_from_int {
+    ($($int:ty),+) => {
+        $(
+            impl From<$int> for Value {
+                fn from(n: $int) -> Value {
+                    Value::Int(n.into())
+                }
+            }
+        )+
+    };
+    // no `From` into i128 exists for these, but pointers are at most 64 bits
+    (as $($int:ty),+) => {
+        $(
+            impl From<$int> for Value {
+                fn from(n: $int) -> Value {
+                    Value::Int(n as i128)
+                }
+            }
+        )+
+    };
+}
+
+value_from_int!(i8, i16, i32, i64, i128, u8, u16, u32, u64);
+value_from_int!(as isize, usize);
+
+impl From<u128> for Value {
+    fn from(n: u128) -> Value {
+        i128::try_from(n).map_or(Value::UInt(n), Value::Int)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(x: f32) -> Value {
+        Value::Float(x.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Value {
+        Value::Float(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::Str(s.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::Str(s)
+    }
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) | Value::UInt(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::Str(_) => "a string",
+        }
+    }
+}
+
+/// Positional and named arguments for `Template::render`.
+#[derive(Debug, Default, Clone)]
+pub struct Args {
+    positional: Vec<Value>,
+    named: HashMap<String, Value>,
+}
+
+impl Args {
+    pub fn new() -> Args {
+        Args::default()
+    }
+
+    /// Appends the next positional argument.
+    pub fn arg(mut self, value: impl Into<Value>) -> Args {
+        self.positional.push(value.into());
+        self
+    }
+
+    pub fn named(mut self, name: &str, value: impl Into<Value>) -> Args {
+        self.named.insert(name.to_owned(), value.into());
+        self
+    }
+}
+
+impl From<Vec<Value>> for Args {
+    fn from(positional: Vec<Value>) -> Args {
+        Args {
+            positional,
+            named: HashMap::new(),
+        }
+    }
+}
+
+impl From<HashMap<String, Value>> for Args {
+    fn from(named: HashMap<String, Value>) -> Args {
+        Args {
+            positional: Vec::new(),
+            named,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// A `{` without its `}`.
+    UnclosedField,
+    /// A `}` which neither closes a field nor is escaped as `}}`.
+    UnmatchedBrace,
+    InvalidArgument(String),
+    InvalidNumber(String),
+    MissingPrecision,
+    UnknownType(String),
+    MissingPositional(usize),
+    MissingNamed(String),
+    /// e.g. `{:x}` applied to a string.
+    Unsupported {
+        value: &'static str,
+        spec: &'static str,
+    },
+}
+
+/// An error in a template, located by 1-based line and column (in characters).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnclosedField => write!(f, "unclosed `{{`, use `{{{{` for a literal brace"),
+            ErrorKind::UnmatchedBrace => {
+                write!(f, "unmatched `}}`, use `}}}}` for a literal brace")
+            }
+            ErrorKind::InvalidArgument(arg) => write!(f, "invalid argument name `{}`", arg),
+            ErrorKind::InvalidNumber(number) => write!(f, "number `{}` is too large", number),
+            ErrorKind::MissingPrecision => write!(f, "expected a precision after `.`"),
+            ErrorKind::UnknownType(ty) => write!(f, "unknown format type `{}`", ty),
+            ErrorKind::MissingPositional(index) => {
+                write!(f, "no positional argument at index {}", index)
+            }
+            ErrorKind::MissingNamed(name) => write!(f, "no argument named `{}`", name),
+            ErrorKind::Unsupported { value, spec } => {
+                write!(f, "cannot format {} with `{}`", value, spec)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Display,
+    Debug,
+    Binary,
+    Octal,
+    LowerHex,
+    UpperHex,
+    LowerExp,
+    UpperExp,
+}
+
+impl Kind {
+    fn is_radix(self) -> bool {
+        matches!(
+            self,
+            Kind::Binary | Kind::Octal | Kind::LowerHex | Kind::UpperHex
+        )
+    }
+
+    fn spec(self) -> &'static str {
+        match self {
+            Kind::Display => "{}",
+            Kind::Debug => "{:?}",
+            Kind::Binary => "{:b}",
+            Kind::Octal => "{:o}",
+            Kind::LowerHex => "{:x}",
+            Kind::UpperHex => "{:X}",
+            Kind::LowerExp => "{:e}",
+            Kind::UpperExp => "{:E}",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Spec {
+    fill: char,
+    align: Option<Align>,
+    plus: bool,
+    alternate: bool,
+    zero: bool,
+    width: Option<usize>,
+    precision: Option<usize>,
+    kind: Kind,
+}
+
+impl Default for Spec {
+    fn default() -> Spec {
+        Spec {
+            fill: ' ',
+            align: None,
+            plus: false,
+            alternate: false,
+            zero: false,
+            width: None,
+            precision: None,
+            kind: Kind::Display,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field {
+        arg: Arg,
+        spec: Spec,
+        // position of the opening `{`
+        line: usize,
+        column: usize,
+    },
+}
+
+/// A parsed format string, which can be rendered any number of times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    // implicit `{}` fields take the positional arguments in order
+    next_positional: usize,
+}
+
+impl Parser {
+    fn error(&self, index: usize, kind: ErrorKind) -> Error {
+        let (line, column) = self.position(index);
+        Error { line, column, kind }
+    }
+
+    fn position(&self, index: usize) -> (usize, usize) {
+        let before = &self.chars[..index];
+        let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+        let line_start = before
+            .iter()
+            .rposition(|&c| c == '\n')
+            .map_or(0, |newline| newline + 1);
+        (line, index - line_start + 1)
+    }
+
+    fn text(&self, start: usize, end: usize) -> String {
+        self.chars[start..end].iter().collect()
+    }
+
+    fn number(&self, start: &mut usize, end: usize) -> Result<Option<usize>, Error> {
+        let first = *start;
+        while *start < end && self.chars[*start].is_ascii_digit() {
+            *start += 1;
+        }
+        if first == *start {
+            return Ok(None);
+        }
+        let digits = self.text(first, *start);
+        digits
+            .parse()
+            .map(Some)
+            .map_err(|_| self.error(first, ErrorKind::InvalidNumber(digits)))
+    }
+
+    fn arg(&mut self, start: usize, end: usize) -> Result<Arg, Error> {
+        let name = self.text(start, end);
+        if name.is_empty() {
+            self.next_positional += 1;
+            return Ok(Arg::Index(self.next_positional - 1));
+        }
+        if name.chars().all(|c| c.is_ascii_digit()) {
+            return name
+                .parse()
+                .map(Arg::Index)
+                .map_err(|_| self.error(start, ErrorKind::InvalidNumber(name)));
+        }
+        let mut chars = name.chars();
+        let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_');
+        if valid {
+            Ok(Arg::Name(name))
+        } else {
+            Err(self.error(start, ErrorKind::InvalidArgument(name)))
+        }
+    }
+
+    /// `[[fill]align][+][#][0][width][.precision][type]`, between `start` and `end`.
+    fn spec(&self, mut p: usize, end: usize) -> Result<Spec, Error> {
+        let align_of = |c: char| match c {
+            '<' => Some(Align::Left),
+            '^' => Some(Align::Center),
+            '>' => Some(Align::Right),
+            _ => None,
+        };
+        let mut spec = Spec::default();
+
+        if let Some(align) = self.chars[p..end].get(1).copied().and_then(align_of) {
+            spec.fill = self.chars[p];
+            spec.align = Some(align);
+            p += 2;
+        } else if let Some(align) = self.chars[p..end].first().copied().and_then(align_of) {
+            spec.align = Some(align);
+            p += 1;
+        }
+        if p < end && self.chars[p] == '+' {
+            spec.plus = true;
+            p += 1;
+        }
+        if p < end && self.chars[p] == '#' {
+            spec.alternate = true;
+            p += 1;
+        }
+        if p < end && self.chars[p] == '0' {
+            spec.zero = true;
+            p += 1;
+        }
+        spec.width = self.number(&mut p, end)?;
+        if p < end && self.chars[p] == '.' {
+            p += 1;
+            spec.precision = self.number(&mut p, end)?;
+            if spec.precision.is_none() {
+                return Err(self.error(p, ErrorKind::MissingPrecision));
+            }
+        }
+
+        spec.kind = match self.text(p, end).as_str() {
+            "" => Kind::Display,
+            "?" => Kind::Debug,
+            "b" => Kind::Binary,
+            "o" => Kind::Octal,
+            "x" => Kind::LowerHex,
+            "X" => Kind::UpperHex,
+            "e" => Kind::LowerExp,
+            "E" => Kind::UpperExp,
+            other => return Err(self.error(p, ErrorKind::UnknownType(other.to_owned()))),
+        };
+        Ok(spec)
+    }
+
+    fn parse(mut self) -> Result<Template, Error> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut i = 0;
+
+        while i < self.chars.len() {
+            let next = self.chars.get(i + 1).copied();
+            match self.chars[i] {
+                '{' if next == Some('{') => {
+                    literal.push('{');
+                    i += 2;
+                }
+                '{' => {
+                    let close = self.chars[i + 1..]
+                        .iter()
+                        .position(|&c| c == '}')
+                        .map(|offset| i + 1 + offset)
+                        .ok_or_else(|| self.error(i, ErrorKind::UnclosedField))?;
+                    let colon = self.chars[i + 1..close]
+                        .iter()
+                        .position(|&c| c == ':')
+                        .map(|offset| i + 1 + offset);
+
+                    let arg = self.arg(i + 1, colon.unwrap_or(close))?;
+                    let spec = match colon {
+                        Some(colon) => self.spec(colon + 1, close)?,
+                        None => Spec::default(),
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    let (line, column) = self.position(i);
+                    segments.push(Segment::Field {
+                        arg,
+                        spec,
+                        line,
+                        column,
+                    });
+                    i = close + 1;
+                }
+                '}' if next == Some('}') => {
+                    literal.push('}');
+                    i += 2;
+                }
+                '}' => return Err(self.error(i, ErrorKind::UnmatchedBrace)),
+                c => {
+                    literal.push(c);
+                    i += 1;
+                }
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Template { segments })
+    }
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, Error> {
+        Parser {
+            chars: source.chars().collect(),
+            next_positional: 0,
+        }
+        .parse()
+    }
+
+    pub fn render(&self, args: &Args) -> Result<String, Error> {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => rendered.push_str(text),
+                Segment::Field {
+                    arg,
+                    spec,
+                    line,
+                    column,
+                } => {
+                    let error = |kind| Error {
+                        line: *line,
+                        column: *column,
+                        kind,
+                    };
+                    let value = match arg {
+                        Arg::Index(index) => args
+                            .positional
+                            .get(*index)
+                            .ok_or_else(|| error(ErrorKind::MissingPositional(*index)))?,
+                        Arg::Name(name) => args
+                            .named
+                            .get(name)
+                            .ok_or_else(|| error(ErrorKind::MissingNamed(name.clone())))?,
+                    };
+                    rendered.push_str(&format_value(spec, value).map_err(error)?);
+                }
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+/// Parses and renders in one go.
+pub fn format(source: &str, args: &Args) -> Result<String, Error> {
+    Template::parse(source)?.render(args)
+}
+
+fn format_value(spec: &Spec, value: &Value) -> Result<String, ErrorKind> {
+    let unsupported = || ErrorKind::Unsupported {
+        value: value.type_name(),
+        spec: spec.kind.spec(),
+    };
+
+    // sign, radix prefix and digits are kept apart so that `0` can pad between them
+    let (negative, prefix, digits) = match *value {
+        Value::Int(n) if n < 0 && spec.kind.is_radix() => {
+            return Err(ErrorKind::Unsupported {
+                value: "a negative integer",
+                spec: spec.kind.spec(),
+            })
+        }
+        Value::Int(n) => integer(spec, n < 0, n.unsigned_abs()),
+        Value::UInt(n) => integer(spec, false, n),
+        Value::Float(x) => {
+            let magnitude = x.abs();
+            let digits = match (spec.kind, spec.precision) {
+                (Kind::Display, Some(precision)) | (Kind::Debug, Some(precision)) => {
+                    format!("{:.*}", precision, magnitude)
+                }
+                (Kind::Display, None) => format!("{}", magnitude),
+                (Kind::Debug, None) => format!("{:?}", magnitude),
+                (Kind::LowerExp, Some(precision)) => format!("{:.*e}", precision, magnitude),
+                (Kind::LowerExp, None) => format!("{:e}", magnitude),
+                (Kind::UpperExp, Some(precision)) => format!("{:.*E}", precision, magnitude),
+                (Kind::UpperExp, None) => format!("{:E}", magnitude),
+                _ => return Err(unsupported()),
+            };
+            (x.is_sign_negative() && !x.is_nan(), "", digits)
+        }
+        Value::Str(ref s) => {
+            let text = match spec.kind {
+                Kind::Display => match spec.precision {
+                    Some(precision) => s.chars().take(precision).collect(),
+                    None => s.clone(),
+                },
+                Kind::Debug => format!("{:?}", s),
+                _ => return Err(unsupported()),
+            };
+            return Ok(pad(spec, &text, Align::Left));
+        }
+    };
+
+    // `format!` never signs a NaN, even with `+`
+    let nan = matches!(value, Value::Float(x) if x.is_nan());
+    let sign = if negative {
+        "-"
+    } else if spec.plus && !nan {
+        "+"
+    } else {
+        ""
+    };
+    if spec.zero {
+        // like `format!`, zero padding goes after the sign and ignores fill and alignment
+        let len = sign.len() + prefix.len() + digits.chars().count();
+        let zeros = spec.width.unwrap_or(0).saturating_sub(len);
+        return Ok(format!("{}{}{}{}", sign, prefix, "0".repeat(zeros), digits));
+    }
+    Ok(pad(
+        spec,
+        &format!("{}{}{}", sign, prefix, digits),
+        Align::Right,
+    ))
+}
+
+/// The sign, radix prefix and digits of an integer.
+fn integer(spec: &Spec, negative: bool, magnitude: u128) -> (bool, &'static str, String) {
+    let (prefix, digits) = match spec.kind {
+        Kind::Display | Kind::Debug => ("", magnitude.to_string()),
+        Kind::Binary => ("0b", format!("{:b}", magnitude)),
+        Kind::Octal => ("0o", format!("{:o}", magnitude)),
+        Kind::LowerHex => ("0x", format!("{:x}", magnitude)),
+        Kind::UpperHex => ("0x", format!("{:X}", magnitude)),
+        Kind::LowerExp => ("", format!("{:e}", magnitude)),
+        Kind::UpperExp => ("", format!("{:E}", magnitude)),
+    };
+    (negative, if spec.alternate { prefix } else { "" }, digits)
+}
+
+fn pad(spec: &Spec, text: &str, default: Align) -> String {
+    let len = text.chars().count();
+    let padding = spec.width.unwrap_or(0).saturating_sub(len);
+    let (before, after) = match spec.align.unwrap_or(default) {
+        Align::Left => (0, padding),
+        Align::Center => (padding / 2, padding - padding / 2),
+        Align::Right => (padding, 0),
+    };
+    let fill = |count| std::iter::repeat_n(spec.fill, count).collect::<String>();
+    format!("{}{}{}", fill(before), text, fill(after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_chapter1_specifiers() {
+        let args = Args::new().arg("Alice").arg("Bob");
+        assert_eq!(
+            format("{0} this is {1}, {1} this is {0}", &args).unwrap(),
+            "Alice this is Bob, Bob this is Alice"
+        );
+        let args = Args::new()
+            .named("fruit", "mango")
+            .named("creature", "monkey");
+        assert_eq!(
+            format("{fruit} eaten by {creature}", &args).unwrap(),
+            "mango eaten by monkey"
+        );
+
+        let radix = Args::new().arg(12345);
+        assert_eq!(
+            format("{} {:b} {:o} {:x}", &Args::from(vec![Value::Int(12345); 4])).unwrap(),
+            "12345 11000000111001 30071 3039"
+        );
+        assert_eq!(format("{0:#X}", &radix).unwrap(), "0x3039");
+
+        let one = Args::new().named("number", 1);
+        assert_eq!(format("{number:>5}", &one).unwrap(), "    1");
+        assert_eq!(format("{number:0>5}", &one).unwrap(), "00001");
+        assert_eq!(format("{number:0<5}", &one).unwrap(), "10000");
+        assert_eq!(
+            format("|{:=^21}|", &Args::new().arg("heading")).unwrap(),
+            "|=======heading=======|"
+        );
+        assert_eq!(
+            format(
+                "{0} is {1:.3}",
+                &Args::new().arg("pi").arg(std::f64::consts::PI)
+            )
+            .unwrap(),
+            "pi is 3.142"
+        );
+        assert_eq!(
+            format("{{escaped}} {}", &Args::new().arg(1)).unwrap(),
+            "{escaped} 1"
+        );
+    }
+
+    #[test]
+    fn matches_format_macro() {
+        let cases: Vec<(&str, Value, String)> = vec![
+            ("{:+}", 5.into(), std::format!("{:+}", 5)),
+            ("{:05}", (-42).into(), std::format!("{:05}", -42)),
+            ("{:#010b}", 5.into(), std::format!("{:#010b}", 5)),
+            ("{:<6}|", 7.into(), std::format!("{:<6}|", 7)),
+            ("{:^7.2}", (-1.5).into(), std::format!("{:^7.2}", -1.5)),
+            ("{:?}", 1.0.into(), std::format!("{:?}", 1.0)),
+            ("{:e}", 1234.5.into(), std::format!("{:e}", 1234.5)),
+            ("{:.2E}", 0.00123.into(), std::format!("{:.2E}", 0.00123)),
+            ("{:?}", "a\"b".into(), std::format!("{:?}", "a\"b")),
+            (
+                "{:*<6.3}",
+                "abcdef".into(),
+                std::format!("{:*<6.3}", "abcdef"),
+            ),
+            ("{:x}", u64::MAX.into(), std::format!("{:x}", u64::MAX)),
+            ("{:+}", f64::NAN.into(), std::format!("{:+}", f64::NAN)),
+            (
+                "{:+05}",
+                (-f64::NAN).into(),
+                std::format!("{:+05}", -f64::NAN),
+            ),
+            (
+                "{:+}",
+                f64::INFINITY.into(),
+                std::format!("{:+}", f64::INFINITY),
+            ),
+            ("{}", u128::MAX.into(), std::format!("{}", u128::MAX)),
+            ("{:#x}", u128::MAX.into(), std::format!("{:#x}", u128::MAX)),
+            ("{:+}", usize::MAX.into(), std::format!("{:+}", usize::MAX)),
+            (
+                "{:05}",
+                isize::MIN.into(),
+                std::format!("{:05}", isize::MIN),
+            ),
+        ];
+        for (template, value, expected) in cases {
+            let rendered = format(template, &Args::from(vec![value])).unwrap();
+            assert_eq!(rendered, expected, "{}", template);
+        }
+        // only what does not fit an i128 stays unsigned
+        assert_eq!(Value::from(5u128), Value::from(5));
+        assert_eq!(Value::from(7usize), Value::Int(7));
+    }
+
+    #[test]
+    fn errors_point_at_the_column() {
+        let error = |source: &str| Template::parse(source).unwrap_err();
+        assert_eq!(
+            error("{0} is {1:.3"),
+            Error {
+                line: 1,
+                column: 8,
+                kind: ErrorKind::UnclosedField
+            }
+        );
+        assert_eq!(error("a }").column, 3);
+        assert_eq!(error("a }").kind, ErrorKind::UnmatchedBrace);
+        assert_eq!(error("{:.}").kind, ErrorKind::MissingPrecision);
+        assert_eq!(
+            error("first\n{x:>5q}"),
+            Error {
+                line: 2,
+                column: 6,
+                kind: ErrorKind::UnknownType(String::from("q"))
+            }
+        );
+        assert_eq!(
+            error("{1a}").kind,
+            ErrorKind::InvalidArgument(String::from("1a"))
+        );
+
+        let missing = format("ok {2}", &Args::new().arg(1)).unwrap_err();
+        assert_eq!(missing.kind, ErrorKind::MissingPositional(2));
+        assert_eq!(missing.column, 4);
+        assert_eq!(
+            format("{:b}", &Args::new().arg("text"))
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 1: cannot format a string with `{:b}`"
+        );
+        // `format!` would print the two's complement, `ffffffff`
+        assert_eq!(
+            format("{:x}", &Args::new().arg(-1)).unwrap_err().kind,
+            ErrorKind::Unsupported {
+                value: "a negative integer",
+                spec: "{:x}"
+            }
+        );
+        assert_eq!(format("{:e}", &Args::new().arg(-5)).unwrap(), "-5e0");
+    }
+}
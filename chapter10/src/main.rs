@@ -1,4 +1,6 @@
 #![allow(dead_code)]
+use std::io::{self, Write};
+
 // modules
 
 // only public items can be accessed by other modules
@@ -6,64 +8,81 @@
 use crate::{my_mod::nested::function, my_new_mod::ClosedBox};
 
 mod my_mod {
-    fn private_function() {
-        println!("private function called");
+
+    use std::io::{self, Write};
+
+    fn private_function(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "private function called")?;
+        Ok(())
     }
 
-    pub fn public_function() {
-        println!("public function called");
+    pub fn public_function(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "public function called")?;
+        Ok(())
     }
-    pub fn indirect_access() {
-        println!("Get ready for indirectly accessed call");
-        private_function();
+    pub fn indirect_access(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "Get ready for indirectly accessed call")?;
+        private_function(out)?;
+        Ok(())
     }
 
     // nested module
     pub mod nested {
-        pub fn function() {
-            println!("public function from nested module called");
+        use std::io::{self, Write};
+
+        pub fn function(out: &mut dyn Write) -> io::Result<()> {
+            writeln!(out, "public function from nested module called")?;
+            Ok(())
         }
         #[allow(dead_code)]
-        fn private_function() {
-            println!("called my_mod::nested::private_function()");
+        fn private_function(out: &mut dyn Write) -> io::Result<()> {
+            writeln!(out, "called my_mod::nested::private_function()")?;
+            Ok(())
         }
 
         // we can specify if function is public only at a given path
-        pub(in crate::my_mod) fn public_function_in_my_mod() {
-            println!("Called public function in my_mod::nested");
-            println!("Another call:-");
-            public_yet_private_function();
+        pub(in crate::my_mod) fn public_function_in_my_mod(out: &mut dyn Write) -> io::Result<()> {
+            writeln!(out, "Called public function in my_mod::nested")?;
+            writeln!(out, "Another call:-")?;
+            public_yet_private_function(out)?;
+            Ok(())
         }
 
         // pub(self) is same as private (default)
-        pub(self) fn public_yet_private_function() {
-            println!("I am public function but only in my own module");
+        #[allow(clippy::needless_pub_self)]
+        pub(self) fn public_yet_private_function(out: &mut dyn Write) -> io::Result<()> {
+            writeln!(out, "I am public function but only in my own module")?;
+            Ok(())
         }
 
-        pub(super) fn public_function_for_super() {
-            println!("called my_mod::nested::public_function_for_super()");
+        pub(super) fn public_function_for_super(out: &mut dyn Write) -> io::Result<()> {
+            writeln!(out, "called my_mod::nested::public_function_for_super()")?;
+            Ok(())
         }
     }
 
-    pub fn call_public_functions() {
-        println!("Calling some public functions");
-        nested::public_function_in_my_mod();
-        println!(">");
-        nested::public_function_for_super();
+    pub fn call_public_functions(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "Calling some public functions")?;
+        nested::public_function_in_my_mod(out)?;
+        writeln!(out, ">")?;
+        nested::public_function_for_super(out)?;
+        Ok(())
     }
 
-    pub(crate) fn public_function_for_crate() {
-        println!("called function public for crate only");
+    pub(crate) fn public_function_for_crate(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "called function public for crate only")?;
+        Ok(())
     }
 }
 
-fn part1() {
-    my_mod::call_public_functions();
-    my_mod::indirect_access();
-    my_mod::public_function();
-    my_mod::public_function_for_crate();
-    my_mod::nested::function();
-    // my_mod::nested::public_yet_private_function();
+fn part1(out: &mut dyn Write) -> io::Result<()> {
+    my_mod::call_public_functions(out)?;
+    my_mod::indirect_access(out)?;
+    my_mod::public_function(out)?;
+    my_mod::public_function_for_crate(out)?;
+    my_mod::nested::function(out)?;
+    // my_mod::nested::public_yet_private_function(out)?;
+    Ok(())
 }
 
 // structs visibility
@@ -85,15 +104,16 @@ mod my_new_mod {
     }
 }
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let open_box = my_new_mod::OpenBox {
         contents: "public info",
     };
-    println!("OpenBox has {}", open_box.contents);
+    writeln!(out, "OpenBox has {}", open_box.contents)?;
 
     // cant initialize closed box directly from struct
     // also cannot access
     let _closed_box = ClosedBox::new("private data");
+    Ok(())
 }
 
 // use declaration
@@ -101,48 +121,60 @@ fn part2() {
 
 mod deeply {
     pub mod nested {
-        pub fn function() {
-            println!("I am a deeply nested function!");
+        use std::io::{self, Write};
+
+        pub fn function(out: &mut dyn Write) -> io::Result<()> {
+            writeln!(out, "I am a deeply nested function!")?;
+            Ok(())
         }
     }
 }
 
 use deeply::nested::function as another_function;
 
-fn part3() {
-    another_function();
-    println!("Entering block");
+fn part3(out: &mut dyn Write) -> io::Result<()> {
+    another_function(out)?;
+    writeln!(out, "Entering block")?;
     {
         use crate::deeply::nested::function;
         // use bindings have local scope
-        function();
+        function(out)?;
     }
-    println!("Left block");
-    function(); // different function is called
+    writeln!(out, "Left block")?;
+    function(out)?; // different function is called
+    Ok(())
 }
 
 // super and self -> to prevent hardcoding of paths
 
-fn cool_function() {
-    println!("A cool function was called");
+fn cool_function(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "A cool function was called")?;
+    Ok(())
 }
 
 mod cool {
-    pub fn cool_function() {
-        println!("Called cool::cool_function()");
-        println!("Calling my parent's function");
-        super::cool_function();
+
+    use std::io::{self, Write};
+
+    pub fn cool_function(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "Called cool::cool_function()")?;
+        writeln!(out, "Calling my parent's function")?;
+        super::cool_function(out)?;
+        Ok(())
     }
 }
 
-fn part4() {
-    self::cool_function();
-    cool::cool_function();
+fn part4(out: &mut dyn Write) -> io::Result<()> {
+    self::cool_function(out)?;
+    cool::cool_function(out)?;
+    Ok(())
 }
 
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    part4();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "visibility of functions in nested modules",
+        part2 => "struct visibility: OpenBox and ClosedBox",
+        part3 => "use declarations and renaming with as",
+        part4 => "super and self",
+    ])
 }
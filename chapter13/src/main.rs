@@ -1,20 +1,29 @@
+use std::io::{self, Write};
+
 #[cfg(target_os = "linux")]
-fn are_you_on_linux() {
-    println!("You are running linux");
+fn are_you_on_linux(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "You are running linux")
 }
 
 #[cfg(not(target_os = "linux"))]
-fn are_you_on_linux() {
-    println!("You are not on linux");
+fn are_you_on_linux(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "You are not on linux")
 }
 
-fn main() {
-    println!("Hello, world!");
-    are_you_on_linux();
-    println!("Are you sure?");
+fn part1(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "Hello, world!")?;
+    are_you_on_linux(out)?;
+    writeln!(out, "Are you sure?")?;
     if cfg!(target_os = "linux") {
-        println!("Certainly I am on linux");
+        writeln!(out, "Certainly I am on linux")?;
     } else {
-        println!("Certainly I am not on linux");
+        writeln!(out, "Certainly I am not on linux")?;
     }
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "conditional compilation with cfg attributes and cfg!",
+    ])
 }
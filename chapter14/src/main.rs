@@ -1,6 +1,8 @@
+#![allow(dead_code)]
 // generics
 
 use std::fmt::Display;
+use std::io::{self, Write};
 
 struct A;
 
@@ -9,7 +11,7 @@ struct Single(A);
 struct SingleGen<T>(T);
 
 // generic structs
-fn part1() {
+fn part1(_out: &mut dyn Write) -> io::Result<()> {
     let _s = Single(A);
 
     let _char: SingleGen<char> = SingleGen('a');
@@ -17,6 +19,7 @@ fn part1() {
     let _t = SingleGen(A);
     let _i32 = SingleGen(6);
     let _char = SingleGen('c');
+    Ok(())
 }
 
 // generic functions
@@ -29,12 +32,13 @@ fn gen_spec_i32(_s: SGen<i32>) {}
 
 fn gen_spec_generic<T>(_s: SGen<T>) {}
 
-fn part2() {
+fn part2(_out: &mut dyn Write) -> io::Result<()> {
     reg_fn(S(A));
     gen_spec_i32(SGen(12));
 
     gen_spec_generic::<char>(SGen('a'));
-    gen_spec_generic(SGen('a'))
+    gen_spec_generic(SGen('a'));
+    Ok(())
 }
 
 // generic implementation
@@ -68,11 +72,12 @@ impl<T> GenVal<T> {
     }
 }
 
-fn part3() {
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     let x = Val { val: 3.0 };
     let y = GenVal { gen_val: 3i32 };
 
-    println!("{} {}", x.value(), y.value());
+    writeln!(out, "{} {}", x.value(), y.value())?;
+    Ok(())
 }
 
 // generic traits
@@ -88,18 +93,20 @@ impl<T, U> DoubleDrop<T> for U {
     fn double_drop(self, _: T) {}
 }
 
-fn part4() {
+fn part4(_out: &mut dyn Write) -> io::Result<()> {
     let empty = Empty;
     let null = Null;
 
     empty.double_drop(null);
     // deallocates empty and null both
     // null; // error
+    Ok(())
 }
 
 // bounds
-fn printer<T: Display>(t: T) {
-    println!("{}", t);
+fn printer<T: Display>(out: &mut dyn Write, t: T) -> io::Result<()> {
+    writeln!(out, "{}", t)?;
+    Ok(())
 }
 
 struct Str<T: Display>(T);
@@ -118,37 +125,70 @@ impl HasArea for Rectangle {
     }
 }
 
+// base `length` and perpendicular `height`
+impl HasArea for Triangle {
+    fn area(&self) -> f64 {
+        self.length * self.height / 2.0
+    }
+}
+
+// a foreign type can implement our trait
+impl HasArea for geometry::Polygon {
+    fn area(&self) -> f64 {
+        geometry::Polygon::area(self)
+    }
+}
+
 #[derive(Debug)]
 struct Rectangle {
     length: f64,
     height: f64,
 }
 
+#[derive(Debug)]
 struct Triangle {
     length: f64,
     height: f64,
 }
 
-fn print_debug<T: Debug>(t: &T) {
-    println!("{:?}", t);
+fn print_debug<T: Debug>(out: &mut dyn Write, t: &T) -> io::Result<()> {
+    writeln!(out, "{:?}", t)?;
+    Ok(())
 }
 
 fn area<T: HasArea>(t: &T) -> f64 {
     t.area()
 }
 
-fn part5() {
+fn part5(out: &mut dyn Write) -> io::Result<()> {
     // let s = Str(vec![1]); // not allowed because vector does not implement Display trait
     let rectangle = Rectangle {
         length: 3.0,
         height: 4.0,
     };
-    let _triangle = Triangle {
+    let triangle = Triangle {
         length: 3.0,
         height: 4.0,
     };
-    print_debug(&rectangle);
-    println!("Area = {}", area(&rectangle));
+    print_debug(out, &rectangle)?;
+    writeln!(out, "Area = {}", area(&rectangle))?;
+    print_debug(out, &triangle)?;
+    writeln!(out, "Area = {}", area(&triangle))?;
+
+    // trait objects mix the shapes in one list
+    let Ok(pentagon) = geometry::Polygon::new(
+        [(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (2.0, 5.0), (0.0, 3.0)]
+            .into_iter()
+            .map(geometry::Point::from)
+            .collect(),
+    ) else {
+        return Ok(());
+    };
+    writeln!(out, "Polygon {} has area {}", pentagon, area(&pentagon))?;
+    let shapes: [&dyn HasArea; 3] = [&rectangle, &triangle, &pentagon];
+    let total: f64 = shapes.iter().map(|shape| shape.area()).sum();
+    writeln!(out, "Total area = {}", total)?;
+    Ok(())
 }
 
 // empty bounds
@@ -170,36 +210,40 @@ fn blue<T: Blue>(_: &T) -> &'static str {
     "blue"
 }
 
-fn part6() {
+fn part6(out: &mut dyn Write) -> io::Result<()> {
     let cardinal = Cardinal;
     let blue_jay = BlueJay;
     let _turkey = Turkey;
 
-    println!("A cardinal is {}", red(&cardinal));
-    println!("A blue jay is {}", blue(&blue_jay));
+    writeln!(out, "A cardinal is {}", red(&cardinal))?;
+    writeln!(out, "A blue jay is {}", blue(&blue_jay))?;
+    Ok(())
 }
 
 // multiple bounds
 // applied using `+`
 
-fn compare_prints<T: Debug + Display>(t: &T) {
-    println!("Debug: `{:?}`", t);
-    println!("Display: `{}`", t);
+fn compare_prints<T: Debug + Display>(out: &mut dyn Write, t: &T) -> io::Result<()> {
+    writeln!(out, "Debug: `{:?}`", t)?;
+    writeln!(out, "Display: `{}`", t)?;
+    Ok(())
 }
 
-fn compare_types<T: Debug, U: Debug>(t: &T, u: &U) {
-    println!("t: {:?}", t);
-    println!("u: {:?}", u);
+fn compare_types<T: Debug, U: Debug>(out: &mut dyn Write, t: &T, u: &U) -> io::Result<()> {
+    writeln!(out, "t: {:?}", t)?;
+    writeln!(out, "u: {:?}", u)?;
+    Ok(())
 }
 
-fn part7() {
+fn part7(out: &mut dyn Write) -> io::Result<()> {
     let string = "words";
     let array = [1, 2, 3];
     let vec = vec![1, 2, 3];
-    compare_prints(&string);
+    compare_prints(out, &string)?;
     // will not work because Display not implemented by array
-    // compare_prints(&array);
-    compare_types(&array, &vec);
+    // compare_prints(out, &array)?;
+    compare_types(out, &array, &vec)?;
+    Ok(())
 }
 
 // where clause
@@ -225,21 +269,22 @@ where
 
 // another example
 trait PrintInOption {
-    fn print_in_option(self);
+    fn print_in_option(self, out: &mut dyn Write) -> io::Result<()>;
 }
 
 impl<T> PrintInOption for T
 where
     Option<T>: Debug,
 {
-    fn print_in_option(self) {
-        println!("{:?}", Some(self));
+    fn print_in_option(self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{:?}", Some(self))
     }
 }
 
-fn part8() {
+fn part8(out: &mut dyn Write) -> io::Result<()> {
     let vec = vec![1, 2, 3];
-    vec.print_in_option();
+    vec.print_in_option(out)?;
+    Ok(())
 }
 
 // new type idiom
@@ -263,11 +308,12 @@ fn old_enough(age: &Years) -> bool {
     age.0 >= 18
 }
 
-fn part9() {
+fn part9(out: &mut dyn Write) -> io::Result<()> {
     let age = Years(5);
     let age_days = age.to_days();
-    println!("Old Enough: {}", old_enough(&age));
-    println!("Old Enough: {}", old_enough(&age_days.to_years()));
+    writeln!(out, "Old Enough: {}", old_enough(&age))?;
+    writeln!(out, "Old Enough: {}", old_enough(&age_days.to_years()))?;
+    Ok(())
 }
 
 /* Associated Items */
@@ -303,22 +349,23 @@ where
     container.last() - container.first()
 }
 
-fn part10() {
+fn part10(out: &mut dyn Write) -> io::Result<()> {
     let number_1 = 3;
     let number_2 = 10;
 
     let container = Container(number_1, number_2);
 
-    println!(
+    writeln!(out, 
         "Does container contain {} and {}: {}",
         &number_1,
         &number_2,
         container.contains(&number_1, &number_2)
-    );
-    println!("First number: {}", container.first());
-    println!("last number: {}", container.last());
+    )?;
+    writeln!(out, "First number: {}", container.first())?;
+    writeln!(out, "last number: {}", container.last())?;
 
-    println!("Difference: {}", difference(&container));
+    writeln!(out, "Difference: {}", difference(&container))?;
+    Ok(())
 } */
 
 // associated types will move inner types locally into trait as output types
@@ -354,22 +401,23 @@ fn difference<C: Contains>(container: &C) -> i32 {
     container.last() - container.first()
 }
 
-fn part10() {
+fn part10(out: &mut dyn Write) -> io::Result<()> {
     let number_1 = 3;
     let number_2 = 10;
 
     let container = Container(number_1, number_2);
 
-    println!(
+    writeln!(out, 
         "Does container contain {} and {}: {}",
         &number_1,
         &number_2,
         container.contains(&number_1, &number_2)
-    );
-    println!("First number: {}", container.first());
-    println!("last number: {}", container.last());
+    )?;
+    writeln!(out, "First number: {}", container.first())?;
+    writeln!(out, "last number: {}", container.last())?;
 
-    println!("Difference: {}", difference(&container));
+    writeln!(out, "Difference: {}", difference(&container))?;
+    Ok(())
 }
 
 /* Phantom type parameters */
@@ -389,7 +437,7 @@ struct PhantomStruct<A, B> {
     phantom: PhantomData<B>,
 }
 
-fn part11() {
+fn part11(_out: &mut dyn Write) -> io::Result<()> {
     let _tuple1: PhantomTuple<char, f32> = PhantomTuple('Q', PhantomData);
     let _tuple2: PhantomTuple<char, f64> = PhantomTuple('Q', PhantomData);
 
@@ -405,6 +453,7 @@ fn part11() {
 
     // we cant compare _tuple1 and _tuple2 or _struct1 and _struct 2 at compile time and it will show error;
     // _tuple1 == _tuple2;
+    Ok(())
 }
 
 // example of phantom data
@@ -428,28 +477,96 @@ impl<Unit> Add for Length<Unit> {
     }
 }
 
-fn part12() {
+fn part12(out: &mut dyn Write) -> io::Result<()> {
     let one_foot: Length<Inch> = Length(12.0, PhantomData);
     let one_meter: Length<Mm> = Length(1000.0, PhantomData);
 
     let two_feet = one_foot + one_foot;
     let two_meters = one_meter + one_meter;
 
-    println!("one foot + one_foot = {:?} in", two_feet.0);
-    println!("one meter + one_meter = {:?} mm", two_meters.0);
+    writeln!(out, "one foot + one_foot = {:?} in", two_feet.0)?;
+    writeln!(out, "one meter + one_meter = {:?} mm", two_meters.0)?;
 
     // now there is type safety in such unit conversion
-}
+    Ok(())
+}
+
+/* computational geometry */
+// the shared geometry crate's polygons: shoelace area, centroid, point in
+// polygon, Andrew's monotone chain convex hull, segment intersection and
+// Sutherland-Hodgman clipping against a convex window
+
+fn part13(out: &mut dyn Write) -> io::Result<()> {
+    use geometry::{convex_hull, Intersection, Point, Polygon, Rectangle, Segment};
+
+    let points: Vec<Point<f64>> = [
+        (1.0, 1.0),
+        (4.0, 0.0),
+        (2.0, 2.0),
+        (6.0, 2.0),
+        (5.0, 5.0),
+        (3.0, 3.0),
+        (1.0, 4.0),
+        (0.0, 2.0),
+    ]
+    .into_iter()
+    .map(Point::from)
+    .collect();
+    let Some(hull) = convex_hull(&points) else {
+        writeln!(out, "The points are on one line")?;
+        return Ok(());
+    };
+    writeln!(out, "Convex hull: {}", hull)?;
+    writeln!(out, "Area {}, perimeter {:.3}", hull.area(), hull.perimeter())?;
+    if let Some(centroid) = hull.centroid() {
+        writeln!(out, "Centroid ({:.3}, {:.3})", centroid.x, centroid.y)?;
+    }
+    for p in [Point::new(3.0, 2.0), Point::new(6.0, 2.0), Point::new(6.0, 5.0)] {
+        writeln!(out, "Contains {}: {}", p, hull.contains(p))?;
+    }
+
+    let a = Segment::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0));
+    let b = Segment::new(Point::new(0.0, 4.0), Point::new(4.0, 0.0));
+    let c = Segment::new(Point::new(2.0, 2.0), Point::new(6.0, 6.0));
+    for (first, second) in [(a, b), (a, c), (b, c)] {
+        match first.intersection(&second) {
+            Some(Intersection::Point(p)) => writeln!(out, "{} meets {} at {}", first, second, p)?,
+            Some(Intersection::Overlap(s)) => {
+                writeln!(out, "{} overlaps {} along {}", first, second, s)?
+            }
+            None => writeln!(out, "{} misses {}", first, second)?,
+        }
+    }
 
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    // part6();
-    // part7();
-    // part8();
-    // part9();
-    part10();
+    let window = Polygon::from(Rectangle::new(Point::new(0.0, 0.0), Point::new(3.0, 3.0)));
+    match hull.clip(&window) {
+        Ok(Some(clipped)) => writeln!(
+            out,
+            "Clipped to {}: {:.2}, area {:.3}",
+            window,
+            clipped,
+            clipped.area()
+        )?,
+        Ok(None) => writeln!(out, "Nothing of the hull is inside {}", window)?,
+        Err(e) => writeln!(out, "Cannot clip: {}", e)?,
+    }
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "generic structs",
+        part2 => "generic functions",
+        part3 => "generic implementations",
+        part4 => "generic traits: DoubleDrop",
+        part5 => "trait bounds: HasArea",
+        part6 => "empty bounds",
+        part7 => "multiple bounds",
+        part8 => "where clauses",
+        part9 => "the newtype idiom",
+        part10 => "associated types",
+        part11 => "phantom type parameters",
+        part12 => "unit clarification with phantom types",
+        part13 => "computational geometry: polygons, hulls and clipping",
+    ])
 }
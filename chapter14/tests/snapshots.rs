@@ -0,0 +1,10 @@
+// golden output of every part, see support::snapshot
+
+#[test]
+fn parts_match_snapshots() {
+    support::snapshot::Suite::new(
+        env!("CARGO_BIN_EXE_chapter14"),
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+    )
+    .check();
+}
@@ -1,4 +1,8 @@
 #![allow(dead_code)]
+// the explicit lifetimes below are what this chapter is about
+#![allow(clippy::needless_lifetimes, clippy::extra_unused_lifetimes)]
+use std::io::{self, Write};
+
 /* Scoping Rules */
 
 /* RAII: Resource Acquisition is Initialization */
@@ -9,7 +13,7 @@ fn create_box() {
     // _box1 is destroyed
 }
 
-fn part1() {
+fn part1(_out: &mut dyn Write) -> io::Result<()> {
     let _box2 = Box::new(5i32);
 
     {
@@ -20,20 +24,26 @@ fn part1() {
         create_box();
     }
     // no memory leakage will occur
+    Ok(())
 }
 
 // destructor can be implemented using the Drop trait
 
-struct ToDrop;
-impl Drop for ToDrop {
+// `drop` cannot take extra parameters, so the value holds on to a shared sink
+struct ToDrop<'s, 'out>(&'s support::SharedSink<'out>);
+impl Drop for ToDrop<'_, '_> {
     fn drop(&mut self) {
-        println!("Dropped");
+        writeln!(self.0, "Dropped");
     }
 }
 
-fn part2() {
-    let _x = ToDrop;
-    println!("ToDrop made");
+fn part2(out: &mut dyn Write) -> io::Result<()> {
+    let sink = support::SharedSink::new(out);
+    {
+        let _x = ToDrop(&sink);
+        writeln!(sink, "ToDrop made");
+    }
+    sink.finish()
 }
 
 /* Ownership and Moves */
@@ -42,52 +52,56 @@ fn part2() {
 // 1. assignment e.g. let x = y;
 // 2. passing function arguments e.g. foo(x)
 
-fn destroy_box(_c: Box<i32>) {
-    println!("Destroying the box value");
+#[allow(clippy::boxed_local)]
+fn destroy_box(out: &mut dyn Write, _c: Box<i32>) -> io::Result<()> {
+    writeln!(out, "Destroying the box value")?;
     // owner scope ends, so memory is freed
+    Ok(())
 }
 
-fn part3() {
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     let x = 5_u32; // allocated in stack
     let y = x; // no resources are moved
-    println!("x is {} and y is {}", x, y);
+    writeln!(out, "x is {} and y is {}", x, y)?;
 
     let a = Box::new(5_i32); // allocated in heap
-    println!("a contains {}", a);
+    writeln!(out, "a contains {}", a)?;
 
     let b = a; // move occurs
                // now both a and b point to the same data but b owns the data
 
-    // !Error: println!("a contains {}", a);
+    // !Error: writeln!(out, "a contains {}", a)?;
 
     // function takes ownership of b
-    destroy_box(b);
+    destroy_box(out, b)?;
     // b can no longer be used
+    Ok(())
 }
 
 /* Mutability */
 // mutability can be changed while transferring the data
-fn part4() {
+fn part4(out: &mut dyn Write) -> io::Result<()> {
     let immutable_box = Box::new(5_u32);
 
-    println!("immutable box contains {}", immutable_box);
+    writeln!(out, "immutable box contains {}", immutable_box)?;
 
     // *immutable_box = 4; // no allowed because immutable
 
     // the immutable box is now moved and mutable
     let mut mutable_box = immutable_box;
 
-    println!("mutable box contains {}", mutable_box);
+    writeln!(out, "mutable box contains {}", mutable_box)?;
 
     *mutable_box = 4; // allowed
 
-    println!("mutable box now contains {}", mutable_box);
+    writeln!(out, "mutable box now contains {}", mutable_box)?;
+    Ok(())
 }
 
 /* Partial Moves */
 // we move only a few parts during destructuring. As a result, the whole object cannot be used but the values destructured as ref can be used
 
-fn part5() {
+fn part5(out: &mut dyn Write) -> io::Result<()> {
     #[derive(Debug)]
     struct Person {
         name: String,
@@ -101,16 +115,17 @@ fn part5() {
 
     // `name` is moved but `age` is referenced
     let Person { name, ref age } = person;
-    println!("The person's age is {}", age);
-    println!("The person's name is {}", name);
+    writeln!(out, "The person's age is {}", age)?;
+    writeln!(out, "The person's name is {}", name)?;
 
     // cannot use complete person
-    // println!("The peron struct is {:?}", person); // not allowed
+    // writeln!(out, "The peron struct is {:?}", person)?; // not allowed
 
     // person.age was not moved and can be used
-    println!("Person's age from person struct is {}", person.age);
+    writeln!(out, "Person's age from person struct is {}", person.age)?;
 
     // if age was on stack instead of heap, then there would not have been any need of ref
+    Ok(())
 }
 
 /* Borrowing */
@@ -118,31 +133,34 @@ fn part5() {
 // the compiler ensures that references always point to valid object
 
 // take ownership and destroy
-fn eat_box_i32(boxed_i32: Box<i32>) {
-    println!("Destroying box that contains {}", boxed_i32);
+fn eat_box_i32(out: &mut dyn Write, boxed_i32: Box<i32>) -> io::Result<()> {
+    writeln!(out, "Destroying box that contains {}", boxed_i32)?;
+    Ok(())
 }
 
-fn borrow_i32(borrowed_i32: &i32) {
-    println!("This int is {}", borrowed_i32);
+fn borrow_i32(out: &mut dyn Write, borrowed_i32: &i32) -> io::Result<()> {
+    writeln!(out, "This int is {}", borrowed_i32)?;
+    Ok(())
 }
 
-fn part6() {
+fn part6(out: &mut dyn Write) -> io::Result<()> {
     let boxed_i32 = Box::new(5_i32);
     let stacked_i32 = 6_i32;
 
     // borrow contents of box, ownership remains here
-    borrow_i32(&boxed_i32);
-    borrow_i32(&stacked_i32);
+    borrow_i32(out, &boxed_i32)?;
+    borrow_i32(out, &stacked_i32)?;
 
     {
         let _ref_to_i32: &i32 = &boxed_i32;
-        // eat_box_i32(boxed_i32); //not allowed because inner value is borrowed later in scope
+        // eat_box_i32(out, boxed_i32)?; //not allowed because inner value is borrowed later in scope
 
-        borrow_i32(_ref_to_i32);
+        borrow_i32(out, _ref_to_i32)?;
 
         // ref_to_i32 no longer borrowed
     }
-    eat_box_i32(boxed_i32);
+    eat_box_i32(out, boxed_i32)?;
+    Ok(())
 }
 
 // mutability in borrowing
@@ -155,19 +173,21 @@ struct Book {
     year: u32,
 }
 
-fn borrow_book(book: &Book) {
-    println!(
+fn borrow_book(out: &mut dyn Write, book: &Book) -> io::Result<()> {
+    writeln!(out, 
         "I immutably borrowed {} - {} edition",
         book.title, book.year
-    );
+    )?;
+    Ok(())
 }
 
-fn new_edition(book: &mut Book) {
+fn new_edition(out: &mut dyn Write, book: &mut Book) -> io::Result<()> {
     book.year = 2023;
-    println!("I mutably borrowed {} - {} edition", book.title, book.year);
+    writeln!(out, "I mutably borrowed {} - {} edition", book.title, book.year)?;
+    Ok(())
 }
 
-fn part7() {
+fn part7(out: &mut dyn Write) -> io::Result<()> {
     let immutable_book = Book {
         author: "Douglas Hofstadter",
         title: "Godel, Escher, Bach",
@@ -177,12 +197,13 @@ fn part7() {
     // mutable copy
     let mut mutable_book = immutable_book;
 
-    borrow_book(&immutable_book);
-    borrow_book(&mutable_book);
+    borrow_book(out, &immutable_book)?;
+    borrow_book(out, &mutable_book)?;
 
-    new_edition(&mut mutable_book);
+    new_edition(out, &mut mutable_book)?;
 
-    // new_edition(&mut immutable_book); // not allowed
+    // new_edition(out, &mut immutable_book)?; // not allowed
+    Ok(())
 }
 
 // aliasing
@@ -195,21 +216,21 @@ struct Point {
     z: i32,
 }
 
-fn part8() {
+fn part8(out: &mut dyn Write) -> io::Result<()> {
     let mut point = Point { x: 0, y: 0, z: 0 };
     let borrowed_point = &point;
     let another_borrow = &point;
 
-    println!(
+    writeln!(out, 
         "Point has location: ({} {} {})",
         point.x, borrowed_point.y, another_borrow.z
-    ); // allowed
+    )?; // allowed
 
     // let mutable_borrow = &mut point; // not allowed because immutable borrows again used in next line
-    println!(
+    writeln!(out, 
         "Again, point has location: ({} {} {})",
         point.x, borrowed_point.y, another_borrow.z
-    );
+    )?;
 
     let mutable_borrow = &mut point;
 
@@ -221,16 +242,17 @@ fn part8() {
 
     // cant event print point because print takes immutable reference
 
-    println!(
+    writeln!(out, 
         "Now point has location: ({} {} {})",
         mutable_borrow.x, mutable_borrow.y, mutable_borrow.z
-    );
+    )?;
 
     let new_borrow = &point;
-    println!(
+    writeln!(out, 
         "Again, point has location: ({} {} {})",
         new_borrow.x, new_borrow.y, new_borrow.z
-    );
+    )?;
+    Ok(())
 }
 
 // ref pattern
@@ -242,12 +264,13 @@ struct MyPoint {
     y: i32,
 }
 
-fn part9() {
+#[allow(clippy::toplevel_ref_arg)]
+fn part9(out: &mut dyn Write) -> io::Result<()> {
     let c = 'Q';
     // ref on LHS is same as & on RHS
     let ref ref_c1 = c;
     let ref_c2 = &c;
-    println!("ref_c1 and ref_c2 are equal? {}", *ref_c1 == *ref_c2);
+    writeln!(out, "ref_c1 and ref_c2 are equal? {}", *ref_c1 == *ref_c2)?;
 
     let point = MyPoint { x: 0, y: 0 };
 
@@ -270,8 +293,8 @@ fn part9() {
         *mut_ref_to_y = 1;
     }
 
-    println!("point is ({},{})", point.x, point.y);
-    println!("mutable point is ({},{})", mutable_point.x, mutable_point.y);
+    writeln!(out, "point is ({},{})", point.x, point.y)?;
+    writeln!(out, "mutable point is ({},{})", mutable_point.x, mutable_point.y)?;
 
     let mut mutable_tuple = (Box::new(5u32), 3u32);
 
@@ -280,7 +303,8 @@ fn part9() {
         *last = 2u32;
     }
 
-    println!("Tuple is {:?}", mutable_tuple);
+    writeln!(out, "Tuple is {:?}", mutable_tuple)?;
+    Ok(())
 }
 
 /* Lifetimes */
@@ -293,14 +317,14 @@ fn example() {
     { //                                                   │
         let borrow1 = &i; // `borrow1` lifetime starts. ──┐│
         //                                                ││
-        println!("borrow1: {}", borrow1); //              ││
+        writeln!(out, "borrow1: {}", borrow1)?; //              ││
     } // `borrow1` ends. ─────────────────────────────────┘│
     //                                                     │
     //                                                     │
     { //                                                   │
         let borrow2 = &i; // `borrow2` lifetime starts. ──┐│
         //                                                ││
-        println!("borrow2: {}", borrow2); //              ││
+        writeln!(out, "borrow2: {}", borrow2)?; //              ││
     } // `borrow2` ends. ─────────────────────────────────┘│
     //                                                     │
 }   // Lifetime ends. ─────────────────────────────────────┘
@@ -312,8 +336,9 @@ fn example() {
 // foo<'a, 'b> lifetime parameters 'a and 'b
 //    here, lifetime of foo cannot exceed either 'a or 'b
 
-fn print_refs<'a, 'b>(x: &'a i32, y: &'b i32) {
-    println!("x is {x} and y is {y}");
+fn print_refs<'a, 'b>(out: &mut dyn Write, x: &'a i32, y: &'b i32) -> io::Result<()> {
+    writeln!(out, "x is {x} and y is {y}")?;
+    Ok(())
 }
 
 fn failed_borrow<'a>() {
@@ -321,13 +346,14 @@ fn failed_borrow<'a>() {
     // let y:&'a i32 = &_x; // not allowed because x does not live long enough, _x has shorter life than y
 }
 
-fn part10() {
+fn part10(out: &mut dyn Write) -> io::Result<()> {
     let (four, nine) = (4, 9);
 
-    print_refs(&four, &nine);
+    print_refs(out, &four, &nine)?;
     // the lifetime of four and nine must be grater than that of print_refs
 
     failed_borrow();
+    Ok(())
 }
 
 // lifetimes of functions
@@ -336,16 +362,18 @@ fn part10() {
 //    returned reference must have same lifetime as input or static
 //    returning reference without input is banned
 
-fn print_one<'a>(x: &'a i32) {
-    println!("print_one: x is {x}");
+fn print_one<'a>(out: &mut dyn Write, x: &'a i32) -> io::Result<()> {
+    writeln!(out, "print_one: x is {x}")?;
+    Ok(())
 }
 
 fn add_one<'a>(x: &'a mut i32) {
     *x += 1;
 }
 
-fn print_multi<'a, 'b>(x: &'a i32, y: &'b i32) {
-    println!("print_multi: x is {x} and y is {y}");
+fn print_multi<'a, 'b>(out: &mut dyn Write, x: &'a i32, y: &'b i32) -> io::Result<()> {
+    writeln!(out, "print_multi: x is {x} and y is {y}")?;
+    Ok(())
 }
 
 // returning lifetime must be correct
@@ -358,19 +386,20 @@ fn pass_x<'a, 'b>(x: &'a i32, _: &'b i32) -> &'a i32 {
     &String::from("foo")
 } */
 
-fn part11() {
+fn part11(out: &mut dyn Write) -> io::Result<()> {
     let x = 7;
     let y = 9;
 
-    print_one(&x);
-    print_multi(&x, &y);
+    print_one(out, &x)?;
+    print_multi(out, &x, &y)?;
 
     let z = pass_x(&x, &y);
-    print_one(z);
+    print_one(out, z)?;
 
     let mut t = 3;
     add_one(&mut t);
-    print_one(&t);
+    print_one(out, &t)?;
+    Ok(())
 }
 
 // lifetimes of methods
@@ -382,16 +411,17 @@ impl Owner {
     fn add_one<'a>(&'a mut self) {
         self.0 += 1;
     }
-    fn print<'a>(&'a self) {
-        println!("print: {}", self.0);
+    fn print<'a>(&'a self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "print: {}", self.0)
     }
 }
 
-fn part12() {
+fn part12(out: &mut dyn Write) -> io::Result<()> {
     let mut owner = Owner(18);
 
     owner.add_one();
-    owner.print();
+    owner.print(out)?;
+    Ok(())
 }
 
 // lifetime of struct
@@ -412,7 +442,7 @@ enum Either<'a> {
     Ref(&'a i32),
 }
 
-fn part13() {
+fn part13(out: &mut dyn Write) -> io::Result<()> {
     let x = 18;
     let y = 15;
 
@@ -421,10 +451,11 @@ fn part13() {
     let reference = Either::Ref(&x);
     let number = Either::Num(y);
 
-    println!("x is borrowed in {:?}", single);
-    println!("x and y are borrowed in {:?}", double);
-    println!("x is borrowed in {:?}", reference);
-    println!("y is *not* borrowed in {:?}", number);
+    writeln!(out, "x is borrowed in {:?}", single)?;
+    writeln!(out, "x and y are borrowed in {:?}", double)?;
+    writeln!(out, "x is borrowed in {:?}", reference)?;
+    writeln!(out, "y is *not* borrowed in {:?}", number)?;
+    Ok(())
 }
 
 // lifetimes for traits
@@ -439,9 +470,10 @@ impl<'a> Default for MyBorrowed<'a> {
     }
 }
 
-fn part14() {
+fn part14(out: &mut dyn Write) -> io::Result<()> {
     let b: MyBorrowed = Default::default();
-    println!("b is {:?}", b);
+    writeln!(out, "b is {:?}", b)?;
+    Ok(())
 }
 
 // bounds in lifetimes
@@ -452,26 +484,27 @@ fn part14() {
 struct MyRef<'a, T: 'a>(&'a T);
 // `Ref` contains a reference to a generic type `T` that has an unknown lifetime `'a`. `T` is bounded such that any *references* in `T` must outlive `'a`. Additionally, the lifetime of `Ref` may not exceed `'a`.
 
-fn print<T>(t: T)
+fn print<T>(out: &mut dyn Write, t: T) -> io::Result<()>
 where
     T: std::fmt::Debug,
 {
-    println!("print: t is {:?}", t);
+    writeln!(out, "print: t is {:?}", t)
 }
 
-fn print_ref<'a, T>(t: &'a T)
+fn print_ref<'a, T>(out: &mut dyn Write, t: &'a T) -> io::Result<()>
 where
     T: std::fmt::Debug + 'a,
 {
-    println!("print ref: t is {:?}", t);
+    writeln!(out, "print ref: t is {:?}", t)
 }
 
-fn part15() {
+fn part15(out: &mut dyn Write) -> io::Result<()> {
     let x = 7;
     let ref_x = MyRef(&x);
 
-    print_ref(&ref_x);
-    print(ref_x);
+    print_ref(out, &ref_x)?;
+    print(out, ref_x)?;
+    Ok(())
 }
 
 // coercion
@@ -486,14 +519,15 @@ fn choose_first<'a: 'b, 'b>(first: &'a i32, _: &'b i32) -> &'b i32 {
     first
 }
 
-fn part16() {
+fn part16(out: &mut dyn Write) -> io::Result<()> {
     let first = 2; // longer lifetime
 
     {
         let second = 3; // shorter lifetime
-        println!("The product is {}", multiply(&first, &second));
-        println!("{} is the first", choose_first(&first, &second));
+        writeln!(out, "The product is {}", multiply(&first, &second))?;
+        writeln!(out, "{} is the first", choose_first(&first, &second))?;
     };
+    Ok(())
 }
 
 // static
@@ -508,10 +542,10 @@ fn coerce_static<'a>(_: &'a i32) -> &'a i32 {
     &NUM
 }
 
-fn part17() {
+fn part17(out: &mut dyn Write) -> io::Result<()> {
     {
         let static_string = "I am in read only memory";
-        println!("static string: {}", static_string);
+        writeln!(out, "static string: {}", static_string)?;
 
         // the data will remain but static_string will no longer be usable
     }
@@ -521,22 +555,25 @@ fn part17() {
 
         let coerced_static = coerce_static(&lifetime_num);
 
-        println!("coerced_static: {}", coerced_static);
+        writeln!(out, "coerced_static: {}", coerced_static)?;
     }
-    println!("NUM: {} is still accessible", NUM);
+    writeln!(out, "NUM: {} is still accessible", NUM)?;
+    Ok(())
 }
 
 // trait bound: owned data always contains a static lifetime but references need not
 
-fn print_it(input: impl std::fmt::Debug + 'static) {
-    println!("static value passed in is {:?}", input);
+fn print_it(out: &mut dyn Write, input: impl std::fmt::Debug + 'static) -> io::Result<()> {
+    writeln!(out, "static value passed in is {:?}", input)?;
+    Ok(())
 }
 
-fn part18() {
+fn part18(out: &mut dyn Write) -> io::Result<()> {
     let i = 5;
-    print_it(i); // valid
+    print_it(out, i)?; // valid
 
-    // print_it(&i); invalid because its lifetime is defined by the scope of main, and after main `i` is dropped but borrowed by `print_it`
+    // print_it(out, &i)?; invalid because its lifetime is defined by the scope of main, and after main `i` is dropped but borrowed by `print_it`
+    Ok(())
 }
 
 // elision
@@ -547,12 +584,14 @@ fn part18() {
 // 2. if only one input is present, output reference also gets the same lifetime parameter
 // 3. if `&self` or `&mut self` is a parameter, output has the same reference as them
 
-fn elided_input(x: &i32) {
-    println!("`elided_input`: {}", x);
+fn elided_input(out: &mut dyn Write, x: &i32) -> io::Result<()> {
+    writeln!(out, "`elided_input`: {}", x)?;
+    Ok(())
 }
 
-fn annotated_input<'a>(x: &'a i32) {
-    println!("`annotated_input`: {}", x);
+fn annotated_input<'a>(out: &mut dyn Write, x: &'a i32) -> io::Result<()> {
+    writeln!(out, "`annotated_input`: {}", x)?;
+    Ok(())
 }
 
 fn elided_pass(x: &i32) -> &i32 {
@@ -563,34 +602,37 @@ fn annotated_pass<'a>(x: &'a i32) -> &'a i32 {
     x
 }
 
-fn part19() {
+fn part19(out: &mut dyn Write) -> io::Result<()> {
     let x = 3;
 
-    elided_input(&x);
-    annotated_input(&x);
-
-    println!("`elided_pass`: {}", elided_pass(&x));
-    println!("`annotated_pass`: {}", annotated_pass(&x));
-}
-
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    // part6();
-    // part7();
-    // part8();
-    // part9();
-    // part10();
-    // part11();
-    // part12();
-    // part13();
-    // part14();
-    // part15();
-    // part16();
-    // part17();
-    // part18();
-    part19();
+    elided_input(out, &x)?;
+    annotated_input(out, &x)?;
+
+    writeln!(out, "`elided_pass`: {}", elided_pass(&x))?;
+    writeln!(out, "`annotated_pass`: {}", annotated_pass(&x))?;
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "RAII: boxes are freed when they go out of scope",
+        part2 => "destructors with the Drop trait",
+        part3 => "ownership and moves",
+        part4 => "mutability changes when ownership moves",
+        part5 => "partial moves",
+        part6 => "borrowing",
+        part7 => "mutable borrows",
+        part8 => "aliasing",
+        part9 => "the ref pattern",
+        part10 => "explicit lifetime annotations",
+        part11 => "lifetimes in functions",
+        part12 => "lifetimes in methods",
+        part13 => "lifetimes in structs and enums",
+        part14 => "lifetimes in trait implementations",
+        part15 => "lifetime bounds",
+        part16 => "lifetime coercion",
+        part17 => "static references",
+        part18 => "static trait bounds",
+        part19 => "lifetime elision",
+    ])
 }
@@ -1,4 +1,6 @@
 #![allow(dead_code, unused_variables)]
+use std::io::{self, Write};
+
 /* Traits */
 
 // a collection of methods on an unknown type Self
@@ -15,8 +17,8 @@ trait Animal {
     fn noise(&self) -> &'static str;
 
     // we can also provide default definition
-    fn talk(&self) {
-        println!("{} says {}", self.name(), self.noise());
+    fn talk(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{} says {}", self.name(), self.noise())
     }
 }
 
@@ -25,13 +27,14 @@ impl Sheep {
         self.naked
     }
 
-    fn sheer(&mut self) {
+    fn sheer(&mut self, out: &mut dyn Write) -> io::Result<()> {
         if self.is_naked() {
-            println!("{} is already naked", self.name());
+            writeln!(out, "{} is already naked", self.name())?;
         } else {
-            println!("{} gets a haircut!", self.name);
+            writeln!(out, "{} gets a haircut!", self.name)?;
             self.naked = true;
         }
+        Ok(())
     }
 }
 
@@ -51,18 +54,19 @@ impl Animal for Sheep {
             "baaaaah!"
         }
     }
-    fn talk(&self) {
-        println!("{} pauses briefly... {}", self.name, self.noise());
+    fn talk(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{} pauses briefly... {}", self.name, self.noise())
     }
 }
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     // type annotation compulsory in such method
     let mut dolly: Sheep = Animal::new("Dolly");
 
-    dolly.talk();
-    dolly.sheer();
-    dolly.talk();
+    dolly.talk(out)?;
+    dolly.sheer(out)?;
+    dolly.talk(out)?;
+    Ok(())
 }
 
 // derive
@@ -92,13 +96,13 @@ impl Inches {
 
 struct Seconds(i32);
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let one_second = Seconds(1);
-    // println!("{:?}", one_second); // not allowed
+    // writeln!(out, "{:?}", one_second)?; // not allowed
     // let _is_is_true = (one_second == one_second);// not allowed
 
     let foot = Inches(12);
-    println!("One foot equals {:?}", foot);
+    writeln!(out, "One foot equals {:?}", foot)?;
 
     let meter = Centimeters(100.0);
 
@@ -107,7 +111,8 @@ fn part2() {
     } else {
         "bigger"
     };
-    println!("One foot is {} than one meter", cmp);
+    writeln!(out, "One foot is {} than one meter", cmp)?;
+    Ok(())
 }
 
 /*  returning traits with dyn */
@@ -141,78 +146,90 @@ fn random_animal(random_number: f64) -> Box<dyn MyAnimal> {
     }
 }
 
-fn part3() {
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     let random_number = 0.234;
     let animal = random_animal(random_number);
-    println!("You have chosen an animal which says {}", animal.noise());
+    writeln!(out, "You have chosen an animal which says {}", animal.noise())?;
+    Ok(())
 }
 
 /* Operator Overloading */
 // operators are just syntactic sugar for method calls
 use std::ops;
 
-struct Foo;
-struct Bar;
+// operator methods have fixed signatures, so the operands carry the sink
+struct Foo<'s, 'out>(&'s support::SharedSink<'out>);
+struct Bar<'s, 'out>(&'s support::SharedSink<'out>);
 #[derive(Debug)]
 struct FooBar;
 
 #[derive(Debug)]
 struct BarFoo;
 
-impl ops::Add<Bar> for Foo {
+impl<'s, 'out> ops::Add<Bar<'s, 'out>> for Foo<'s, 'out> {
     type Output = FooBar;
 
-    fn add(self, rhs: Bar) -> Self::Output {
-        println!("Foo.add(Bar) was called");
+    fn add(self, rhs: Bar<'s, 'out>) -> Self::Output {
+        writeln!(self.0, "Foo.add(Bar) was called");
         FooBar
     }
 }
 
-impl ops::Add<Foo> for Bar {
+impl<'s, 'out> ops::Add<Foo<'s, 'out>> for Bar<'s, 'out> {
     type Output = BarFoo;
-    fn add(self, rhs: Foo) -> Self::Output {
-        println!("Bar.add(Foo) was called");
+    fn add(self, rhs: Foo<'s, 'out>) -> Self::Output {
+        writeln!(self.0, "Bar.add(Foo) was called");
         BarFoo
     }
 }
 
-fn part4() {
-    println!("Foo + Bar = {:?}", Foo + Bar);
-    println!("Bar + Foo = {:?}", Bar + Foo);
+fn part4(out: &mut dyn Write) -> io::Result<()> {
+    let sink = support::SharedSink::new(out);
+    let foo_bar = Foo(&sink) + Bar(&sink);
+    writeln!(sink, "Foo + Bar = {:?}", foo_bar);
+    let bar_foo = Bar(&sink) + Foo(&sink);
+    writeln!(sink, "Bar + Foo = {:?}", bar_foo);
+    sink.finish()
 }
 
 // Drop trait -> like Destructor
 // Box, Vec, String, File and Process implement Drop trait
 
-struct Droppable {
+struct Droppable<'s, 'out> {
     name: &'static str,
+    // `drop` only gets `&mut self`, so the sink has to live in the value
+    sink: &'s support::SharedSink<'out>,
 }
 
-impl Drop for Droppable {
+impl Drop for Droppable<'_, '_> {
     fn drop(&mut self) {
-        println!("Dropping {}", self.name);
+        writeln!(self.sink, "Dropping {}", self.name);
     }
 }
 
-fn part5() {
-    let _a = Droppable { name: "a" };
+fn part5(out: &mut dyn Write) -> io::Result<()> {
+    let sink = support::SharedSink::new(out);
     {
-        let _b = Droppable { name: "b" };
+        let _a = Droppable { name: "a", sink: &sink };
         {
-            let _c = Droppable { name: "c" };
-            let _d = Droppable { name: "d" };
+            let _b = Droppable { name: "b", sink: &sink };
+            {
+                let _c = Droppable { name: "c", sink: &sink };
+                let _d = Droppable { name: "d", sink: &sink };
 
-            println!("Exiting Block B");
-        }
-        println!("Just exited Block B");
+                writeln!(sink, "Exiting Block B");
+            }
+            writeln!(sink, "Just exited Block B");
 
-        println!("Exiting Block A");
-    }
-    println!("Just exited Block  A");
+            writeln!(sink, "Exiting Block A");
+        }
+        writeln!(sink, "Just exited Block  A");
 
-    drop(_a); // manually drop
+        drop(_a); // manually drop
 
-    println!("End of main");
+        writeln!(sink, "End of main");
+    }
+    sink.finish()
 }
 
 /* Iterators */
@@ -229,7 +246,7 @@ impl Iterator for Fibonacci {
     fn next(&mut self) -> Option<Self::Item> {
         let current = self.current;
         self.current = self.next;
-        self.next = current + self.next;
+        self.next += current;
         Some(current)
         // Fibonacci series in infinite and never returns None
     }
@@ -242,40 +259,41 @@ fn fibonacci() -> Fibonacci {
     }
 }
 
-fn part6() {
+fn part6(out: &mut dyn Write) -> io::Result<()> {
     let mut sequence = 0..3;
-    println!("Four consecutive `next` calls on 0..3");
-    println!("> {:?}", sequence.next());
-    println!("> {:?}", sequence.next());
-    println!("> {:?}", sequence.next());
-    println!("> {:?}", sequence.next());
+    writeln!(out, "Four consecutive `next` calls on 0..3")?;
+    writeln!(out, "> {:?}", sequence.next())?;
+    writeln!(out, "> {:?}", sequence.next())?;
+    writeln!(out, "> {:?}", sequence.next())?;
+    writeln!(out, "> {:?}", sequence.next())?;
 
     // `for` works through an `Iterator` until it returns `None`.
     // Each `Some` value is unwrapped and bound to a variable (here, `i`).
-    println!("Iterate through 0..3 using `for`");
+    writeln!(out, "Iterate through 0..3 using `for`")?;
     for i in 0..3 {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
 
     // The `take(n)` method reduces an `Iterator` to its first `n` terms.
-    println!("The first four terms of the Fibonacci sequence are: ");
+    writeln!(out, "The first four terms of the Fibonacci sequence are: ")?;
     for i in fibonacci().take(4) {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
 
     // The `skip(n)` method shortens an `Iterator` by dropping its first `n` terms.
-    println!("The next four terms of the Fibonacci sequence are: ");
+    writeln!(out, "The next four terms of the Fibonacci sequence are: ")?;
     for i in fibonacci().skip(4).take(4) {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
 
     let array = [1u32, 3, 3, 7];
 
     // The `iter` method produces an `Iterator` over an array/slice.
-    println!("Iterate the following array {:?}", &array);
+    writeln!(out, "Iterate the following array {:?}", &array)?;
     for i in array.iter() {
-        println!("> {}", i);
+        writeln!(out, "> {}", i)?;
     }
+    Ok(())
 }
 
 /* impl keyword */
@@ -308,15 +326,15 @@ fn combine_vectors_explicit_return_type(
     v: Vec<i32>,
     u: Vec<i32>,
 ) -> iter::Cycle<iter::Chain<IntoIter<i32>, IntoIter<i32>>> {
-    v.into_iter().chain(u.into_iter()).cycle()
+    v.into_iter().chain(u).cycle()
 }
 
 // same function with impl return type
 fn combine_vectors(v: Vec<i32>, u: Vec<i32>) -> impl Iterator<Item = i32> {
-    v.into_iter().chain(u.into_iter()).cycle()
+    v.into_iter().chain(u).cycle()
 }
 
-fn part7() {
+fn part7(out: &mut dyn Write) -> io::Result<()> {
     let v1 = vec![1, 2, 3];
     let v2 = vec![4, 5];
     let mut v3 = combine_vectors(v1, v2);
@@ -325,31 +343,37 @@ fn part7() {
     assert_eq!(Some(3), v3.next());
     assert_eq!(Some(4), v3.next());
     assert_eq!(Some(5), v3.next());
-    println!("all done");
+    writeln!(out, "all done")?;
+    Ok(())
 }
 
 // some Rust types can't be written e.g. type of each closure is different
 
+// binding the closure first shows that its type has no name
+#[allow(clippy::let_and_return)]
 fn make_adder_function(y: i32) -> impl Fn(i32) -> i32 {
     let closure = move |x: i32| x + y;
     closure
 }
 
-fn part8() {
+fn part8(out: &mut dyn Write) -> io::Result<()> {
     let plus_one = make_adder_function(1);
     assert_eq!(plus_one(2), 3);
+    Ok(())
 }
 
 // we can return Iterator that implement map and filter
 
+#[allow(clippy::ptr_arg)]
 fn double_positives<'a>(numbers: &'a Vec<i32>) -> impl Iterator<Item = i32> + 'a {
     numbers.iter().filter(|x| x > &&0).map(|x| x * 2)
 }
 
-fn part9() {
+fn part9(out: &mut dyn Write) -> io::Result<()> {
     let singles = vec![-3, -2, 2, 3];
     let doubles = double_positives(&singles);
     assert_eq!(doubles.collect::<Vec<i32>>(), vec![4, 6]);
+    Ok(())
 }
 
 /* Clone */
@@ -360,25 +384,26 @@ struct Unit;
 #[derive(Clone, Debug)]
 struct Pair(Box<i32>, Box<i32>);
 
-fn part10() {
+fn part10(out: &mut dyn Write) -> io::Result<()> {
     let unit = Unit;
     let copied_unit = unit; // copied by default because no resources to move
 
-    println!("original: {:?}", unit);
-    println!("copied: {:?}", copied_unit);
+    writeln!(out, "original: {:?}", unit)?;
+    writeln!(out, "copied: {:?}", copied_unit)?;
 
     let pair = Pair(Box::new(1), Box::new(2));
-    println!("original: {:?}", pair);
+    writeln!(out, "original: {:?}", pair)?;
 
     let moved_pair = pair; // not copied
-    println!("moved: {:?}", moved_pair);
+    writeln!(out, "moved: {:?}", moved_pair)?;
 
-    // println!("{:?}", pair); // not allowed
+    // writeln!(out, "{:?}", pair)?; // not allowed
 
     let cloned_pair = moved_pair.clone();
     drop(moved_pair);
 
-    println!("clone: {:?}", cloned_pair);
+    writeln!(out, "clone: {:?}", cloned_pair)?;
+    Ok(())
 }
 
 // Supertraits
@@ -441,14 +466,15 @@ impl Person for Me {
     }
 }
 
-fn part11() {
+fn part11(out: &mut dyn Write) -> io::Result<()> {
     let me = Me {
         name: "manikya",
         lang: "rust",
         university: "dtu",
         username: "Manikya-Sharma",
     };
-    println!("{}", it_student_greeting(&me));
+    writeln!(out, "{}", it_student_greeting(&me))?;
+    Ok(())
 }
 
 /* disambiguating overlapping traits */
@@ -481,7 +507,7 @@ impl AgeWidget for Form {
     }
 }
 
-fn part12() {
+fn part12(out: &mut dyn Write) -> io::Result<()> {
     let form = Form {
         username: "rustacean".to_owned(),
         age: 28,
@@ -492,20 +518,22 @@ fn part12() {
 
     assert_eq!("rustacean".to_owned(), username);
     assert_eq!(28, age);
-
-}
-
-
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    // part6();
-    // part8();
-    // part9();
-    // part10();
-    // part11();
-    part12();
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "traits: the Animal trait for Sheep",
+        part2 => "derivable traits",
+        part3 => "returning traits with dyn",
+        part4 => "operator overloading",
+        part5 => "the Drop trait",
+        part6 => "iterators: a Fibonacci iterator",
+        part7 => "impl Trait as a return type",
+        part8 => "returning closures with impl Fn",
+        part9 => "returning iterator adaptors",
+        part10 => "Clone and Copy",
+        part11 => "supertraits",
+        part12 => "disambiguating overlapping traits",
+    ])
 }
@@ -1,4 +1,6 @@
 #![allow(dead_code)]
+use std::io::{self, Write};
+
 /* metaprogramming in rust - macros */
 
 // macros end with a bang (!)
@@ -10,15 +12,16 @@
 
 // macro name say_hello
 macro_rules! say_hello {
-    // () means no arguments
-    () => {
+    // only the sink is passed; local names such as `out` are hygienic,
+    // so a macro cannot see the caller's variables unless they are passed in
+    ($out:expr) => {
         // macro will expand to this
-        println!("Hello!")
+        writeln!($out, "Hello!")
     };
 }
 
-fn part1() {
-    say_hello!();
+fn part1(out: &mut dyn Write) -> io::Result<()> {
+    say_hello!(out)
 }
 
 // benefits of macros
@@ -32,8 +35,8 @@ macro_rules! create_function {
     // ident designator is used for variable/function name
     // arguments in macro are prefixed by dollar
     ($func_name:ident) => {
-        fn $func_name() {
-            println!("You called {:?}()", stringify!($func_name));
+        fn $func_name(out: &mut dyn Write) -> io::Result<()> {
+            writeln!(out, "You called {:?}()", stringify!($func_name))
         }
     };
 }
@@ -44,17 +47,17 @@ create_function!(bar);
 
 macro_rules! print_result {
     // expr is designator for expressions
-    ($expression:expr) => {
-        println!("{:?} = {:?}", stringify!($expression), $expression)
+    ($out:expr, $expression:expr) => {
+        writeln!($out, "{:?} = {:?}", stringify!($expression), $expression)
     };
 }
 
-fn part2() {
-    foo();
-    bar();
-    print_result!(1u32 + 1);
+fn part2(out: &mut dyn Write) -> io::Result<()> {
+    foo(out)?;
+    bar(out)?;
+    print_result!(out, 1u32 + 1)?;
     // blocks are also expressions!
-    print_result!({
+    print_result!(out, {
         let x = 1u32;
         x * x + 2 * x - 1
     })
@@ -77,8 +80,9 @@ fn part2() {
 // macros can be overloaded
 
 macro_rules! test {
-    ($left:expr; and $right:expr) => {
-        println!(
+    ($out:expr, $left:expr; and $right:expr) => {
+        writeln!(
+            $out,
             "{:?} and {:?} is {:?}",
             stringify!($left),
             stringify!($right),
@@ -86,8 +90,9 @@ macro_rules! test {
         )
     };
     // each arm must end with a semicolon
-    ($left:expr; or $right:expr) => {
-        println!(
+    ($out:expr, $left:expr; or $right:expr) => {
+        writeln!(
+            $out,
             "{:?} or {:?} is {:?}",
             stringify!($left),
             stringify!($right),
@@ -96,9 +101,10 @@ macro_rules! test {
     };
 }
 
-fn part3() {
-    test!(1i32+1 == 2i32; and 2i32 *2 == 4i32);
-    test!(true; or false);
+#[allow(clippy::eq_op)]
+fn part3(out: &mut dyn Write) -> io::Result<()> {
+    test!(out, 1i32+1 == 2i32; and 2i32 *2 == 4i32)?;
+    test!(out, true; or false)
 }
 
 /* Repeat */
@@ -114,10 +120,11 @@ macro_rules! find_min {
     )
 }
 
-fn part4() {
-    println!("{}", find_min!(1));
-    println!("{}", find_min!(1 + 2, 2));
-    println!("{}", find_min!(5, 2 * 3, 4));
+fn part4(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "{}", find_min!(1))?;
+    writeln!(out, "{}", find_min!(1 + 2, 2))?;
+    writeln!(out, "{}", find_min!(5, 2 * 3, 4))?;
+    Ok(())
 }
 
 // DRY e.g. tests
@@ -140,7 +147,7 @@ macro_rules! assert_equal_len {
 
 macro_rules! op {
     ($func:ident, $bound:ident, $op:tt, $method:ident) => {
-        fn $func<T: $bound<T, Output = T> + Copy>(xs: &mut Vec<T>, ys: &Vec<T>) {
+        fn $func<T: $bound<T, Output = T> + Copy>(xs: &mut [T], ys: &[T]) {
             assert_equal_len!(xs, ys, $func, $op);
 
             for (x, y) in xs.iter_mut().zip(ys.iter()) {
@@ -156,6 +163,9 @@ op!(add_assign, Add, +=, add);
 op!(mul_assign, Mul, *=, mul);
 op!(sub_assign, Sub, -=, sub);
 
+// kept next to the macros it exercises
+#[allow(clippy::items_after_test_module)]
+#[cfg(test)]
 mod test {
     use std::iter;
     macro_rules! test {
@@ -188,51 +198,57 @@ mod test {
 // eval 1+2 -> 1 + 2 = 3
 
 macro_rules! calculate {
-    (eval $e:expr) => {
+    ($out:expr, eval $e:expr) => {
         let val:usize = $e;
-        println!("{} = {}", stringify!{$e}, val);
+        writeln!($out, "{} = {}", stringify!{$e}, val)?;
     };
 }
 
-fn part5() {
+#[allow(clippy::erasing_op)]
+fn part5(out: &mut dyn Write) -> io::Result<()> {
     calculate! {
-        eval 1 + 2
+        out, eval 1 + 2
     }
     calculate! {
-        eval (1+2)* (3/4)
+        out, eval (1+2)* (3/4)
     }
+    Ok(())
 }
 
 /* Variadic Interface */
 // to allow for a variable number of arguments
 macro_rules! calculate2 {
-    (eval $e:expr) => {
+    ($out:expr, eval $e:expr) => {
         let val:usize = $e;
-        println!("{} = {}", stringify!($e), val);
+        writeln!($out, "{} = {}", stringify!($e), val)?;
     };
 
     // decompose eval's recursively
-    (eval $e:expr, $(eval $es:expr), +) => {
+    ($out:expr, eval $e:expr, $(eval $es:expr), +) => {
         {
-            calculate2! {eval $e}
-            calculate2! { $(eval $es), +}
+            calculate2! {$out, eval $e}
+            calculate2! {$out, $(eval $es), +}
         }
     };
 }
 
-fn part6() {
+fn part6(out: &mut dyn Write) -> io::Result<()> {
     calculate2! {
+        out,
         eval 1+2,
         eval 3+4,
         eval (2*3)+1
     }
-}
-
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    part6();
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "macro_rules! without arguments",
+        part2 => "designators: ident and expr",
+        part3 => "overloading macro arms",
+        part4 => "repetition: find_min!",
+        part5 => "a domain specific language: calculate!",
+        part6 => "variadic interfaces: calculate2!",
+    ])
 }
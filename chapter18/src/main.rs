@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+use std::io::{self, Write};
 
 /* Error handling in Rust */
 
@@ -8,96 +9,103 @@
 
 /* panic */
 
-use std::fmt::format;
-
-fn drink(beverage: &str) {
+fn drink(out: &mut dyn Write, beverage: &str) -> io::Result<()> {
     if beverage == "lemonade" {
         panic!("Don't drink too much sugar!");
     }
-    println!("Some refreshing {} is all I need", beverage);
+    writeln!(out, "Some refreshing {} is all I need", beverage)?;
+    Ok(())
 }
 
-fn part1() {
-    drink("water");
-    drink("lemonade");
-    drink("water");
+fn part1(out: &mut dyn Write) -> io::Result<()> {
+    drink(out, "water")?;
+    drink(out, "lemonade")?;
+    drink(out, "water")?;
+    Ok(())
 }
 
 /* abort and unwind */
 
-fn drink2(beverage: &str) {
+fn drink2(out: &mut dyn Write, beverage: &str) -> io::Result<()> {
     if beverage == "lemonade" {
         if cfg!(panic = "abort") {
-            println!("This is not your party. Run!");
+            writeln!(out, "This is not your party. Run!")?;
         } else {
-            println!("Spit it out!");
+            writeln!(out, "Spit it out!")?;
         }
     } else {
-        println!("Some refreshing {} is all I need", beverage);
+        writeln!(out, "Some refreshing {} is all I need", beverage)?;
     }
+    Ok(())
 }
 
-fn part2() {
-    drink2("water");
-    drink2("lemonade");
+fn part2(out: &mut dyn Write) -> io::Result<()> {
+    drink2(out, "water")?;
+    drink2(out, "lemonade")?;
+    Ok(())
 }
 
 #[cfg(panic = "unwind")]
-fn ah() {
-    println!("Spit it out!");
+fn ah(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "Spit it out!")
 }
 
 #[cfg(not(panic = "unwind"))]
-fn ah() {
-    println!("This is not your party, run!");
+fn ah(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "This is not your party, run!")
 }
 
-fn drink3(beverage: &str) {
+fn drink3(out: &mut dyn Write, beverage: &str) -> io::Result<()> {
     if beverage == "lemonade" {
-        ah();
+        ah(out)?;
     } else {
-        println!("Some refreshing {} is all I need.", beverage);
+        writeln!(out, "Some refreshing {} is all I need.", beverage)?;
     }
+    Ok(())
 }
 
-fn part3() {
-    drink3("water");
-    drink3("lemonade");
+fn part3(out: &mut dyn Write) -> io::Result<()> {
+    drink3(out, "water")?;
+    drink3(out, "lemonade")?;
+    Ok(())
 }
 
 /* Option and Unwrap */
 
-fn give_adult(drink: Option<&str>) {
+fn give_adult(out: &mut dyn Write, drink: Option<&str>) -> io::Result<()> {
     match drink {
-        Some("lemonade") => println!("Yuck! Too sugary"),
-        Some(inner) => println!("{}? How nice", inner),
-        None => println!("No drink? Oh well."),
+        Some("lemonade") => writeln!(out, "Yuck! Too sugary")?,
+        Some(inner) => writeln!(out, "{}? How nice", inner)?,
+        None => writeln!(out, "No drink? Oh well.")?,
     }
+    Ok(())
 }
 
-fn drink4(drink: Option<&str>) {
+fn drink4(out: &mut dyn Write, drink: Option<&str>) -> io::Result<()> {
     let inside = drink.unwrap();
     if inside == "lemonade" {
         panic!("No..")
     }
 
-    println!("I love {}s!", inside);
+    writeln!(out, "I love {}s!", inside)?;
+    Ok(())
 }
 
-fn part4() {
+fn part4(out: &mut dyn Write) -> io::Result<()> {
     let water = Some("water");
     let lemonade = Some("lemonade");
     let void = None;
 
-    give_adult(water);
-    give_adult(lemonade);
-    give_adult(void);
+    give_adult(out, water)?;
+    give_adult(out, lemonade)?;
+    give_adult(out, void)?;
 
     let coffee = Some("coffee");
     let nothing = None;
 
-    drink4(coffee);
-    drink4(nothing);
+    drink4(out, coffee)?;
+    drink4(out, nothing)?;
+    Ok(())
 }
 
 // unpacking options with ?
@@ -107,9 +115,10 @@ fn next_birthday(current_age: Option<u8>) -> Option<String> {
     Some(format!("Next year I will be {}", next_age))
 }
 
-fn part5() {
+fn part5(_out: &mut dyn Write) -> io::Result<()> {
     next_birthday(Some(13));
     next_birthday(None);
+    Ok(())
 }
 
 struct Person {
@@ -133,7 +142,7 @@ impl Person {
     }
 }
 
-fn part6() {
+fn part6(_out: &mut dyn Write) -> io::Result<()> {
     let p = Person {
         job: Some(Job {
             phone_number: Some(PhoneNumber {
@@ -143,6 +152,7 @@ fn part6() {
         }),
     };
     assert_eq!(p.work_area_code(), Some(16));
+    Ok(())
 }
 
 /* Combinators */
@@ -163,6 +173,8 @@ struct Chopped(Food);
 #[derive(Debug)]
 struct Cooked(Food);
 
+// peel and chop spell out by hand what `map` does for cook
+#[allow(clippy::manual_map)]
 fn peel(food: Option<Food>) -> Option<Peeled> {
     match food {
         Some(food) => Some(Peeled(food)),
@@ -170,6 +182,7 @@ fn peel(food: Option<Food>) -> Option<Peeled> {
     }
 }
 
+#[allow(clippy::manual_map)]
 fn chop(peeled: Option<Peeled>) -> Option<Chopped> {
     match peeled {
         Some(Peeled(food)) => Some(Chopped(food)),
@@ -182,20 +195,22 @@ fn cook(chopped: Option<Chopped>) -> Option<Cooked> {
 }
 
 // this way map can be used
+#[allow(clippy::redundant_closure)]
 fn process(food: Option<Food>) -> Option<Cooked> {
     food.map(|f| Peeled(f))
         .map(|Peeled(f)| Chopped(f))
         .map(|Chopped(f)| Cooked(f))
 }
 
-fn eat(food: Option<Cooked>) {
+fn eat(out: &mut dyn Write, food: Option<Cooked>) -> io::Result<()> {
     match food {
-        Some(food) => println!("Mmm, I love {:?}", food),
-        None => println!("It wasn't edible!"),
+        Some(food) => writeln!(out, "Mmm, I love {:?}", food)?,
+        None => writeln!(out, "It wasn't edible!")?,
     }
+    Ok(())
 }
 
-fn part7() {
+fn part7(out: &mut dyn Write) -> io::Result<()> {
     let apple = Some(Food::Apple);
     let carrot = Some(Food::Carrot);
     let potato = None;
@@ -204,9 +219,10 @@ fn part7() {
     let cooked_carrot = cook(chop(peel(carrot)));
     let cooked_potato = process(potato);
 
-    eat(cooked_apple);
-    eat(cooked_carrot);
-    eat(cooked_potato);
+    eat(out, cooked_apple)?;
+    eat(out, cooked_carrot)?;
+    eat(out, cooked_potato)?;
+    Ok(())
 }
 
 // and_then combinator
@@ -251,23 +267,26 @@ fn cookable_v3(food: Food2) -> Option<Food2> {
     have_recipe(food).and_then(have_ingredients)
 }
 
+#[allow(clippy::map_flatten)]
 fn cookable_v2(food: Food2) -> Option<Food2> {
     have_recipe(food).map(have_ingredients).flatten()
 }
 
-fn eat2(food: Food2, day: Day) {
+fn eat2(out: &mut dyn Write, food: Food2, day: Day) -> io::Result<()> {
     match cookable_v3(food) {
-        Some(food) => println!("We can eat {:?} on {:?}", food, day),
-        None => println!("We cant eat on {:?}", day),
+        Some(food) => writeln!(out, "We can eat {:?} on {:?}", food, day)?,
+        None => writeln!(out, "We cant eat on {:?}", day)?,
     }
+    Ok(())
 }
 
-fn part8() {
+fn part8(out: &mut dyn Write) -> io::Result<()> {
     let (cordon_bleu, steak, sushi) = (Food2::CordonBleu, Food2::Steak, Food2::Sushi);
 
-    eat2(cordon_bleu, Day::Monday);
-    eat2(steak, Day::Tuesday);
-    eat2(sushi, Day::Wednesday);
+    eat2(out, cordon_bleu, Day::Monday)?;
+    eat2(out, steak, Day::Tuesday)?;
+    eat2(out, sushi, Day::Wednesday)?;
+    Ok(())
 }
 
 // unpacking options and defaults
@@ -282,72 +301,80 @@ enum Fruit {
     Lemon,
 }
 
-fn part9() {
+fn part9(out: &mut dyn Write) -> io::Result<()> {
     let apple = Some(Fruit::Apple);
     let orange = Some(Fruit::Orange);
     let no_fruit: Option<Fruit> = None;
 
     let first_available_fruit = no_fruit.or(orange).or(apple);
-    println!("First available fruit: {:?}", first_available_fruit);
+    writeln!(out, "First available fruit: {:?}", first_available_fruit)?;
+    Ok(())
 }
 
 // or_else() -> chainable, lazy evaluation, empty value intact
 
-fn part10() {
-    let apple = Some(Fruit::Apple);
+fn part10(out: &mut dyn Write) -> io::Result<()> {
+    let _apple = Some(Fruit::Apple);
     let no_fruit: Option<Fruit> = None;
+    // the closures must return an Option, so they write through a shared sink
+    let sink = support::SharedSink::new(out);
     let get_kiwi_as_fallback = || {
-        println!("Providing kiwi as a fallback");
+        writeln!(sink, "Providing kiwi as a fallback");
         Some(Fruit::Kiwi)
     };
     let get_lemon_as_fallback = || {
-        println!("Providing lemon as fallback");
+        writeln!(sink, "Providing lemon as fallback");
         Some(Fruit::Lemon)
     };
     let first_available_fruit = no_fruit.or_else(get_kiwi_as_fallback).or_else(get_lemon_as_fallback);
 
-    println!("first_available_fruit: {:?}", first_available_fruit);
+    writeln!(sink, "first_available_fruit: {:?}", first_available_fruit);
+    sink.finish()
 }
 
 // get_or_insert() -> eager evaluation, modify empty value in place
 // it ensures Option will give a value
 
-fn part11() {
+fn part11(out: &mut dyn Write) -> io::Result<()> {
     let mut my_fruit:Option<Fruit> = None;
     let apple = Fruit::Apple;
     let first_available_fruit = my_fruit.get_or_insert(apple);
 
-    println!("first_available_fruit: {:?}", first_available_fruit);
-    println!("my_fruit: {:?}", my_fruit);
+    writeln!(out, "first_available_fruit: {:?}", first_available_fruit)?;
+    writeln!(out, "my_fruit: {:?}", my_fruit)?;
+    Ok(())
 }
 
 // get_or_insert_with() -> evaluates lazy, modify in place
 
-fn part12() {
+fn part12(out: &mut dyn Write) -> io::Result<()> {
     let mut my_fruit: Option<Fruit> = None;
+    let sink = support::SharedSink::new(out);
     let get_lemon_as_fallback = ||{
-        println!("Providing lemon as a fallback");
+        writeln!(sink, "Providing lemon as a fallback");
         Fruit::Lemon
     };
     let first_available_fruit = my_fruit.get_or_insert_with(get_lemon_as_fallback);
-    println!("first_available_fruit is: {:?}", first_available_fruit);
-    println!("my_fruit is: {:?}", my_fruit);
+    writeln!(sink, "first_available_fruit is: {:?}", first_available_fruit);
+    writeln!(sink, "my_fruit is: {:?}", my_fruit);
 
     // if Option has a value, then the closure is not called
-}
-
-
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    // part6();
-    // part7();
-    // part8();
-    // part9();
-    // part10();
-    // part11();
-    part12();
+    sink.finish()
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "panic! on lemonade",
+        part2 => "abort and unwind with cfg!(panic)",
+        part3 => "abort and unwind with #[cfg(panic)]",
+        part4 => "Option and unwrap",
+        part5 => "unpacking options with ?",
+        part6 => "chaining ? through nested options",
+        part7 => "combinators: map",
+        part8 => "combinators: and_then",
+        part9 => "defaults with or",
+        part10 => "defaults with or_else",
+        part11 => "get_or_insert",
+        part12 => "get_or_insert_with",
+    ])
 }
@@ -0,0 +1,24 @@
+// part1 and part4 panic on purpose, `--json` must still run everything after them
+
+use std::process::Command;
+
+#[test]
+fn json_report_runs_past_panics() {
+    let output = Command::new(env!("CARGO_BIN_EXE_chapter18"))
+        .arg("--json")
+        .output()
+        .expect("cannot run chapter18");
+    assert_eq!(output.status.code(), Some(101));
+
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.contains("\"passed\": 10,\n  \"panicked\": 2,\n  \"failed\": 0,"));
+    assert!(report.contains(
+        "\"status\": \"panicked\", \"output\": \"Some refreshing water is all I need\\n\", \
+         \"panic\": {\"message\": \"Don't drink too much sugar!\""
+    ));
+    assert!(report.contains("\"message\": \"called `Option::unwrap()` on a `None` value\""));
+    assert!(report.contains(
+        "{\"name\": \"part12\", \"description\": \"get_or_insert_with\", \
+         \"status\": \"passed\""
+    ));
+}
@@ -1,4 +1,6 @@
 #![allow(dead_code)]
+use std::io::{self, Write};
+
 /* Result<T, E> */
 // Ok(T) : Element T was found
 // Err(E): Error found with element E
@@ -12,13 +14,14 @@ fn multiply(first_number_str: &str, second_number_str: &str) -> i32 {
     first_number * second_number
 }
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let twenty = multiply("10", "2");
 
-    println!("double: {}", twenty);
+    writeln!(out, "double: {}", twenty)?;
 
     let tt = multiply("t", "2");
-    println!("double: {}", tt);
+    writeln!(out, "double: {}", tt)?;
+    Ok(())
 }
 
 // main can also return a Result
@@ -38,23 +41,25 @@ fn multiply_v2(first_number_str: &str, second_number_str: &str) -> Result<i32, P
     }
 }
 
-fn print(result: Result<i32, ParseIntError>) {
+fn print(out: &mut dyn Write, result: Result<i32, ParseIntError>) -> io::Result<()> {
     match result {
-        Ok(n) => println!("n is {}", n),
+        Ok(n) => writeln!(out, "n is {}", n)?,
         Err(e) => {
-            println!("Error: {}", e)
+            writeln!(out, "Error: {}", e)?
         }
     }
+    Ok(())
 }
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     // This still presents a reasonable answer.
     let twenty = multiply_v2("10", "2");
-    print(twenty);
+    print(out, twenty)?;
 
     // The following now provides a much more helpful error message.
     let tt = multiply_v2("t", "2");
-    print(tt);
+    print(out, tt)?;
+    Ok(())
 }
 // using Option's map and and_then for Result
 fn multiply_v3(first_number_str: &str, second_number_str: &str) -> Result<i32, ParseIntError> {
@@ -65,14 +70,15 @@ fn multiply_v3(first_number_str: &str, second_number_str: &str) -> Result<i32, P
     })
 }
 
-fn part3() {
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     // This still presents a reasonable answer.
     let twenty = multiply_v3("10", "2");
-    print(twenty);
+    print(out, twenty)?;
 
     // The following now provides a much more helpful error message.
     let tt = multiply_v3("t", "2");
-    print(tt);
+    print(out, tt)?;
+    Ok(())
 }
 
 /* Result alias */
@@ -87,13 +93,16 @@ fn multiply_v4(first_number_str: &str, second_number_str: &str) -> AliasResult<i
     })
 }
 
-fn part4() {
-    print(multiply_v4("10", "2"));
-    print(multiply_v4("t", "2"));
+fn part4(out: &mut dyn Write) -> io::Result<()> {
+    print(out, multiply_v4("10", "2"))?;
+    print(out, multiply_v4("t", "2"))?;
+    Ok(())
 }
 
 // early return allows for better code
 
+// the long-hand version of what `?` does in multiply_v6
+#[allow(clippy::question_mark)]
 fn multiply_v5(first_number_str: &str, second_number_str: &str) -> Result<i32, ParseIntError> {
     let first_number = match first_number_str.parse::<i32>() {
         Ok(first_num) => first_num,
@@ -106,9 +115,10 @@ fn multiply_v5(first_number_str: &str, second_number_str: &str) -> Result<i32, P
     Ok(first_number * second_number)
 }
 
-fn part5() {
-    print(multiply_v5("10", "2"));
-    print(multiply_v5("t", "2"));
+fn part5(out: &mut dyn Write) -> io::Result<()> {
+    print(out, multiply_v5("10", "2"))?;
+    print(out, multiply_v5("t", "2"))?;
+    Ok(())
 }
 
 // ? to unwrap without panic
@@ -120,24 +130,30 @@ fn multiply_v6(first_number_str: &str, second_number_str: &str) -> Result<i32, P
     Ok(first_number*second_number)
 }
 
-fn part6() {
-    print(multiply_v6("10", "2"));
-    print(multiply_v6("t", "2"));
+fn part6(out: &mut dyn Write) -> io::Result<()> {
+    print(out, multiply_v6("10", "2"))?;
+    print(out, multiply_v6("t", "2"))?;
+    Ok(())
 }
 
-fn main() /* -> Result<(), ParseIntError> */
-{
-    // part1();
-    /* let number_str = "10";
+// main can also return a Result:-
+/* fn main() -> Result<(), ParseIntError> {
+    let number_str = "10";
     let number = match number_str.parse::<i32>() {
         Ok(num) => num,
         Err(e) => return Err(e)
     };
     println!("{}", number);
-    Ok(()) */
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    part6();
+    Ok(())
+} */
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "unwrap on a parse error panics",
+        part2 => "matching on Result",
+        part3 => "map and and_then for Result",
+        part4 => "aliases for Result",
+        part5 => "early returns",
+        part6 => "the ? operator",
+    ])
 }
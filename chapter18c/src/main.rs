@@ -1,4 +1,6 @@
 #![allow(dead_code)]
+use std::io::{self, Write};
+
 /* Multiple error types */
 // sometimes Option has to interact with Result
 // or result of one type of error interacts with other
@@ -8,42 +10,43 @@ fn double_first(vec: Vec<&str>) -> i32 {
     2 * first.parse::<i32>().unwrap() // error 2
 }
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
 
-    println!("The first doubled is {}", double_first(numbers));
+    writeln!(out, "The first doubled is {}", double_first(numbers))?;
 
-    println!("The first doubled is {}", double_first(empty));
+    writeln!(out, "The first doubled is {}", double_first(empty))?;
     // Error 1: the input vector is empty
 
-    println!("The first doubled is {}", double_first(strings));
+    writeln!(out, "The first doubled is {}", double_first(strings))?;
     // Error 2: Element isn't a number
+    Ok(())
 }
 
 // Method 1: Embed Errors
 use std::num::ParseIntError;
 
-fn my_func_1(vec: Vec<&str>) /*  -> Option<Result<i32, ParseIntError>> */
-{
-    /* vec.first().map(|first| first.parse::<i32>().map(|n| 2 * n)) */
+fn my_func_1(vec: Vec<&str>) -> Option<std::result::Result<i32, ParseIntError>> {
+    vec.first().map(|first| first.parse::<i32>().map(|n| 2 * n))
 
     // can also return Result<Option<i32>, ParseIntError> for which map_or can be used.
 }
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
 
-    println!("The first doubled is {:?}", my_func_1(numbers));
+    writeln!(out, "The first doubled is {:?}", my_func_1(numbers))?;
 
-    println!("The first doubled is {:?}", my_func_1(empty));
+    writeln!(out, "The first doubled is {:?}", my_func_1(empty))?;
     // Error 1: the input vector is empty
 
-    println!("The first doubled is {:?}", my_func_1(strings));
+    writeln!(out, "The first doubled is {:?}", my_func_1(strings))?;
     // Error 2: the element doesn't parse to a number
+    Ok(())
 }
 
 // Defining an error type
@@ -72,21 +75,23 @@ fn my_func_2(vec: Vec<&str>) -> Result<i32> {
         .and_then(|s| s.parse::<i32>().map_err(|_| DoubleError).map(|i| 2 * i))
 }
 
-fn print(result: Result<i32>) {
+fn print(out: &mut dyn Write, result: Result<i32>) -> io::Result<()> {
     match result {
-        Ok(n) => println!("The first doubled is {}", n),
-        Err(e) => println!("Error: {}", e),
+        Ok(n) => writeln!(out, "The first doubled is {}", n)?,
+        Err(e) => writeln!(out, "Error: {}", e)?,
     }
+    Ok(())
 }
 
-fn part3() {
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
 
-    print(my_func_2(numbers));
-    print(my_func_2(empty));
-    print(my_func_2(strings));
+    print(out, my_func_2(numbers))?;
+    print(out, my_func_2(empty))?;
+    print(out, my_func_2(strings))?;
+    Ok(())
 }
 
 /* Boxing Errors */
@@ -117,21 +122,23 @@ fn my_func_3(vec: Vec<&str>) -> Result2<i32> {
         })
 }
 
-fn print_2(result: Result2<i32>) {
+fn print_2(out: &mut dyn Write, result: Result2<i32>) -> io::Result<()> {
     match result {
-        Ok(n) => println!("The first doubled is {}", n),
-        Err(e) => println!("Error: {}", e),
+        Ok(n) => writeln!(out, "The first doubled is {}", n)?,
+        Err(e) => writeln!(out, "Error: {}", e)?,
     }
+    Ok(())
 }
 
-fn part4() {
+fn part4(out: &mut dyn Write) -> io::Result<()> {
     let numbers = vec!["42", "93", "18"];
     let empty = vec![];
     let strings = vec!["tofu", "93", "18"];
 
-    print_2(my_func_3(numbers));
-    print_2(my_func_3(empty));
-    print_2(my_func_3(strings));
+    print_2(out, my_func_3(numbers))?;
+    print_2(out, my_func_3(empty))?;
+    print_2(out, my_func_3(strings))?;
+    Ok(())
 }
 
 /* ? can mean unwrap or return Err(From::from(err)), so it can be used for custom error types */
@@ -144,17 +151,18 @@ fn part4() {
 
 // filter_map: calls a function and filters out None
 
-fn part5() {
+fn part5(out: &mut dyn Write) -> io::Result<()> {
     let strings = vec!["tofu", "93", "18"];
     let numbers: Vec<_> = strings
         .into_iter()
         .filter_map(|s| s.parse::<i32>().ok())
         .collect();
-    println!("Results: {:?}", numbers);
+    writeln!(out, "Results: {:?}", numbers)?;
+    Ok(())
 }
 
 // map_err() can be used with filter_map() to store errors
-fn part6() {
+fn part6(out: &mut dyn Write) -> io::Result<()> {
     let strings = vec!["42", "tofu", "93", "999", "18"];
     let mut errors = vec![];
     let numbers: Vec<_> = strings
@@ -162,21 +170,23 @@ fn part6() {
         .map(|s| s.parse::<u8>())
         .filter_map(|r| r.map_err(|e| errors.push(e)).ok())
         .collect();
-    println!("Numbers: {:?}", numbers);
-    println!("Errors: {:?}", errors);
+    writeln!(out, "Numbers: {:?}", numbers)?;
+    writeln!(out, "Errors: {:?}", errors)?;
+    Ok(())
 }
 
 // whole collect can give a result
 
-fn part7() {
+fn part7(out: &mut dyn Write) -> io::Result<()> {
     let strings = vec!["tofu", "93", "18"];
     let numbers: std::result::Result<Vec<_>, _> = strings.into_iter().map(|s| s.parse::<i32>()).collect();
-    println!("Results: {:?}", numbers);
+    writeln!(out, "Results: {:?}", numbers)?;
+    Ok(())
 }
 
 // collect all valid values and failures with partition
 
-fn part8() {
+fn part8(out: &mut dyn Write) -> io::Result<()> {
     let strings = vec!["tofu", "93", "18"];
     let (numbers, errors): (Vec<_>, Vec<_>) = strings
         .into_iter()
@@ -184,16 +194,20 @@ fn part8() {
         .partition(std::result::Result::is_ok);
     let numbers: Vec<_> = numbers.into_iter().map(std::result::Result::unwrap).collect();
     let errors: Vec<_> = errors.into_iter().map(std::result::Result::unwrap_err).collect();
-    println!("Numbers: {:?}", numbers);
-    println!("Errors: {:?}", errors);
-}
-
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    // part6();
-    part7();
+    writeln!(out, "Numbers: {:?}", numbers)?;
+    writeln!(out, "Errors: {:?}", errors)?;
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "unwrap on an empty vector or a bad number panics",
+        part2 => "embedding errors in each other",
+        part3 => "defining an error type",
+        part4 => "boxing errors",
+        part5 => "ignoring failed items with filter_map",
+        part6 => "collecting failed items with map_err",
+        part7 => "failing the whole operation with collect",
+        part8 => "collecting all failures with partition",
+    ])
 }
@@ -4,6 +4,7 @@
 // Box is also cleared automatically
 
 use std::mem;
+use std::io::{self, Write};
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
@@ -26,7 +27,7 @@ fn boxed_origin() -> Box<Point> {
     Box::new(Point { x: 0.0, y: 0.0 })
 }
 
-fn main() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     // stack allocated variables
     let point: Point = origin();
     let rectangle = Rectangle {
@@ -43,33 +44,40 @@ fn main() {
     let boxed_point = Box::new(origin());
     let box_in_a_box = Box::new(boxed_origin());
 
-    println!(
+    writeln!(out, 
         "Point occupies {} bytes on the stack",
         mem::size_of_val(&point)
-    );
-    println!(
+    )?;
+    writeln!(out, 
         "Rectangle occupies {} bytes on the stack",
         mem::size_of_val(&rectangle)
-    );
+    )?;
 
     // box size == pointer size
-    println!(
+    writeln!(out, 
         "Boxed point occupies {} bytes on the stack",
         mem::size_of_val(&boxed_point)
-    );
-    println!(
+    )?;
+    writeln!(out, 
         "Boxed rectangle occupies {} bytes on the stack",
         mem::size_of_val(&boxed_rectangle)
-    );
-    println!(
+    )?;
+    writeln!(out, 
         "Boxed box occupies {} bytes on the stack",
         mem::size_of_val(&box_in_a_box)
-    );
+    )?;
 
     // Copy the data contained in `boxed_point` into `unboxed_point`
     let unboxed_point: Point = *boxed_point;
-    println!(
+    writeln!(out, 
         "Unboxed point occupies {} bytes on the stack",
         mem::size_of_val(&unboxed_point)
-    );
+    )?;
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "Box: stack and heap sizes",
+    ])
 }
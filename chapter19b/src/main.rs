@@ -1,19 +1,21 @@
+use std::io::{self, Write};
+
 /* Vectors */
 // vector can be resized
 
 // if length of vector surpasses its capacity, it needs to be reallocated with larger capacity
 
-fn main() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let collected_iterator: Vec<i32> = (0..10).collect();
 
-    println!("Collected (0..10) into {:?}", collected_iterator);
+    writeln!(out, "Collected (0..10) into {:?}", collected_iterator)?;
 
     let mut xs = vec![1_i32, 2, 3];
-    println!("Initial vector: {:?}", xs);
+    writeln!(out, "Initial vector: {:?}", xs)?;
 
-    println!("After pushing 4");
+    writeln!(out, "After pushing 4")?;
     xs.push(4);
-    println!("Vector: {:?}", xs);
+    writeln!(out, "Vector: {:?}", xs)?;
 
     // collected_iterator.push(0); // not allowed
 
@@ -22,16 +24,23 @@ fn main() {
 
     // iterator can be used
     for x in xs.iter() {
-        println!("> {}", x);
+        writeln!(out, "> {}", x)?;
     }
 
     for (i, x) in xs.iter().enumerate() {
-        println!("We have {} at position {}", x, i);
+        writeln!(out, "We have {} at position {}", x, i)?;
     }
 
     // we can also modify using iter_mut
     for x in xs.iter_mut() {
         *x += 3;
     }
-    println!("Updated vector: {:?}", xs);
+    writeln!(out, "Updated vector: {:?}", xs)?;
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "vectors: collecting, pushing and iterating",
+    ])
 }
@@ -3,6 +3,7 @@
 // growable and shrinkable
 
 use std::collections::HashMap;
+use std::io::{self, Write};
 
 fn call(number: &str) -> &str {
     match number {
@@ -18,7 +19,7 @@ fn call(number: &str) -> &str {
     }
 }
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let mut contacts = HashMap::with_capacity(4);
 
     contacts.insert("Daniel", "798-1364");
@@ -27,15 +28,15 @@ fn part1() {
     contacts.insert("Robert", "956-1745");
 
     match contacts.get(&"Daniel") {
-        Some(&number) => println!("Calling Daniel {}", call(number)),
-        _ => println!("Don't have Daniel's number"),
+        Some(&number) => writeln!(out, "Calling Daniel {}", call(number))?,
+        _ => writeln!(out, "Don't have Daniel's number")?,
     }
 
     contacts.insert("Daniel", "164-6743");
 
     match contacts.get(&"Ashley") {
-        Some(&number) => println!("Calling Ashley: {}", call(number)),
-        _ => println!("Don't have Ashley's number."),
+        Some(&number) => writeln!(out, "Calling Ashley: {}", call(number))?,
+        _ => writeln!(out, "Don't have Ashley's number.")?,
     }
 
     contacts.remove(&"Ashley");
@@ -43,13 +44,14 @@ fn part1() {
     // `HashMap::iter()` returns an iterator that yields
     // (&'a key, &'a value) pairs in arbitrary order.
     for (contact, &number) in contacts.iter() {
-        println!("Calling {}: {}", contact, call(number));
+        writeln!(out, "Calling {}: {}", contact, call(number))?;
     }
 
     // for a key to be valid, it must implement Eq and Hash traits
     // e.g. bool, int, uint, String, &str
     // f32 and f64 don't implement Hash due to precision errors
     // #define[PartialEq, Hash] can be used
+    Ok(())
 }
 
 #[derive(PartialEq, Eq, Hash)]
@@ -65,24 +67,30 @@ struct AccountInfo<'a> {
 
 type Accounts<'a> = HashMap<Account<'a>, AccountInfo<'a>>;
 
-fn try_login<'a>(accounts: &Accounts<'a>, username: &'a str, password: &'a str) {
-    println!("Username: {}", username);
-    println!("Password: {}", password);
-    println!("Attempting login ... ");
+fn try_login<'a>(
+    out: &mut dyn Write,
+    accounts: &Accounts<'a>,
+    username: &'a str,
+    password: &'a str,
+) -> io::Result<()> {
+    writeln!(out, "Username: {}", username)?;
+    writeln!(out, "Password: {}", password)?;
+    writeln!(out, "Attempting login ... ")?;
 
     let login = Account { username, password };
 
     match accounts.get(&login) {
         Some(account_info) => {
-            println!("Successful Login");
-            println!("Name: {}", account_info.name);
-            println!("Email: {}", account_info.email);
+            writeln!(out, "Successful Login")?;
+            writeln!(out, "Name: {}", account_info.name)?;
+            writeln!(out, "Email: {}", account_info.email)?;
         }
-        _ => println!("Login failed"),
+        _ => writeln!(out, "Login failed")?,
     }
+    Ok(())
 }
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let mut accounts: Accounts = HashMap::new();
 
     let account = Account {
@@ -97,9 +105,10 @@ fn part2() {
 
     accounts.insert(account, account_info);
 
-    try_login(&accounts, "j.everyman", "psasword123");
+    try_login(out, &accounts, "j.everyman", "psasword123")?;
 
-    try_login(&accounts, "j.everyman", "password123");
+    try_login(out, &accounts, "j.everyman", "password123")?;
+    Ok(())
 }
 
 /* HashSet */
@@ -114,30 +123,108 @@ fn part2() {
 use std::collections::HashSet;
 
 
-fn part3() {
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     let mut a: HashSet<i32> = vec![1, 2, 3].into_iter().collect();
     let mut b: HashSet<i32> = vec![2i32, 3, 4].into_iter().collect();
 
     assert!(a.insert(4));
     assert!(a.contains(&4));
 
-    println!("Did it insert 4 again?: {}", b.insert(4));
+    writeln!(out, "Did it insert 4 again?: {}", b.insert(4))?;
 
-    println!("A: {:?}", a);
-    println!("B: {:?}", b);
+    writeln!(out, "A: {:?}", a)?;
+    writeln!(out, "B: {:?}", b)?;
 
-    println!("Union: {:?}", a.union(&b).collect::<Vec<&i32>>());
+    writeln!(out, "Union: {:?}", a.union(&b).collect::<Vec<&i32>>())?;
 
-    println!("intersection: {:?}", a.intersection(&b).collect::<Vec<&i32>>());
+    writeln!(out, "intersection: {:?}", a.intersection(&b).collect::<Vec<&i32>>())?;
 
-    println!("difference: {:?}", a.difference(&b).collect::<Vec<&i32>>());
+    writeln!(out, "difference: {:?}", a.difference(&b).collect::<Vec<&i32>>())?;
+
+    writeln!(out, "symmetric_difference: {:?}", a.symmetric_difference(&b).collect::<Vec<&i32>>())?;
+    Ok(())
+}
 
-    println!("symmetric_difference: {:?}", a.symmetric_difference(&b).collect::<Vec<&i32>>());
+// the same listings as tables, sorted so that they print the same on every run
+fn part4(out: &mut dyn Write) -> io::Result<()> {
+    use support::table::{Align, Border, Table};
 
+    let mut contacts = HashMap::new();
+    contacts.insert("Daniel", "164-6743");
+    contacts.insert("Katie", "435-8291");
+    contacts.insert("Robert", "956-1745");
+    contacts.insert("Ashley", "645-7689");
+
+    let mut names: Vec<_> = contacts.keys().collect();
+    names.sort();
+    let mut table = Table::new(["Name", "Number", "Answer"]).max_width(2, 32);
+    for name in names {
+        let number = contacts[name];
+        // the answers span several lines in the source
+        let answer = call(number).split_whitespace().collect::<Vec<_>>().join(" ");
+        table.row([*name, number, answer.as_str()]);
+    }
+    write!(out, "{}", table)?;
+
+    let a: HashSet<i32> = vec![1, 2, 3, 4].into_iter().collect();
+    let b: HashSet<i32> = vec![2, 3, 4, 5].into_iter().collect();
+    let sorted = |values: Vec<&i32>| {
+        let mut values: Vec<i32> = values.into_iter().copied().collect();
+        values.sort_unstable();
+        format!("{:?}", values)
+    };
+
+    let mut table = Table::new(["Operation", "Result"])
+        .border(Border::Ascii)
+        .align(0, Align::Right);
+    table
+        .row(["A", &sorted(a.iter().collect())])
+        .row(["B", &sorted(b.iter().collect())])
+        .row(["union", &sorted(a.union(&b).collect())])
+        .row(["intersection", &sorted(a.intersection(&b).collect())])
+        .row(["difference", &sorted(a.difference(&b).collect())])
+        .row([
+            "symmetric_difference",
+            &sorted(a.symmetric_difference(&b).collect()),
+        ]);
+    write!(out, "{}", table)?;
+    Ok(())
 }
 
-fn main() {
-    // part1();
-    // part2();
-    part3();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "HashMap: a contact list",
+        part2 => "HashMap with a custom key type",
+        part3 => "HashSet operations",
+        part4 => "contacts and set operations as tables",
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_login_checks_the_password() {
+        let mut accounts: Accounts = HashMap::new();
+        let account = Account {
+            username: "j.everyman",
+            password: "password123",
+        };
+        let account_info = AccountInfo {
+            name: "John Everyman",
+            email: "j.everyman@email.com",
+        };
+        accounts.insert(account, account_info);
+
+        let mut out = Vec::new();
+        try_login(&mut out, &accounts, "j.everyman", "psasword123").unwrap();
+        assert!(String::from_utf8(out).unwrap().ends_with("Login failed\n"));
+
+        let mut out = Vec::new();
+        try_login(&mut out, &accounts, "j.everyman", "password123").unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("Successful Login\nName: John Everyman\n"));
+    }
 }
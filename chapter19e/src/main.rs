@@ -4,53 +4,60 @@
 // reference increasing on each clone but deep copy is not made
 
 use std::rc::Rc;
+use std::io::{self, Write};
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let rc_examples = "Rc examples".to_string();
 
     {
-        println!(" --- rc_a is created ---");
+        writeln!(out, " --- rc_a is created ---")?;
         let rc_a: Rc<String> = Rc::new(rc_examples);
-        println!("Reference Count of rc_a: {}", Rc::strong_count(&rc_a));
+        writeln!(out, "Reference Count of rc_a: {}", Rc::strong_count(&rc_a))?;
         {
-            println!("--- rc_a is cloned to rc_b ---");
+            writeln!(out, "--- rc_a is cloned to rc_b ---")?;
             let rc_b = Rc::clone(&rc_a);
-            println!("Reference count of rc_b: {}", Rc::strong_count(&rc_b));
-            println!("Reference Count of rc_a: {}", Rc::strong_count(&rc_a));
+            writeln!(out, "Reference count of rc_b: {}", Rc::strong_count(&rc_b))?;
+            writeln!(out, "Reference Count of rc_a: {}", Rc::strong_count(&rc_a))?;
 
-            println!("rc_a and rc_b are equal: {}", rc_a.eq(&rc_b));
+            writeln!(out, "rc_a and rc_b are equal: {}", rc_a.eq(&rc_b))?;
 
-            println!("Length of the value inside rc_a: {}", rc_a.len());
-            println!("Value of rc_b: {}", rc_b);
+            writeln!(out, "Length of the value inside rc_a: {}", rc_a.len())?;
+            writeln!(out, "Value of rc_b: {}", rc_b)?;
 
-            println!("--- rc_b is dropped out of scope ---");
+            writeln!(out, "--- rc_b is dropped out of scope ---")?;
         }
-        println!("Reference Count of rc_a: {}", Rc::strong_count(&rc_a));
+        writeln!(out, "Reference Count of rc_a: {}", Rc::strong_count(&rc_a))?;
 
-        println!("--- rc_a is dropped out of scope ---");
+        writeln!(out, "--- rc_a is dropped out of scope ---")?;
     }
     // note that rc_examples was moved so it cannot be used
+    Ok(())
 }
 
 
 /* Arc - sharing data between threads */
-use std::time::Duration;
 use std::sync::Arc;
 use std::thread;
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let apple = Arc::new("The same apple");
+    let mut handles = Vec::new();
     for _ in 0..10 {
         let apple = Arc::clone(&apple);
 
-        thread::spawn(move || {
-            println!("{:?}", apple);
-        });
+        // `out` cannot be shared with the threads, so each one hands its line back
+        handles.push(thread::spawn(move || format!("{:?}", apple)));
     }
-    thread::sleep(Duration::from_secs(1));
+    // joining waits for every thread instead of sleeping for a while
+    for handle in handles {
+        writeln!(out, "{}", handle.join().expect("the thread panicked"))?;
+    }
+    Ok(())
 }
 
-fn main() {
-    // part1();
-    part2();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "Rc: reference counting",
+        part2 => "Arc: sharing data between threads",
+    ])
 }
@@ -0,0 +1,718 @@
+/* Linear algebra */
+// decompositions and solvers over `DMatrix`: Gaussian elimination, LU with
+// partial pivoting, Householder QR, `solve`, least squares and polynomial
+// fits, rank and condition number
+
+// nothing here returns NaN or infinity for a bad system: singular,
+// ill-conditioned or mis-shaped input is a `LinalgError`, and so is a NaN or
+// infinite entry, which would slip through every comparison below. only
+// `row_echelon` and `rank`, which cannot fail, pass a NaN through
+
+use std::fmt;
+
+use crate::matrix::{DMatrix, Real};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinalgError {
+    NotSquare {
+        rows: usize,
+        cols: usize,
+    },
+    /// The right-hand side or the sample count does not match the matrix.
+    DimensionMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// No usable pivot in this column; the matrix is singular to working precision.
+    Singular {
+        column: usize,
+    },
+    /// Solvable, but the 1-norm condition number is so large that the
+    /// result would have no correct digits.
+    IllConditioned {
+        condition: f64,
+    },
+    /// Fewer equations than unknowns, so there is no unique least-squares solution.
+    Underdetermined {
+        rows: usize,
+        cols: usize,
+    },
+    /// A NaN or infinite entry in the matrix or the right-hand side.
+    NotFinite,
+}
+
+impl fmt::Display for LinalgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinalgError::NotSquare { rows, cols } => {
+                write!(f, "expected a square matrix, found {}x{}", rows, cols)
+            }
+            LinalgError::DimensionMismatch { expected, found } => {
+                write!(f, "expected {} values, found {}", expected, found)
+            }
+            LinalgError::Singular { column } => {
+                write!(f, "matrix is singular, no pivot in column {}", column)
+            }
+            LinalgError::IllConditioned { condition } => {
+                write!(
+                    f,
+                    "matrix is ill-conditioned, condition number {:e}",
+                    condition
+                )
+            }
+            LinalgError::Underdetermined { rows, cols } => {
+                write!(f, "{} equations cannot determine {} unknowns", rows, cols)
+            }
+            LinalgError::NotFinite => write!(f, "input contains NaN or infinity"),
+        }
+    }
+}
+
+impl std::error::Error for LinalgError {}
+
+fn max<T: Real>(values: impl Iterator<Item = T>) -> T {
+    values.fold(T::zero(), |max, x| if x > max { x } else { max })
+}
+
+/// Entries at or below this size count as zero: rounding noise relative to
+/// the largest entry of `a`.
+fn tolerance<T: Real>(a: &DMatrix<T>) -> T {
+    let scale = max(a.as_slice().iter().map(|x| x.abs()));
+    scale * T::EPSILON * T::from_usize(a.rows().max(a.cols()))
+}
+
+fn check_square<T>(a: &DMatrix<T>) -> Result<(), LinalgError> {
+    if a.is_square() {
+        Ok(())
+    } else {
+        Err(LinalgError::NotSquare {
+            rows: a.rows(),
+            cols: a.cols(),
+        })
+    }
+}
+
+fn check_len(expected: usize, found: usize) -> Result<(), LinalgError> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(LinalgError::DimensionMismatch { expected, found })
+    }
+}
+
+fn check_finite<T: Real>(values: &[T]) -> Result<(), LinalgError> {
+    if values.iter().all(|x| x.is_finite()) {
+        Ok(())
+    } else {
+        Err(LinalgError::NotFinite)
+    }
+}
+
+/// Estimates `‖A⁻¹‖₁` from a few solves with `A` and `Aᵀ`, without forming
+/// the inverse: Hager's method with Higham's extra test vector. The result
+/// is a lower bound, in practice exact or within a factor of about 3.
+fn inverse_norm1_estimate<T: Real>(
+    n: usize,
+    solve: impl Fn(Vec<T>) -> Vec<T>,
+    solve_transposed: impl Fn(Vec<T>) -> Vec<T>,
+) -> T {
+    if n == 0 {
+        return T::zero();
+    }
+    let norm = |v: &[T]| v.iter().fold(T::zero(), |sum, x| sum + x.abs());
+    let mut x = vec![T::one() / T::from_usize(n); n];
+    let mut estimate = T::zero();
+    for iteration in 0..5 {
+        let y = solve(x.clone());
+        let next = norm(&y);
+        if iteration > 0 && next <= estimate {
+            break;
+        }
+        estimate = next;
+        let signs = y
+            .iter()
+            .map(|&v| if v >= T::zero() { T::one() } else { -T::one() })
+            .collect();
+        let z = solve_transposed(signs);
+        let j = pivot_index(&z);
+        let zx = z
+            .iter()
+            .zip(&x)
+            .fold(T::zero(), |sum, (&a, &b)| sum + a * b);
+        if iteration > 0 && z[j].abs() <= zx {
+            break;
+        }
+        x = vec![T::zero(); n];
+        x[j] = T::one();
+    }
+    // alternating, growing entries catch what the unit vectors miss
+    let last = T::from_usize(n.saturating_sub(1).max(1));
+    let alternating = (0..n)
+        .map(|i| {
+            let entry = T::one() + T::from_usize(i) / last;
+            if i % 2 == 0 {
+                entry
+            } else {
+                -entry
+            }
+        })
+        .collect();
+    let two = T::one() + T::one();
+    let alternative = two * norm(&solve(alternating)) / (T::from_usize(3 * n));
+    if alternative > estimate {
+        alternative
+    } else {
+        estimate
+    }
+}
+
+/// Index of the largest entry by absolute value.
+fn pivot_index<T: Real>(v: &[T]) -> usize {
+    (0..v.len()).fold(
+        0,
+        |best, i| if v[i].abs() > v[best].abs() { i } else { best },
+    )
+}
+
+/// Solves `Rx = b` in place for upper triangular `R`.
+fn back_substitute<T: Real>(r: &DMatrix<T>, mut x: Vec<T>) -> Vec<T> {
+    for i in (0..x.len()).rev() {
+        for j in i + 1..x.len() {
+            x[i] = x[i] - r[(i, j)] * x[j];
+        }
+        x[i] = x[i] / r[(i, i)];
+    }
+    x
+}
+
+/// Solves `Rᵀx = b` in place for upper triangular `R`.
+fn forward_substitute_transposed<T: Real>(r: &DMatrix<T>, mut x: Vec<T>) -> Vec<T> {
+    for i in 0..x.len() {
+        for j in 0..i {
+            x[i] = x[i] - r[(j, i)] * x[j];
+        }
+        x[i] = x[i] / r[(i, i)];
+    }
+    x
+}
+
+/// `cond · ε ≥ 1` leaves no correct digit in the solution, and neither does
+/// an estimate that overflowed to infinity or NaN.
+fn check_condition<T: Real>(condition: T) -> Result<(), LinalgError> {
+    if !condition.is_finite() || condition * T::EPSILON >= T::one() {
+        Err(LinalgError::IllConditioned {
+            condition: condition.to_f64(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Index of the row at or below `from` with the largest entry in `col`.
+fn pivot_row<T: Real>(a: &DMatrix<T>, col: usize, from: usize) -> usize {
+    (from..a.rows()).fold(from, |best, row| {
+        if a[(row, col)].abs() > a[(best, col)].abs() {
+            row
+        } else {
+            best
+        }
+    })
+}
+
+/// Reduced row echelon form by Gaussian elimination with partial pivoting.
+/// Entries within rounding noise of zero are set to exactly zero.
+pub fn row_echelon<T: Real>(a: &DMatrix<T>) -> DMatrix<T> {
+    let tolerance = tolerance(a);
+    let mut a = a.clone();
+    let mut row = 0;
+
+    for col in 0..a.cols() {
+        if row == a.rows() {
+            break;
+        }
+        let pivot = pivot_row(&a, col, row);
+        if a[(pivot, col)].abs() <= tolerance {
+            for r in row..a.rows() {
+                a[(r, col)] = T::zero();
+            }
+            continue;
+        }
+        a.swap_rows(pivot, row);
+
+        let pivot = a[(row, col)];
+        for k in 0..a.cols() {
+            a[(row, k)] = a[(row, k)] / pivot;
+        }
+        for r in (0..a.rows()).filter(|&r| r != row) {
+            let factor = a[(r, col)];
+            for k in 0..a.cols() {
+                a[(r, k)] = a[(r, k)] - factor * a[(row, k)];
+            }
+        }
+        row += 1;
+    }
+    a
+}
+
+/// The number of linearly independent rows, up to rounding noise.
+pub fn rank<T: Real>(a: &DMatrix<T>) -> usize {
+    let echelon = row_echelon(a);
+    (0..echelon.rows())
+        .filter(|&row| echelon.row(row).iter().any(|&x| x != T::zero()))
+        .count()
+}
+
+/// `PA = LU`, with `L` unit lower triangular and `U` upper triangular,
+/// both packed into one matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lu<T> {
+    lu: DMatrix<T>,
+    /// `permutation[i]` is the row of `A` that ended up in row `i`.
+    permutation: Vec<usize>,
+    swaps: usize,
+    /// `‖A‖₁`, kept for the condition number.
+    norm: T,
+}
+
+impl<T: Real> Lu<T> {
+    pub fn new(a: &DMatrix<T>) -> Result<Lu<T>, LinalgError> {
+        check_square(a)?;
+        check_finite(a.as_slice())?;
+        let n = a.rows();
+        let tolerance = tolerance(a);
+        let mut lu = a.clone();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut swaps = 0;
+
+        for col in 0..n {
+            let pivot = pivot_row(&lu, col, col);
+            if lu[(pivot, col)].abs() <= tolerance {
+                return Err(LinalgError::Singular { column: col });
+            }
+            if pivot != col {
+                lu.swap_rows(pivot, col);
+                permutation.swap(pivot, col);
+                swaps += 1;
+            }
+            for row in col + 1..n {
+                let factor = lu[(row, col)] / lu[(col, col)];
+                lu[(row, col)] = factor;
+                for k in col + 1..n {
+                    lu[(row, k)] = lu[(row, k)] - factor * lu[(col, k)];
+                }
+            }
+        }
+        Ok(Lu {
+            lu,
+            permutation,
+            swaps,
+            norm: norm1(a),
+        })
+    }
+
+    pub fn l(&self) -> DMatrix<T> {
+        let n = self.lu.rows();
+        DMatrix::from_fn(n, n, |i, j| match i.cmp(&j) {
+            std::cmp::Ordering::Greater => self.lu[(i, j)],
+            std::cmp::Ordering::Equal => T::one(),
+            std::cmp::Ordering::Less => T::zero(),
+        })
+    }
+
+    pub fn u(&self) -> DMatrix<T> {
+        let n = self.lu.rows();
+        DMatrix::from_fn(
+            n,
+            n,
+            |i, j| if i <= j { self.lu[(i, j)] } else { T::zero() },
+        )
+    }
+
+    /// The permutation matrix `P`.
+    pub fn p(&self) -> DMatrix<T> {
+        let n = self.lu.rows();
+        DMatrix::from_fn(n, n, |i, j| {
+            if self.permutation[i] == j {
+                T::one()
+            } else {
+                T::zero()
+            }
+        })
+    }
+
+    pub fn determinant(&self) -> T {
+        let product = (0..self.lu.rows()).fold(T::one(), |det, i| det * self.lu[(i, i)]);
+        if self.swaps.is_multiple_of(2) {
+            product
+        } else {
+            -product
+        }
+    }
+
+    /// Solves `Ax = b` by forward and back substitution.
+    pub fn solve(&self, b: &[T]) -> Result<Vec<T>, LinalgError> {
+        let n = self.lu.rows();
+        check_len(n, b.len())?;
+        check_finite(b)?;
+
+        // Ly = Pb
+        let mut x: Vec<T> = self.permutation.iter().map(|&row| b[row]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] = x[i] - self.lu[(i, j)] * x[j];
+            }
+        }
+        // Ux = y
+        Ok(back_substitute(&self.lu, x))
+    }
+
+    // Aᵀ = UᵀLᵀP, so solve Uᵀz = b, then Lᵀw = z, and unpermute w
+    fn solve_transposed(&self, b: Vec<T>) -> Vec<T> {
+        let n = self.lu.rows();
+        let mut w = forward_substitute_transposed(&self.lu, b);
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                w[i] = w[i] - self.lu[(j, i)] * w[j];
+            }
+        }
+        let mut x = vec![T::zero(); n];
+        for (i, &row) in self.permutation.iter().enumerate() {
+            x[row] = w[i];
+        }
+        x
+    }
+
+    pub fn inverse(&self) -> DMatrix<T> {
+        let n = self.lu.rows();
+        let mut inverse = DMatrix::zeros(n, n);
+        for col in 0..n {
+            let unit: Vec<T> = (0..n)
+                .map(|i| if i == col { T::one() } else { T::zero() })
+                .collect();
+            // the length always matches, the factorisation is n x n
+            let x = self.solve(&unit).unwrap_or(unit);
+            for (row, value) in x.into_iter().enumerate() {
+                inverse[(row, col)] = value;
+            }
+        }
+        inverse
+    }
+
+    /// The 1-norm condition number `‖A‖₁ · ‖A⁻¹‖₁`, with `‖A⁻¹‖₁`
+    /// estimated from a handful of O(n²) solves rather than by forming the
+    /// inverse, which would cost another O(n³). The estimate never exceeds
+    /// the true value.
+    pub fn condition_number(&self) -> T {
+        let n = self.lu.rows();
+        let solve = |b: Vec<T>| self.solve(&b).unwrap_or(b);
+        self.norm * inverse_norm1_estimate(n, solve, |b| self.solve_transposed(b))
+    }
+}
+
+/// The largest absolute column sum.
+pub fn norm1<T: Real>(a: &DMatrix<T>) -> T {
+    max((0..a.cols())
+        .map(|col| (0..a.rows()).fold(T::zero(), |sum, row| sum + a[(row, col)].abs())))
+}
+
+/// `A = QR` for a matrix with at least as many rows as columns, with `Q`
+/// having orthonormal columns and `R` square upper triangular.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Qr<T> {
+    q: DMatrix<T>,
+    r: DMatrix<T>,
+}
+
+impl<T: Real> Qr<T> {
+    /// Householder reflections, one per column.
+    pub fn new(a: &DMatrix<T>) -> Result<Qr<T>, LinalgError> {
+        let (m, n) = (a.rows(), a.cols());
+        if m < n {
+            return Err(LinalgError::Underdetermined { rows: m, cols: n });
+        }
+        check_finite(a.as_slice())?;
+        let mut r = a.clone();
+        let mut q = DMatrix::identity(m);
+
+        for col in 0..n {
+            let norm = (col..m)
+                .fold(T::zero(), |sum, row| sum + r[(row, col)] * r[(row, col)])
+                .sqrt();
+            if norm == T::zero() {
+                continue;
+            }
+            // reflect onto -sign(x₀)‖x‖e₀ so the subtraction below never cancels
+            let alpha = if r[(col, col)] > T::zero() {
+                -norm
+            } else {
+                norm
+            };
+            let mut v: Vec<T> = (col..m).map(|row| r[(row, col)]).collect();
+            v[0] = v[0] - alpha;
+            let v_norm2 = v.iter().fold(T::zero(), |sum, &x| sum + x * x);
+            if v_norm2 == T::zero() {
+                continue;
+            }
+            let two = T::one() + T::one();
+
+            // R = H R and Q = Q H with H = I - 2vvᵀ/vᵀv
+            for k in 0..n {
+                let dot = (col..m).fold(T::zero(), |sum, row| sum + v[row - col] * r[(row, k)]);
+                let scale = two * dot / v_norm2;
+                for row in col..m {
+                    r[(row, k)] = r[(row, k)] - scale * v[row - col];
+                }
+            }
+            for row in 0..m {
+                let dot = (col..m).fold(T::zero(), |sum, k| sum + q[(row, k)] * v[k - col]);
+                let scale = two * dot / v_norm2;
+                for k in col..m {
+                    q[(row, k)] = q[(row, k)] - scale * v[k - col];
+                }
+            }
+        }
+
+        Ok(Qr {
+            q: DMatrix::from_fn(m, n, |i, j| q[(i, j)]),
+            r: DMatrix::from_fn(n, n, |i, j| if i <= j { r[(i, j)] } else { T::zero() }),
+        })
+    }
+
+    pub fn q(&self) -> &DMatrix<T> {
+        &self.q
+    }
+
+    pub fn r(&self) -> &DMatrix<T> {
+        &self.r
+    }
+
+    /// The 1-norm condition number of `R`, which is that of `A` up to a
+    /// factor of at most `√m` either way; estimated like
+    /// [`Lu::condition_number`].
+    pub fn condition_number(&self) -> T {
+        let n = self.r.cols();
+        let solve = |b: Vec<T>| back_substitute(&self.r, b);
+        let solve_transposed = |b: Vec<T>| forward_substitute_transposed(&self.r, b);
+        norm1(&self.r) * inverse_norm1_estimate(n, solve, solve_transposed)
+    }
+
+    /// The `x` minimising `‖Ax - b‖₂`, from `Rx = Qᵀb`, refusing problems
+    /// whose condition number means the answer would be noise.
+    pub fn least_squares(&self, b: &[T]) -> Result<Vec<T>, LinalgError> {
+        let (m, n) = (self.q.rows(), self.r.cols());
+        check_len(m, b.len())?;
+        check_finite(b)?;
+        // a column depends on the ones before it when almost none of its
+        // length is left on the diagonal; judging each column by its own
+        // length keeps badly scaled columns from passing for zero
+        let rows = T::from_usize(m);
+        let dependent = |i: usize| {
+            let length = (0..=i)
+                .fold(T::zero(), |sum, k| sum + self.r[(k, i)] * self.r[(k, i)])
+                .sqrt();
+            self.r[(i, i)].abs() <= length * T::EPSILON * rows
+        };
+        if let Some(i) = (0..n).rev().find(|&i| dependent(i)) {
+            return Err(LinalgError::Singular { column: i });
+        }
+        check_condition(self.condition_number())?;
+
+        let qtb: Vec<T> = (0..n)
+            .map(|j| (0..m).fold(T::zero(), |sum, i| sum + self.q[(i, j)] * b[i]))
+            .collect();
+        Ok(back_substitute(&self.r, qtb))
+    }
+}
+
+/// Solves the square system `Ax = b`, refusing systems whose condition
+/// number means the answer would be noise.
+pub fn solve<T: Real>(a: &DMatrix<T>, b: &[T]) -> Result<Vec<T>, LinalgError> {
+    let lu = Lu::new(a)?;
+    check_condition(lu.condition_number())?;
+    lu.solve(b)
+}
+
+/// The `x` minimising `‖Ax - b‖₂` for an overdetermined system; like
+/// [`solve`], ill-conditioned systems are an error.
+pub fn least_squares<T: Real>(a: &DMatrix<T>, b: &[T]) -> Result<Vec<T>, LinalgError> {
+    Qr::new(a)?.least_squares(b)
+}
+
+/// Coefficients `c₀, c₁, …` of the polynomial `c₀ + c₁x + … + cₙxⁿ` of the
+/// given degree that best fits the points, e.g. a calibration curve. High
+/// degrees over a wide range of `x` make the fit ill-conditioned.
+pub fn polyfit<T: Real>(xs: &[T], ys: &[T], degree: usize) -> Result<Vec<T>, LinalgError> {
+    check_len(xs.len(), ys.len())?;
+    // the Vandermonde matrix, one row of powers per sample
+    let vandermonde = DMatrix::from_fn(xs.len(), degree + 1, |i, j| {
+        (0..j).fold(T::one(), |power, _| power * xs[i])
+    });
+    least_squares(&vandermonde, ys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &[f64], b: &[f64], epsilon: f64) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < epsilon, "{:?} != {:?}", a, b);
+        }
+    }
+
+    fn system() -> DMatrix<f64> {
+        DMatrix::from_vec(3, 3, vec![2.0, 1.0, -1.0, -3.0, -1.0, 2.0, -2.0, 1.0, 2.0])
+    }
+
+    #[test]
+    fn lu_reconstructs_and_solves() {
+        let a = system();
+        let lu = Lu::new(&a).unwrap();
+        let pa = &lu.p() * &a;
+        assert_close(pa.as_slice(), (&lu.l() * &lu.u()).as_slice(), 1e-12);
+        assert!((lu.determinant() - a.determinant().unwrap()).abs() < 1e-12);
+
+        let x = solve(&a, &[8.0, -11.0, -3.0]).unwrap();
+        assert_close(&x, &[2.0, 3.0, -1.0], 1e-12);
+        assert_close(
+            (&a * &lu.inverse()).as_slice(),
+            DMatrix::identity(3).as_slice(),
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn qr_is_orthonormal_and_triangular() {
+        let a = DMatrix::from_vec(4, 3, (1..=12).map(f64::from).collect());
+        let qr = Qr::new(&a).unwrap();
+        let qtq = &qr.q().transpose() * qr.q();
+        assert_close(qtq.as_slice(), DMatrix::identity(3).as_slice(), 1e-12);
+        assert_close((qr.q() * qr.r()).as_slice(), a.as_slice(), 1e-12);
+        assert!((0..3).all(|i| (0..i).all(|j| qr.r()[(i, j)] == 0.0)));
+    }
+
+    #[test]
+    fn fits_calibration_curves() {
+        // y = 0.5 + 2x, with and without noise
+        let xs = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let ys: Vec<f64> = xs.iter().map(|x| 0.5 + 2.0 * x).collect();
+        assert_close(&polyfit(&xs, &ys, 1).unwrap(), &[0.5, 2.0], 1e-12);
+
+        let noisy = [0.6, 2.4, 4.6, 6.4, 8.6];
+        let line = polyfit(&xs, &noisy, 1).unwrap();
+        assert_close(&line, &[0.52, 2.0], 1e-12);
+
+        let ys: Vec<f64> = xs.iter().map(|x| 1.0 - x + 0.25 * x * x).collect();
+        assert_close(&polyfit(&xs, &ys, 2).unwrap(), &[1.0, -1.0, 0.25], 1e-12);
+    }
+
+    #[test]
+    fn rank_and_condition() {
+        assert_eq!(rank(&system()), 3);
+        let dependent = DMatrix::from_vec(3, 3, vec![1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 1.0, 0.0, 1.0]);
+        assert_eq!(rank(&dependent), 2);
+        assert_eq!(rank(&DMatrix::<f64>::zeros(2, 4)), 0);
+
+        let identity = Lu::new(&DMatrix::<f64>::identity(4)).unwrap();
+        assert_eq!(identity.condition_number(), 1.0);
+        let a = DMatrix::from_vec(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        // ‖A‖₁ = 6, ‖A⁻¹‖₁ = 3.5
+        assert!((Lu::new(&a).unwrap().condition_number() - 21.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn condition_estimates_bound_the_exact_value() {
+        let a = DMatrix::from_fn(6, 6, |i, j| {
+            let k = (i * 7 + j * 3) % 11;
+            k as f64 - 5.0 + if i == j { 0.5 } else { 0.0 }
+        });
+        let lu = Lu::new(&a).unwrap();
+        let exact = norm1(&a) * norm1(&lu.inverse());
+        let estimate = lu.condition_number();
+        assert!(estimate <= exact * (1.0 + 1e-12) && estimate >= exact / 3.0);
+
+        // Aᵀx = b
+        let x = lu.solve_transposed(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let atx = &a.transpose() * &DMatrix::from_vec(6, 1, x);
+        assert_close(atx.as_slice(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 1e-9);
+
+        let system = system();
+        let exact = norm1(&system) * norm1(&Lu::new(&system).unwrap().inverse());
+        let qr = Qr::new(&system).unwrap();
+        let r_exact = norm1(qr.r()) * norm1(&Lu::new(qr.r()).unwrap().inverse());
+        assert!((qr.condition_number() - r_exact).abs() < 1e-9 * r_exact);
+        // within √3 of A's own condition number either way
+        assert!(qr.condition_number() < exact * 3.0 && qr.condition_number() > exact / 3.0);
+    }
+
+    #[test]
+    fn typed_errors_instead_of_nans() {
+        let dependent = DMatrix::from_vec(2, 2, vec![1.0, 2.0, 2.0, 4.0]);
+        assert_eq!(
+            solve(&dependent, &[1.0, 2.0]),
+            Err(LinalgError::Singular { column: 1 })
+        );
+        assert_eq!(
+            solve(&system(), &[1.0]),
+            Err(LinalgError::DimensionMismatch {
+                expected: 3,
+                found: 1
+            })
+        );
+        assert_eq!(
+            solve(&DMatrix::<f64>::zeros(2, 3), &[1.0, 2.0]),
+            Err(LinalgError::NotSquare { rows: 2, cols: 3 })
+        );
+        assert_eq!(
+            least_squares(&DMatrix::<f64>::zeros(2, 3), &[1.0, 2.0]),
+            Err(LinalgError::Underdetermined { rows: 2, cols: 3 })
+        );
+        // every sample at the same x cannot fix a slope
+        assert_eq!(
+            polyfit(&[1.0, 1.0, 1.0], &[1.0, 2.0, 3.0], 1),
+            Err(LinalgError::Singular { column: 1 })
+        );
+
+        // a degree 12 polynomial over x up to 30: the columns span 30¹²
+        let xs: Vec<f64> = (0..=30).map(f64::from).collect();
+        let ys: Vec<f64> = xs.iter().map(|x| x.sin()).collect();
+        assert!(matches!(
+            polyfit(&xs, &ys, 12),
+            Err(LinalgError::IllConditioned { condition }) if condition > 1e15
+        ));
+        assert!(polyfit(&xs, &ys, 3).is_ok());
+
+        // the 12x12 Hilbert matrix still factorises, but its condition is ~4e16
+        let hilbert = DMatrix::from_fn(12, 12, |i, j| 1.0 / (i + j + 1) as f64);
+        assert!(matches!(
+            solve(&hilbert, &[1.0; 12]),
+            Err(LinalgError::IllConditioned { condition }) if condition > 1e15
+        ));
+    }
+
+    #[test]
+    fn nan_and_infinity_are_rejected() {
+        let mut nan = system();
+        nan[(1, 1)] = f64::NAN;
+        assert_eq!(solve(&nan, &[1.0, 2.0, 3.0]), Err(LinalgError::NotFinite));
+        assert_eq!(Lu::new(&nan), Err(LinalgError::NotFinite));
+        assert_eq!(
+            least_squares(&nan, &[1.0, 2.0, 3.0]),
+            Err(LinalgError::NotFinite)
+        );
+        assert_eq!(
+            solve(&system(), &[1.0, f64::NAN, 3.0]),
+            Err(LinalgError::NotFinite)
+        );
+        assert_eq!(
+            polyfit(&[0.0, 1.0, f64::INFINITY], &[1.0, 2.0, 3.0], 1),
+            Err(LinalgError::NotFinite)
+        );
+        assert_eq!(
+            polyfit(&[0.0, 1.0, 2.0], &[1.0, f64::NAN, 3.0], 1),
+            Err(LinalgError::NotFinite)
+        );
+        assert!(check_condition(f64::NAN).is_err());
+    }
+}
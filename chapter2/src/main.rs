@@ -1,75 +1,388 @@
-use std::fmt;
+use std::io::{self, Write};
+
+mod linalg;
+mod matmul;
+mod matrix;
+mod ring;
+mod stats;
+mod tuples;
+
+use matrix::{DMatrix, Matrix};
+use tuples::{MapWith, Reverse, Show, TupleArray, Zip};
 //
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     // Simple Operators
-    println!("1i32 - 2 = {}", 1i32 - 2);
-    println!("1 - 2 = {}", 1 - 2);
-    // println!("1u32 - 2 = {}", 1u32-2); // overflow
-    println!("1e4={}", 1e4);
+    writeln!(out, "1i32 - 2 = {}", 1i32 - 2)?;
+    writeln!(out, "1 - 2 = {}", 1 - 2)?;
+    // writeln!(out, "1u32 - 2 = {}", 1u32-2)?; // overflow
+    writeln!(out, "1e4={}", 1e4)?;
 
     // tuples
     let tuple = (1u8, 2u16, 3u32);
-    println!("tuple first value: {}", tuple.0);
-    println!("tuple second value: {}", tuple.1);
+    writeln!(out, "tuple first value: {}", tuple.0)?;
+    writeln!(out, "tuple second value: {}", tuple.1)?;
 
     let tuple_of_tuples = ((1, 2, 3), (4, 5, 6));
-    println!("tuple of tuples: {:?}", tuple_of_tuples);
-    // tuples beyond size of 12 cannot be printed
+    writeln!(out, "tuple of tuples: {:?}", tuple_of_tuples)?;
+    // tuples beyond size of 12 cannot be printed with {:?}, see part8
     let t2 = (1, 2);
-    println!("Reversed pair = {:?}", reverse(t2));
+    writeln!(out, "Reversed pair = {:?}", t2.reverse())?;
 
-    let my_matrix = Matrix(1.1, 2.2, 3.3, 2.2);
-    println!("{}", my_matrix);
-    println!("Transpose:\n{}", transpose(&my_matrix));
-}
-
-fn reverse(pair: (i32, i32)) -> (i32, i32) {
-    (pair.1, pair.0)
-}
-
-struct Matrix(f32, f32, f32, f32);
-impl fmt::Display for Matrix {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "( {} {} )\n( {} {} )", self.0, self.1, self.2, self.3)
-    }
-}
-fn transpose(matrix: &Matrix) -> Matrix {
-    Matrix(matrix.0, matrix.2, matrix.1, matrix.3)
+    let my_matrix = Matrix::new([[1.1_f32, 2.2], [3.3, 2.2]]);
+    writeln!(out, "{}", my_matrix)?;
+    writeln!(out, "Transpose:\n{}", my_matrix.transpose())?;
+    Ok(())
 }
 
 // Arrays and slices
 use std::mem;
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     // a slice is kind of array whose length is not known at compile time
     let array /*: [i32; 5]*/ = [1, 2, 3, 4, 5];
     let array2 = [0; 500]; // 500 elements with value 0
-    println!("array occupies {} bytes", mem::size_of_val(&array));
-    println!("array2 occupies {} bytes", mem::size_of_val(&array2));
+    writeln!(out, "array occupies {} bytes", mem::size_of_val(&array))?;
+    writeln!(out, "array2 occupies {} bytes", mem::size_of_val(&array2))?;
 
     // how we can take slices
-    println!("Whole array as slice:-");
-    analyze_slice(&array);
+    writeln!(out, "Whole array as slice:-")?;
+    analyze_slice(out, &array)?;
 
-    println!("Section of array as slice:-");
-    analyze_slice(&array[1..4]);
+    writeln!(out, "Section of array as slice:-")?;
+    analyze_slice(out, &array[1..4])?;
 
     // empty array: []
 
     // safe accession of data -> get method
     for i in 0..array.len() + 1 {
         match array.get(i) {
-            Some(val) => println!("{}: {}", i, val),
-            None => println!("Some non existing element was asked!"),
+            Some(val) => writeln!(out, "{}: {}", i, val)?,
+            None => writeln!(out, "Some non existing element was asked!")?,
+        }
+    }
+    Ok(())
+}
+
+fn analyze_slice(out: &mut dyn Write, slice: &[i32]) -> io::Result<()> {
+    // `slice[0]` would panic on an empty slice
+    match slice.first() {
+        Some(first) => writeln!(out, "First element: {}", first)?,
+        None => writeln!(out, "Slice is empty")?,
+    }
+    writeln!(out, "Slice has {} elements", slice.len())?;
+    Ok(())
+}
+
+// Pretty printing nested values
+// `{:?}` puts everything on one line and `{:#?}` breaks every level;
+// support::pretty only breaks the levels that do not fit the width
+use std::collections::BTreeMap;
+use support::Pretty;
+
+#[allow(dead_code)]
+#[derive(Debug)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+struct Shape {
+    name: &'static str,
+    corners: Vec<Point>,
+}
+
+fn part3(out: &mut dyn Write) -> io::Result<()> {
+    let tuple_of_tuples = ((1, 2, 3), (4, 5, 6));
+    for width in [40, 20, 8] {
+        writeln!(out, "width {}:\n{}", width, Pretty(&tuple_of_tuples, width))?;
+    }
+
+    let triangle = Shape {
+        name: "triangle",
+        corners: vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 4.0, y: 0.0 },
+            Point { x: 0.0, y: 3.0 },
+        ],
+    };
+    writeln!(out, "{:?}", triangle)?;
+    writeln!(out, "{}", Pretty(&triangle, 40))?;
+
+    let mut areas = BTreeMap::new();
+    areas.insert("square", vec![1.0, 4.0, 9.0]);
+    areas.insert("circle", vec![0.79, 12.57]);
+    writeln!(out, "{}", Pretty(&areas, 30))?;
+    Ok(())
+}
+
+// Matrices
+// the 2x2 tuple struct of part1 generalised, see matrix.rs
+fn part4(out: &mut dyn Write) -> io::Result<()> {
+    let a = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+    let b = Matrix::new([[7, 8], [9, 10], [11, 12]]);
+    writeln!(out, "a =\n{}\nb =\n{}", a, b)?;
+    // (2x3) * (3x2) = (2x2), `a * a` would not compile
+    writeln!(out, "a * b =\n{}", a * b)?;
+    writeln!(out, "a + a =\n{}", a + a)?;
+    writeln!(out, "a * I =\n{}", a * Matrix::identity())?;
+
+    let m = Matrix::new([[4.0, 7.0], [2.0, 6.0]]);
+    writeln!(out, "m =\n{}\ndet(m) = {}", m, m.determinant())?;
+    if let Some(inverse) = m.inverse() {
+        writeln!(out, "m^-1 =\n{:.2}", inverse)?;
+        writeln!(out, "m * m^-1 =\n{:.2}", m * inverse)?;
+    }
+    let singular = Matrix::new([[1.0, 2.0], [2.0, 4.0]]);
+    writeln!(out, "inverse of\n{}\nis {:?}", singular, singular.inverse())?;
+
+    // the Display layout parses back
+    let text = "( 1 0 2 )\n( 0 3 0 )\n( 4 0 5 )";
+    match text.parse::<Matrix<f64, 3, 3>>() {
+        Ok(parsed) => writeln!(out, "parsed, det = {}", parsed.determinant())?,
+        Err(e) => writeln!(out, "error: {}", e)?,
+    }
+    match text.parse::<Matrix<f64, 2, 2>>() {
+        Ok(parsed) => writeln!(out, "parsed:\n{}", parsed)?,
+        Err(e) => writeln!(out, "error: {}", e)?,
+    }
+
+    // sized at runtime, shapes are checked when the operation runs
+    let d: DMatrix<f64> = text.parse().unwrap_or_else(|_| DMatrix::identity(3));
+    let column = DMatrix::from_vec(3, 1, vec![1.0, 1.0, 1.0]);
+    writeln!(out, "d * column =\n{}", &d * &column)?;
+    writeln!(out, "column * column: {:?}", column.checked_mul(&column))?;
+    Ok(())
+}
+
+// Solving linear systems
+// see linalg.rs, everything works on the runtime sized DMatrix
+fn part5(out: &mut dyn Write) -> io::Result<()> {
+    // 2x + y - z = 8, -3x - y + 2z = -11, -2x + y + 2z = -3
+    let a = DMatrix::from_vec(3, 3, vec![2.0, 1.0, -1.0, -3.0, -1.0, 2.0, -2.0, 1.0, 2.0]);
+    let b = [8.0, -11.0, -3.0];
+    match linalg::solve(&a, &b) {
+        Ok(x) => writeln!(out, "solution: {:.3?}", x)?,
+        Err(e) => writeln!(out, "error: {}", e)?,
+    }
+
+    if let Ok(lu) = linalg::Lu::new(&a) {
+        writeln!(out, "P =\n{}\nL =\n{:.3}\nU =\n{:.3}", lu.p(), lu.l(), lu.u())?;
+        writeln!(out, "det = {:.3}", lu.determinant())?;
+        writeln!(out, "condition number = {:.3}", lu.condition_number())?;
+        writeln!(out, "A⁻¹ =\n{:.3}", lu.inverse())?;
+    }
+    if let Ok(qr) = linalg::Qr::new(&a) {
+        writeln!(out, "Q =\n{:.3}\nR =\n{:.3}", qr.q(), qr.r())?;
+    }
+    writeln!(out, "reduced row echelon form =\n{:.3}", linalg::row_echelon(&a))?;
+
+    // a calibration curve: sensor readings against known reference values
+    let readings = [0.12, 1.05, 2.11, 2.94, 4.02, 5.10];
+    let reference = [0.0, 10.0, 20.0, 30.0, 40.0, 50.0];
+    match linalg::polyfit(&readings, &reference, 1) {
+        Ok(c) => writeln!(out, "reference = {:.3} + {:.3} * reading", c[0], c[1])?,
+        Err(e) => writeln!(out, "error: {}", e)?,
+    }
+    match linalg::polyfit(&readings, &reference, 2) {
+        Ok(c) => writeln!(
+            out,
+            "reference = {:.3} + {:.3} * reading + {:.3} * reading^2",
+            c[0], c[1], c[2]
+        )?,
+        Err(e) => writeln!(out, "error: {}", e)?,
+    }
+
+    // bad systems are errors, not NaNs
+    let dependent = DMatrix::from_vec(3, 3, vec![1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 1.0, 0.0, 1.0]);
+    writeln!(out, "rank of\n{}\nis {}", dependent, linalg::rank(&dependent))?;
+    for system in [
+        dependent,
+        DMatrix::from_fn(12, 12, |i, j| 1.0 / (i + j + 1) as f64),
+    ] {
+        let ones = vec![1.0; system.rows()];
+        match linalg::solve(&system, &ones) {
+            Ok(x) => writeln!(out, "solution: {:?}", x)?,
+            Err(e) => writeln!(out, "error: {}", e)?,
         }
     }
+    Ok(())
 }
 
-fn analyze_slice(slice: &[i32]) {
-    println!("First element: {}", slice[0]);
-    println!("Slice has {} elements", slice.len());
+// Benchmarking matrix multiplication
+// timings change from run to run, so this part has no snapshot;
+// build with `--release` to see the real difference
+fn part6(out: &mut dyn Write) -> io::Result<()> {
+    let size = if cfg!(debug_assertions) { 128 } else { 512 };
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let a = DMatrix::from_fn(size, size, |i, j| ((i * 7 + j * 3) % 17) as f32 / 17.0);
+    let b = DMatrix::from_fn(size, size, |i, j| ((i * 5 + j * 11) % 13) as f32 / 13.0);
+    writeln!(out, "{0}x{0} f32, best of 3 runs", size)?;
+
+    let (naive_time, reference) = matmul::time(3, || matmul::naive(&a, &b));
+    let (blocked_time, blocked) = matmul::time(3, || matmul::blocked(&a, &b));
+    let (parallel_time, parallel) = matmul::time(3, || matmul::parallel(&a, &b, threads));
+
+    let mut table = support::Table::new(["method", "time", "speedup", "agrees"])
+        .align(1, support::table::Align::Right)
+        .align(2, support::table::Align::Right);
+    for (name, elapsed, c) in [
+        (String::from("naive"), naive_time, &reference),
+        (String::from("blocked"), blocked_time, &blocked),
+        (format!("parallel x{}", threads), parallel_time, &parallel),
+    ] {
+        let speedup = naive_time.as_secs_f64() / elapsed.as_secs_f64();
+        let agrees = matmul::agrees(&a, &b, &reference, c);
+        table.row([
+            name,
+            format!("{:.2?}", elapsed),
+            format!("{:.1}x", speedup),
+            agrees.to_string(),
+        ]);
+    }
+    write!(out, "{}", table)?;
+    Ok(())
+}
+
+// Statistics over slices
+// analyze_slice grown into stats.rs; empty input is None or an error
+fn part7(out: &mut dyn Write) -> io::Result<()> {
+    let samples = [12, 15, 11, 15, 18, 21, 15, 9, 14, 30, 17, 13];
+    writeln!(out, "samples: {:?}", samples)?;
+    analyze_slice(out, &samples)?;
+    writeln!(out, "mean: {:.3?}", stats::mean(samples))?;
+    writeln!(out, "median: {:?}", stats::median(samples))?;
+    writeln!(out, "mode: {:?}", stats::mode(samples))?;
+    writeln!(out, "variance: {:.3?}", stats::variance(samples))?;
+    writeln!(out, "std dev: {:.3?}", stats::std_dev(samples))?;
+    writeln!(out, "sample std dev: {:.3?}", stats::sample_std_dev(samples))?;
+    for p in [25.0, 90.0, 120.0] {
+        match stats::percentile(samples, p) {
+            Ok(value) => writeln!(out, "{}th percentile: {:.2}", p, value)?,
+            Err(e) => writeln!(out, "error: {}", e)?,
+        }
+    }
+    writeln!(out, "min (index, value): {:?}", stats::min_with_index(samples))?;
+    writeln!(out, "max (index, value): {:?}", stats::max_with_index(samples))?;
+
+    let histogram = stats::Histogram::of(samples, 4).map(|h| h.bar_width(20));
+    match histogram {
+        Ok(histogram) => write!(out, "{:.1}", histogram)?,
+        Err(e) => writeln!(out, "error: {}", e)?,
+    }
+    // a fixed range counts what falls outside it apart
+    if let Ok(mut typical) = stats::Histogram::new(10.0, 20.0, 2) {
+        typical.extend(samples);
+        writeln!(
+            out,
+            "10..=20 in halves: {:?}, (below, above): {:?}",
+            typical.counts(),
+            typical.outliers()
+        )?;
+    }
+
+    // nothing to panic on
+    let empty: [i32; 0] = [];
+    analyze_slice(out, &empty)?;
+    writeln!(out, "mean of nothing: {:?}", stats::mean(empty))?;
+    writeln!(out, "median of nothing: {:?}", stats::median(empty))?;
+
+    // a stream that is never collected: two halves summarised apart, then merged
+    let readings = (0..100_000).map(|i| 20.0 + ((i * 37) % 101) as f64 / 100.0);
+    let (mut even, mut odd) = (stats::Welford::new(), stats::Welford::new());
+    for (i, x) in readings.enumerate() {
+        if i % 2 == 0 {
+            even.push(x);
+        } else {
+            odd.push(x);
+        }
+    }
+    let all = even.merge(&odd);
+    writeln!(
+        out,
+        "streamed {} readings: mean {:.4}, std dev {:.4} ({:.4} as a sample), range {:?}..={:?}",
+        all.count(),
+        all.mean().unwrap_or(f64::NAN),
+        all.std_dev().unwrap_or(f64::NAN),
+        all.sample_std_dev().unwrap_or(f64::NAN),
+        all.min().unwrap_or(f64::NAN),
+        all.max().unwrap_or(f64::NAN)
+    )?;
+    Ok(())
+}
+
+// Tuples of any arity
+// std stops implementing Debug at 12 elements, tuples.rs goes up to 16
+fn part8(out: &mut dyn Write) -> io::Result<()> {
+    let record = (
+        "sensor-7", 42, 'N', 21.5, true, None::<u8>, -3, 1e3, "ok", 10, 11, 12, 13, 14, 15, 16,
+    );
+    // writeln!(out, "{:?}", record)?; // error: `(..)` doesn't implement `Debug`
+    writeln!(out, "record: {}", Show(&record))?;
+    writeln!(out, "reversed: {}", Show(&record.reverse()))?;
+
+    // a Poly function handles every element type, a closure could not
+    let columns = record.map_with(&mut tuples::ToDebugString);
+    writeln!(out, "as strings: {:?}", columns.into_array())?;
+
+    let names = ("x", "y", "z");
+    let values = (1.5, -2.0, 0.25);
+    writeln!(out, "zipped: {:?}", names.zip(values))?;
+
+    // tuples of one type convert to and from arrays
+    let squares = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14).map_each(|x| x * x);
+    let array = squares.into_array();
+    writeln!(out, "squares as an array: {:?}", array)?;
+    let (first, second, third): (char, char, char) = TupleArray::from_array(['a', 'b', 'c']);
+    writeln!(out, "from an array: {:?}", (first, second, third))?;
+    Ok(())
+}
+
+// A ring buffer on a fixed array
+// like `[0; 500]` in part2 the storage is part of the value, so unlike a
+// Vec it never reallocates; see ring.rs
+fn part9(out: &mut dyn Write) -> io::Result<()> {
+    let mut ring: ring::RingBuffer<&str, 3> = ring::RingBuffer::new();
+    writeln!(out, "capacity {}, {} bytes", ring.capacity(), mem::size_of_val(&ring))?;
+    for word in ["b", "c"] {
+        let _ = ring.push_back(word);
+    }
+    let _ = ring.push_front("a");
+    writeln!(out, "{:?}, full: {}", ring, ring.is_full())?;
+    // a full buffer hands the value back
+    writeln!(out, "push_back(\"d\") -> {:?}", ring.push_back("d"))?;
+    writeln!(out, "pop_front() -> {:?}", ring.pop_front())?;
+    writeln!(out, "pop_back() -> {:?}", ring.pop_back())?;
+    writeln!(out, "{:?}, ring[0] = {}", ring, ring[0])?;
+
+    // a sliding window: the moving average of the last 4 sensor samples
+    let samples = [20.1, 20.4, 21.0, 35.7, 21.2, 20.9, 20.6, 20.8];
+    let mut window: ring::RingBuffer<f64, 4> = ring::RingBuffer::new();
+    for sample in samples {
+        let evicted = window.push_overwrite(sample);
+        let average = window.iter().sum::<f64>() / window.len() as f64;
+        writeln!(
+            out,
+            "sample {:>4.1} evicted {:<10} window {:?} average {:.2}",
+            sample,
+            format!("{:?}", evicted),
+            window,
+            average
+        )?;
+    }
+    Ok(())
 }
 
-fn main() {
-    part1();
-    part2();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "primitives: literals, operators, tuples and a first look at the generic Matrix",
+        part2 => "arrays and slices",
+        part3 => "pretty printing nested values to a width",
+        part4 => "generic matrices: operators, determinant, inverse and parsing",
+        part5 => "linear systems: LU, QR, least squares and condition numbers",
+        part6 => "benchmark: naive, cache-blocked and parallel matrix multiplication",
+        part7 => "statistics: summaries, percentiles, histograms and streaming",
+        part8 => "tuples past 12 elements: printing, reverse, zip, map and arrays",
+        part9 => "a fixed-capacity ring buffer and a sliding window",
+    ])
 }
@@ -0,0 +1,691 @@
+/* Matrices */
+// `Matrix<T, R, C>` keeps its size in the type, so adding a 2x3 to a 3x2 or
+// multiplying mismatched shapes does not compile; `DMatrix<T>` is sized at
+// runtime and checks its shapes when the operation runs
+
+// both print one row per line as `( a b c )`, the layout of the old 2x2
+// tuple struct, and parse that layout back with `FromStr`
+
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// Numbers a matrix can hold.
+pub trait Scalar:
+    Copy + PartialEq + fmt::Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+}
+
+/// Scalars with a division, needed by the determinant and the inverse.
+pub trait Real: Scalar + PartialOrd + Neg<Output = Self> + std::ops::Div<Output = Self> {
+    const EPSILON: Self;
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn from_usize(n: usize) -> Self;
+    fn to_f64(self) -> f64;
+    fn is_finite(self) -> bool;
+}
+
+macro_rules! scalar {
+    ($zero:literal, $one:literal: $($t:ty),+) => {
+        $(
+            impl Scalar for $t {
+                fn zero() -> $t {
+                    $zero
+                }
+
+                fn one() -> $t {
+                    $one
+                }
+            }
+        )+
+    };
+}
+
+scalar!(0, 1: i8, i16, i32, i64, i128, isize);
+scalar!(0.0, 1.0: f32, f64);
+
+macro_rules! real {
+    ($($t:ident),+) => {
+        $(
+            impl Real for $t {
+                const EPSILON: $t = $t::EPSILON;
+
+                fn abs(self) -> $t {
+                    $t::abs(self)
+                }
+
+                fn sqrt(self) -> $t {
+                    $t::sqrt(self)
+                }
+
+                fn from_usize(n: usize) -> $t {
+                    n as $t
+                }
+
+                fn to_f64(self) -> f64 {
+                    self.into()
+                }
+
+                fn is_finite(self) -> bool {
+                    $t::is_finite(self)
+                }
+            }
+        )+
+    };
+}
+
+real!(f32, f64);
+
+/// Gauss-Jordan elimination with partial pivoting on the row-major `n`x`n`
+/// matrix `a`, applying the same row operations to the `n`x`m` matrix `rhs`.
+///
+/// Returns the determinant of `a`, or `None` when a pivot is too small
+/// compared to the largest entry, meaning `a` is singular to working precision.
+/// On success `rhs` holds `a⁻¹ · rhs`.
+fn gauss_jordan<T: Real>(a: &mut [T], rhs: &mut [T], n: usize, m: usize) -> Option<T> {
+    let scale = a
+        .iter()
+        .map(|x| x.abs())
+        .fold(T::zero(), |max, x| if x > max { x } else { max });
+    let tolerance = scale * T::EPSILON * T::from_usize(n);
+    let mut determinant = T::one();
+
+    for col in 0..n {
+        let pivot_row = (col..n)
+            .max_by(|&i, &j| {
+                let (x, y) = (a[i * n + col].abs(), a[j * n + col].abs());
+                x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(col);
+        let pivot = a[pivot_row * n + col];
+        // written so that a NaN pivot counts as singular too
+        if pivot.abs().partial_cmp(&tolerance) != Some(std::cmp::Ordering::Greater) {
+            return None;
+        }
+        if pivot_row != col {
+            swap_rows(a, n, pivot_row, col);
+            swap_rows(rhs, m, pivot_row, col);
+            determinant = -determinant;
+        }
+        determinant = determinant * pivot;
+
+        for x in &mut a[col * n..(col + 1) * n] {
+            *x = *x / pivot;
+        }
+        for x in &mut rhs[col * m..(col + 1) * m] {
+            *x = *x / pivot;
+        }
+        for row in (0..n).filter(|&row| row != col) {
+            let factor = a[row * n + col];
+            if factor == T::zero() {
+                continue;
+            }
+            for k in 0..n {
+                a[row * n + k] = a[row * n + k] - factor * a[col * n + k];
+            }
+            for k in 0..m {
+                rhs[row * m + k] = rhs[row * m + k] - factor * rhs[col * m + k];
+            }
+        }
+    }
+    Some(determinant)
+}
+
+fn swap_rows<T>(data: &mut [T], width: usize, i: usize, j: usize) {
+    if width == 0 || i == j {
+        return;
+    }
+    let (low, high) = (i.min(j), i.max(j));
+    let (top, bottom) = data.split_at_mut(high * width);
+    top[low * width..(low + 1) * width].swap_with_slice(&mut bottom[..width]);
+}
+
+fn write_rows<'a, T, I>(f: &mut fmt::Formatter, rows: I) -> fmt::Result
+where
+    T: fmt::Display + 'a,
+    I: Iterator<Item = &'a [T]>,
+{
+    for (index, row) in rows.enumerate() {
+        if index > 0 {
+            writeln!(f)?;
+        }
+        write!(f, "(")?;
+        for x in row {
+            write!(f, " ")?;
+            // passes `{:.2}` and friends on to every element
+            fmt::Display::fmt(x, f)?;
+        }
+        write!(f, " )")?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseMatrixError {
+    Empty,
+    /// A line that is not wrapped in `(` and `)`.
+    MissingParens {
+        row: usize,
+    },
+    InvalidElement {
+        row: usize,
+        column: usize,
+        text: String,
+    },
+    /// A row with a different number of elements than the first one.
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Parsed fine, but the shape does not match the `Matrix` type.
+    Shape {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+}
+
+impl fmt::Display for ParseMatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseMatrixError::Empty => write!(f, "empty matrix"),
+            ParseMatrixError::MissingParens { row } => {
+                write!(f, "row {} is not wrapped in `(` and `)`", row)
+            }
+            ParseMatrixError::InvalidElement { row, column, text } => {
+                write!(
+                    f,
+                    "invalid element {:?} at row {}, column {}",
+                    text, row, column
+                )
+            }
+            ParseMatrixError::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {} has {} elements, expected {}",
+                row, found, expected
+            ),
+            ParseMatrixError::Shape { expected, found } => write!(
+                f,
+                "expected a {}x{} matrix, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseMatrixError {}
+
+/// A matrix with `R` rows and `C` columns, stored row by row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix<T, const R: usize, const C: usize>([[T; C]; R]);
+
+impl<T, const R: usize, const C: usize> Matrix<T, R, C> {
+    pub fn new(rows: [[T; C]; R]) -> Matrix<T, R, C> {
+        Matrix(rows)
+    }
+
+    /// Builds each element from its `(row, column)`.
+    pub fn from_fn(mut f: impl FnMut(usize, usize) -> T) -> Matrix<T, R, C> {
+        Matrix(std::array::from_fn(|i| std::array::from_fn(|j| f(i, j))))
+    }
+
+    pub fn rows(&self) -> &[[T; C]; R] {
+        &self.0
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Matrix<T, R, C> {
+    pub fn zeros() -> Matrix<T, R, C> {
+        Matrix([[T::zero(); C]; R])
+    }
+
+    pub fn transpose(&self) -> Matrix<T, C, R> {
+        Matrix::from_fn(|i, j| self.0[j][i])
+    }
+}
+
+impl<T: Scalar, const N: usize> Matrix<T, N, N> {
+    pub fn identity() -> Matrix<T, N, N> {
+        Matrix::from_fn(|i, j| if i == j { T::one() } else { T::zero() })
+    }
+}
+
+impl<T: Real, const N: usize> Matrix<T, N, N> {
+    pub fn determinant(&self) -> T {
+        let mut a: Vec<T> = self.0.iter().flatten().copied().collect();
+        gauss_jordan(&mut a, &mut [], N, 0).unwrap_or(T::zero())
+    }
+
+    /// `None` when the matrix is singular.
+    pub fn inverse(&self) -> Option<Matrix<T, N, N>> {
+        let mut a: Vec<T> = self.0.iter().flatten().copied().collect();
+        let mut inverse: Vec<T> = Matrix::<T, N, N>::identity()
+            .0
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        gauss_jordan(&mut a, &mut inverse, N, N)?;
+        Some(Matrix::from_fn(|i, j| inverse[i * N + j]))
+    }
+}
+
+impl<T, const R: usize, const C: usize> Index<(usize, usize)> for Matrix<T, R, C> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        &self.0[row][col]
+    }
+}
+
+impl<T, const R: usize, const C: usize> IndexMut<(usize, usize)> for Matrix<T, R, C> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        &mut self.0[row][col]
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Add for Matrix<T, R, C> {
+    type Output = Matrix<T, R, C>;
+
+    fn add(self, other: Matrix<T, R, C>) -> Matrix<T, R, C> {
+        Matrix::from_fn(|i, j| self.0[i][j] + other.0[i][j])
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Sub for Matrix<T, R, C> {
+    type Output = Matrix<T, R, C>;
+
+    fn sub(self, other: Matrix<T, R, C>) -> Matrix<T, R, C> {
+        Matrix::from_fn(|i, j| self.0[i][j] - other.0[i][j])
+    }
+}
+
+// (R x K) * (K x C) = (R x C), any other shape is a type error
+impl<T: Scalar, const R: usize, const K: usize, const C: usize> Mul<Matrix<T, K, C>>
+    for Matrix<T, R, K>
+{
+    type Output = Matrix<T, R, C>;
+
+    fn mul(self, other: Matrix<T, K, C>) -> Matrix<T, R, C> {
+        Matrix::from_fn(|i, j| (0..K).fold(T::zero(), |sum, k| sum + self.0[i][k] * other.0[k][j]))
+    }
+}
+
+impl<T: fmt::Display, const R: usize, const C: usize> fmt::Display for Matrix<T, R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_rows(f, self.0.iter().map(|row| &row[..]))
+    }
+}
+
+impl<T: FromStr, const R: usize, const C: usize> FromStr for Matrix<T, R, C> {
+    type Err = ParseMatrixError;
+
+    fn from_str(text: &str) -> Result<Matrix<T, R, C>, ParseMatrixError> {
+        let matrix: DMatrix<T> = text.parse()?;
+        if (matrix.rows, matrix.cols) != (R, C) {
+            return Err(ParseMatrixError::Shape {
+                expected: (R, C),
+                found: (matrix.rows, matrix.cols),
+            });
+        }
+        let mut elements = matrix.data.into_iter();
+        // the shape was checked, so there is an element for every slot
+        Ok(Matrix::from_fn(|_, _| elements.next().unwrap()))
+    }
+}
+
+/// A matrix sized at runtime. Operators panic on mismatched shapes, the
+/// `checked_` methods return `None` instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DMatrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T> DMatrix<T> {
+    /// `data` holds the elements row by row.
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> DMatrix<T> {
+        assert_eq!(
+            data.len(),
+            rows * cols,
+            "a {}x{} matrix needs {} elements",
+            rows,
+            cols,
+            rows * cols
+        );
+        DMatrix { rows, cols, data }
+    }
+
+    pub fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(usize, usize) -> T) -> DMatrix<T> {
+        let data = (0..rows * cols)
+            .map(|index| f(index / cols, index % cols))
+            .collect();
+        DMatrix { rows, cols, data }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// The elements row by row.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn swap_rows(&mut self, i: usize, j: usize) {
+        swap_rows(&mut self.data, self.cols, i, j);
+    }
+}
+
+impl<T: Scalar> DMatrix<T> {
+    pub fn zeros(rows: usize, cols: usize) -> DMatrix<T> {
+        DMatrix::from_vec(rows, cols, vec![T::zero(); rows * cols])
+    }
+
+    pub fn identity(n: usize) -> DMatrix<T> {
+        DMatrix::from_fn(n, n, |i, j| if i == j { T::one() } else { T::zero() })
+    }
+
+    pub fn transpose(&self) -> DMatrix<T> {
+        DMatrix::from_fn(self.cols, self.rows, |i, j| self[(j, i)])
+    }
+
+    pub fn checked_add(&self, other: &DMatrix<T>) -> Option<DMatrix<T>> {
+        let same_shape = (self.rows, self.cols) == (other.rows, other.cols);
+        same_shape
+            .then(|| DMatrix::from_fn(self.rows, self.cols, |i, j| self[(i, j)] + other[(i, j)]))
+    }
+
+    pub fn checked_sub(&self, other: &DMatrix<T>) -> Option<DMatrix<T>> {
+        let same_shape = (self.rows, self.cols) == (other.rows, other.cols);
+        same_shape
+            .then(|| DMatrix::from_fn(self.rows, self.cols, |i, j| self[(i, j)] - other[(i, j)]))
+    }
+
+    pub fn checked_mul(&self, other: &DMatrix<T>) -> Option<DMatrix<T>> {
+        (self.cols == other.rows).then(|| {
+            DMatrix::from_fn(self.rows, other.cols, |i, j| {
+                (0..self.cols).fold(T::zero(), |sum, k| sum + self[(i, k)] * other[(k, j)])
+            })
+        })
+    }
+}
+
+impl<T: Real> DMatrix<T> {
+    /// `None` for a matrix that is not square.
+    pub fn determinant(&self) -> Option<T> {
+        if !self.is_square() {
+            return None;
+        }
+        let mut a = self.data.clone();
+        Some(gauss_jordan(&mut a, &mut [], self.rows, 0).unwrap_or(T::zero()))
+    }
+
+    /// `None` when the matrix is not square or is singular.
+    pub fn inverse(&self) -> Option<DMatrix<T>> {
+        if !self.is_square() {
+            return None;
+        }
+        let mut a = self.data.clone();
+        let mut inverse = DMatrix::identity(self.rows);
+        gauss_jordan(&mut a, &mut inverse.data, self.rows, self.rows)?;
+        Some(inverse)
+    }
+}
+
+impl<T> Index<(usize, usize)> for DMatrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(col < self.cols, "column {} out of range", col);
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for DMatrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(col < self.cols, "column {} out of range", col);
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+impl<T: Scalar> Add for &DMatrix<T> {
+    type Output = DMatrix<T>;
+
+    fn add(self, other: &DMatrix<T>) -> DMatrix<T> {
+        self.checked_add(other).unwrap_or_else(|| {
+            panic!(
+                "cannot add a {}x{} matrix to a {}x{} one",
+                other.rows, other.cols, self.rows, self.cols
+            )
+        })
+    }
+}
+
+impl<T: Scalar> Sub for &DMatrix<T> {
+    type Output = DMatrix<T>;
+
+    fn sub(self, other: &DMatrix<T>) -> DMatrix<T> {
+        self.checked_sub(other).unwrap_or_else(|| {
+            panic!(
+                "cannot subtract a {}x{} matrix from a {}x{} one",
+                other.rows, other.cols, self.rows, self.cols
+            )
+        })
+    }
+}
+
+impl<T: Scalar> Mul for &DMatrix<T> {
+    type Output = DMatrix<T>;
+
+    fn mul(self, other: &DMatrix<T>) -> DMatrix<T> {
+        self.checked_mul(other).unwrap_or_else(|| {
+            panic!(
+                "cannot multiply a {}x{} matrix by a {}x{} one",
+                self.rows, self.cols, other.rows, other.cols
+            )
+        })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for DMatrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // `chunks` would yield nothing at all for zero columns
+        write_rows(
+            f,
+            (0..self.rows).map(|row| &self.data[row * self.cols..(row + 1) * self.cols]),
+        )
+    }
+}
+
+impl<T: FromStr> FromStr for DMatrix<T> {
+    type Err = ParseMatrixError;
+
+    fn from_str(text: &str) -> Result<DMatrix<T>, ParseMatrixError> {
+        let mut data = Vec::new();
+        let mut cols = None;
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.is_empty() {
+            return Err(ParseMatrixError::Empty);
+        }
+
+        for (row, line) in lines.iter().enumerate() {
+            let inner = line
+                .strip_prefix('(')
+                .and_then(|line| line.strip_suffix(')'))
+                .ok_or(ParseMatrixError::MissingParens { row })?;
+            let mut found = 0;
+            for (column, element) in inner.split_whitespace().enumerate() {
+                let value = element
+                    .parse()
+                    .map_err(|_| ParseMatrixError::InvalidElement {
+                        row,
+                        column,
+                        text: element.to_owned(),
+                    })?;
+                data.push(value);
+                found += 1;
+            }
+            match cols {
+                None => cols = Some(found),
+                Some(expected) if expected != found => {
+                    return Err(ParseMatrixError::Ragged {
+                        row,
+                        expected,
+                        found,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(DMatrix {
+            rows: lines.len(),
+            cols: cols.unwrap_or(0),
+            data,
+        })
+    }
+}
+
+impl<T: Copy, const R: usize, const C: usize> From<Matrix<T, R, C>> for DMatrix<T> {
+    fn from(matrix: Matrix<T, R, C>) -> DMatrix<T> {
+        DMatrix::from_fn(R, C, |i, j| matrix.0[i][j])
+    }
+}
+
+impl<T: Copy, const R: usize, const C: usize> TryFrom<DMatrix<T>> for Matrix<T, R, C> {
+    type Error = DMatrix<T>;
+
+    /// Gives the matrix back when its shape is not `R`x`C`.
+    fn try_from(matrix: DMatrix<T>) -> Result<Matrix<T, R, C>, DMatrix<T>> {
+        if (matrix.rows, matrix.cols) == (R, C) {
+            Ok(Matrix::from_fn(|i, j| matrix[(i, j)]))
+        } else {
+            Err(matrix)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close<const N: usize>(a: Matrix<f64, N, N>, b: Matrix<f64, N, N>) -> bool {
+        (0..N).all(|i| (0..N).all(|j| (a[(i, j)] - b[(i, j)]).abs() < 1e-12))
+    }
+
+    #[test]
+    fn display_keeps_the_tuple_struct_layout() {
+        let m = Matrix::new([[1.1_f32, 2.2], [3.3, 2.2]]);
+        assert_eq!(m.to_string(), "( 1.1 2.2 )\n( 3.3 2.2 )");
+        assert_eq!(m.transpose().to_string(), "( 1.1 3.3 )\n( 2.2 2.2 )");
+        assert_eq!(format!("{:.2}", Matrix::new([[1.0, 0.5]])), "( 1.00 0.50 )");
+    }
+
+    #[test]
+    fn operators() {
+        let a = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+        let b = Matrix::new([[7, 8], [9, 10], [11, 12]]);
+        assert_eq!(a * b, Matrix::new([[58, 64], [139, 154]]));
+        assert_eq!(a + a, Matrix::new([[2, 4, 6], [8, 10, 12]]));
+        assert_eq!(a - a, Matrix::zeros());
+        assert_eq!(a * Matrix::identity(), a);
+        assert_eq!(a.transpose().transpose(), a);
+    }
+
+    #[test]
+    fn determinant_and_inverse() {
+        let m = Matrix::new([[4.0, 7.0], [2.0, 6.0]]);
+        assert!((m.determinant() - 10.0).abs() < 1e-12);
+        let inverse = m.inverse().unwrap();
+        assert!(close(m * inverse, Matrix::identity()));
+
+        // swapping rows flips the sign
+        let m = Matrix::new([[0.0, 1.0, 2.0], [1.0, 0.0, 3.0], [4.0, -3.0, 8.0]]);
+        assert!((m.determinant() - -2.0).abs() < 1e-12);
+        assert!(close(m.inverse().unwrap() * m, Matrix::identity()));
+
+        let singular = Matrix::new([[1.0, 2.0], [2.0, 4.0]]);
+        assert_eq!(singular.inverse(), None);
+        assert_eq!(singular.determinant(), 0.0);
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        let m = Matrix::new([[0.1, -2.5e10, 3.0], [f64::MAX, 1.0 / 3.0, -0.0]]);
+        assert_eq!(m.to_string().parse::<Matrix<f64, 2, 3>>(), Ok(m));
+        assert_eq!(
+            "( 1 2 )\n( 3 4 )".parse::<Matrix<i32, 2, 3>>(),
+            Err(ParseMatrixError::Shape {
+                expected: (2, 3),
+                found: (2, 2)
+            })
+        );
+        assert_eq!(
+            "( 1 2 )\n( 3 )".parse::<DMatrix<i32>>(),
+            Err(ParseMatrixError::Ragged {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            "( 1 x )".parse::<DMatrix<i32>>(),
+            Err(ParseMatrixError::InvalidElement {
+                row: 0,
+                column: 1,
+                text: String::from("x")
+            })
+        );
+        assert_eq!(
+            "1 2".parse::<DMatrix<i32>>(),
+            Err(ParseMatrixError::MissingParens { row: 0 })
+        );
+    }
+
+    #[test]
+    fn dynamic_matrices() {
+        let a = DMatrix::from(Matrix::new([[1.0, 2.0], [3.0, 4.0]]));
+        let b = DMatrix::from_vec(2, 1, vec![5.0, 6.0]);
+        assert_eq!(&a * &b, DMatrix::from_vec(2, 1, vec![17.0, 39.0]));
+        assert_eq!(a.checked_add(&b), None);
+        assert_eq!(b.checked_mul(&b), None);
+        assert_eq!(a.determinant().map(f64::round), Some(-2.0));
+        assert_eq!(b.determinant(), None);
+
+        let product = &a * &a.inverse().unwrap();
+        let identity = Matrix::<f64, 2, 2>::try_from(product).unwrap();
+        assert!(close(identity, Matrix::identity()));
+        assert_eq!(a.to_string().parse(), Ok(a));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot multiply a 2x1 matrix by a 2x1 one")]
+    fn mismatched_shapes_panic() {
+        let b = DMatrix::from_vec(2, 1, vec![5.0, 6.0]);
+        let _ = &b * &b;
+    }
+}
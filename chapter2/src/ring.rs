@@ -0,0 +1,386 @@
+/* Fixed-capacity ring buffer */
+// `RingBuffer<T, N>` lives entirely in an `[MaybeUninit<T>; N]` array, so
+// unlike a `Vec` it never allocates: pushing onto a full buffer either
+// hands the value back or, with `push_overwrite`, evicts the oldest one
+
+// only the `len` slots starting at `head` (wrapping around the end of the
+// array) are initialised; every unsafe block relies on that invariant
+
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ops::{Index, IndexMut};
+
+pub struct RingBuffer<T, const N: usize> {
+    slots: [MaybeUninit<T>; N],
+    /// Slot of the front element.
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    pub const fn new() -> RingBuffer<T, N> {
+        RingBuffer {
+            slots: [const { MaybeUninit::uninit() }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// The slot of the element at logical `index`; only called with `N > 0`.
+    fn slot(&self, index: usize) -> usize {
+        (self.head + index) % N
+    }
+
+    /// Appends at the back, or gives `value` back when the buffer is full.
+    pub fn push_back(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        let slot = self.slot(self.len);
+        self.slots[slot].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Prepends at the front, or gives `value` back when the buffer is full.
+    pub fn push_front(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.head = (self.head + N - 1) % N;
+        self.slots[self.head].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Appends at the back, evicting and returning the front element when the
+    /// buffer is full, which keeps the last `N` values of a stream.
+    pub fn push_overwrite(&mut self, value: T) -> Option<T> {
+        if N == 0 {
+            return Some(value);
+        }
+        let evicted = if self.is_full() {
+            self.pop_front()
+        } else {
+            None
+        };
+        // there is room now, so this cannot fail
+        let _ = self.push_back(value);
+        evicted
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let slot = self.head;
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        // SAFETY: the front slot was live, and is now outside the live
+        // range, so it is read exactly once
+        Some(unsafe { self.slots[slot].assume_init_read() })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.slot(self.len);
+        // SAFETY: as in `pop_front`, the back slot was live and no longer is
+        Some(unsafe { self.slots[slot].assume_init_read() })
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        // SAFETY: `index < len`, so the slot is live
+        Some(unsafe { self.slots[self.slot(index)].assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.slot(index);
+        // SAFETY: `index < len`, so the slot is live
+        Some(unsafe { self.slots[slot].assume_init_mut() })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|last| self.get(last))
+    }
+
+    /// The live elements in logical order: the part up to the end of the
+    /// array, then the part that wrapped around to its start.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (first, second) = self.live_ranges();
+        // SAFETY: both ranges hold exactly the live slots, and
+        // `MaybeUninit<T>` has the same layout as `T`
+        unsafe {
+            (
+                &*(&self.slots[first] as *const [MaybeUninit<T>] as *const [T]),
+                &*(&self.slots[second] as *const [MaybeUninit<T>] as *const [T]),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (first, second) = self.live_ranges();
+        // the second range always starts at slot 0, below the first
+        let (start, end) = self.slots.split_at_mut(first.start);
+        // SAFETY: as in `as_slices`, and the two slices do not overlap
+        unsafe {
+            (
+                &mut *(&mut end[..first.len()] as *mut [MaybeUninit<T>] as *mut [T]),
+                &mut *(&mut start[second] as *mut [MaybeUninit<T>] as *mut [T]),
+            )
+        }
+    }
+
+    fn live_ranges(&self) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let first_len = self.len.min(N - self.head.min(N));
+        (self.head..self.head + first_len, 0..self.len - first_len)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (first, second) = self.as_slices();
+        Chain(first.iter(), second.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (first, second) = self.as_mut_slices();
+        Chain(first.iter_mut(), second.iter_mut())
+    }
+
+    /// Drops every element.
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
+        self.head = 0;
+    }
+}
+
+/// `Iterator::chain` is not `ExactSizeIterator`, this one is.
+pub struct Chain<A, B>(A, B);
+
+pub type Iter<'a, T> = Chain<std::slice::Iter<'a, T>, std::slice::Iter<'a, T>>;
+pub type IterMut<'a, T> = Chain<std::slice::IterMut<'a, T>, std::slice::IterMut<'a, T>>;
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Iterator for Chain<A, B> {
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        self.0.next().or_else(|| self.1.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let ((a, _), (b, _)) = (self.0.size_hint(), self.1.size_hint());
+        (a + b, Some(a + b))
+    }
+}
+
+impl<A, B> DoubleEndedIterator for Chain<A, B>
+where
+    A: DoubleEndedIterator,
+    B: DoubleEndedIterator<Item = A::Item>,
+{
+    fn next_back(&mut self) -> Option<A::Item> {
+        self.1.next_back().or_else(|| self.0.next_back())
+    }
+}
+
+impl<A: ExactSizeIterator, B: ExactSizeIterator<Item = A::Item>> ExactSizeIterator for Chain<A, B> {}
+
+impl<T, const N: usize> Drop for RingBuffer<T, N> {
+    fn drop(&mut self) {
+        // the uninitialised slots must not be dropped, so no `drop_in_place` over all of them
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    fn default() -> RingBuffer<T, N> {
+        RingBuffer::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for RingBuffer<T, N> {
+    fn clone(&self) -> RingBuffer<T, N> {
+        let mut clone = RingBuffer::new();
+        for value in self.iter() {
+            // same capacity, so there is always room
+            let _ = clone.push_back(value.clone());
+        }
+        clone
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for RingBuffer<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for RingBuffer<T, N> {
+    fn eq(&self, other: &RingBuffer<T, N>) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, const N: usize> Index<usize> for RingBuffer<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {} out of range for length {}", index, len))
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for RingBuffer<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index {} out of range for length {}", index, len))
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a RingBuffer<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Consumes the buffer, front to back.
+pub struct IntoIter<T, const N: usize>(RingBuffer<T, N>);
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> IntoIterator for RingBuffer<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> IntoIter<T, N> {
+        IntoIter(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn both_ends() {
+        let mut ring: RingBuffer<i32, 3> = RingBuffer::new();
+        assert_eq!(ring.pop_front(), None);
+        assert_eq!(ring.push_back(2), Ok(()));
+        assert_eq!(ring.push_front(1), Ok(()));
+        assert_eq!(ring.push_back(3), Ok(()));
+        assert_eq!(ring.push_back(4), Err(4));
+        assert_eq!(ring.push_front(0), Err(0));
+        assert_eq!(format!("{:?}", ring), "[1, 2, 3]");
+        assert_eq!((ring.front(), ring.back()), (Some(&1), Some(&3)));
+
+        assert_eq!(ring.pop_back(), Some(3));
+        assert_eq!(ring.pop_front(), Some(1));
+        assert_eq!(ring.push_back(5), Ok(()));
+        assert_eq!(ring.push_back(6), Ok(()));
+        // the elements now wrap around the end of the array
+        assert_eq!(ring.as_slices().0.len() + ring.as_slices().1.len(), 3);
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), [2, 5, 6]);
+        assert_eq!(ring.iter().rev().copied().collect::<Vec<_>>(), [6, 5, 2]);
+        assert_eq!(ring.iter().len(), 3);
+        assert_eq!(ring[1], 5);
+        ring[1] = 50;
+        for x in ring.iter_mut() {
+            *x += 1;
+        }
+        assert_eq!(ring.into_iter().collect::<Vec<_>>(), [3, 51, 7]);
+    }
+
+    #[test]
+    fn overwrite_keeps_the_newest() {
+        let mut window: RingBuffer<u32, 4> = RingBuffer::new();
+        let evicted: Vec<Option<u32>> = (1..=6).map(|x| window.push_overwrite(x)).collect();
+        assert_eq!(evicted, [None, None, None, None, Some(1), Some(2)]);
+        assert_eq!(window.iter().copied().collect::<Vec<_>>(), [3, 4, 5, 6]);
+        assert_eq!(window.clone(), window);
+
+        let mut nothing: RingBuffer<u32, 0> = RingBuffer::new();
+        assert_eq!(nothing.push_overwrite(1), Some(1));
+        assert_eq!(nothing.push_back(1), Err(1));
+        assert!(nothing.is_empty() && nothing.is_full());
+        assert_eq!(nothing.iter().count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 2 out of range for length 2")]
+    fn index_out_of_range() {
+        let mut ring: RingBuffer<i32, 4> = RingBuffer::new();
+        ring.push_back(1).unwrap();
+        ring.push_back(2).unwrap();
+        let _ = ring[2];
+    }
+
+    #[test]
+    fn drops_only_live_elements() {
+        let counter = Rc::new(());
+        {
+            let mut ring: RingBuffer<Rc<()>, 4> = RingBuffer::new();
+            for _ in 0..6 {
+                ring.push_overwrite(Rc::clone(&counter));
+            }
+            assert_eq!(Rc::strong_count(&counter), 5);
+            ring.pop_front();
+            assert_eq!(Rc::strong_count(&counter), 4);
+            // two live elements left in the iterator are dropped with it
+            let mut values = ring.into_iter();
+            values.next();
+            assert_eq!(Rc::strong_count(&counter), 3);
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
@@ -0,0 +1,551 @@
+/* Statistics */
+// summaries of numeric data, where empty input gives `None` or an error
+// instead of the panic of `slice[0]`, a streaming accumulator for data that
+// is never all in memory at once, and fixed-bucket histograms
+
+// any `Copy + Into<f64>` number works: every integer up to 32 bits, f32, f64.
+// the summaries take anything iterable, so a slice is `xs.iter().copied()`
+// and an array or a `Vec` can be passed by value
+
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsError {
+    Empty,
+    /// A percentile outside `0..=100`, or NaN.
+    InvalidPercentile(f64),
+    /// A histogram range whose `min` is not below its `max`.
+    InvalidRange {
+        min: f64,
+        max: f64,
+    },
+    NoBuckets,
+    /// A NaN among the values, which has no place in an ordering.
+    NotANumber,
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsError::Empty => write!(f, "no values"),
+            StatsError::InvalidPercentile(p) => {
+                write!(f, "percentile {} is not between 0 and 100", p)
+            }
+            StatsError::InvalidRange { min, max } => {
+                write!(f, "invalid range {}..{}", min, max)
+            }
+            StatsError::NoBuckets => write!(f, "a histogram needs at least one bucket"),
+            StatsError::NotANumber => write!(f, "the values include NaN"),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+pub fn mean<T: Copy + Into<f64>>(values: impl IntoIterator<Item = T>) -> Option<f64> {
+    values.into_iter().collect::<Welford>().mean()
+}
+
+/// The middle value, or the mean of the two middle values; `None` when
+/// there are no values or one is NaN.
+pub fn median<T: Copy + Into<f64>>(values: impl IntoIterator<Item = T>) -> Option<f64> {
+    percentile(values, 50.0).ok()
+}
+
+/// The most frequent value; ties go to the smallest. NaNs are skipped, as
+/// a NaN is not even equal to itself.
+pub fn mode<T: Copy + PartialOrd>(values: impl IntoIterator<Item = T>) -> Option<T> {
+    let mut sorted: Vec<T> = values
+        .into_iter()
+        .filter(|value| value.partial_cmp(value).is_some())
+        .collect();
+    // without NaNs every pair compares
+    sorted.sort_by(|a, b| a.partial_cmp(b).expect("NaNs were skipped"));
+
+    let mut best: Option<(T, usize)> = None;
+    let mut run_start = 0;
+    for index in 1..=sorted.len() {
+        if index == sorted.len() || sorted[index] != sorted[run_start] {
+            let count = index - run_start;
+            if best.is_none_or(|(_, best_count)| count > best_count) {
+                best = Some((sorted[run_start], count));
+            }
+            run_start = index;
+        }
+    }
+    best.map(|(value, _)| value)
+}
+
+/// The population variance, dividing by `n`.
+pub fn variance<T: Copy + Into<f64>>(values: impl IntoIterator<Item = T>) -> Option<f64> {
+    values.into_iter().collect::<Welford>().variance()
+}
+
+/// The sample variance, dividing by `n - 1`; `None` for fewer than two values.
+pub fn sample_variance<T: Copy + Into<f64>>(values: impl IntoIterator<Item = T>) -> Option<f64> {
+    values.into_iter().collect::<Welford>().sample_variance()
+}
+
+/// The population standard deviation.
+pub fn std_dev<T: Copy + Into<f64>>(values: impl IntoIterator<Item = T>) -> Option<f64> {
+    variance(values).map(f64::sqrt)
+}
+
+pub fn sample_std_dev<T: Copy + Into<f64>>(values: impl IntoIterator<Item = T>) -> Option<f64> {
+    sample_variance(values).map(f64::sqrt)
+}
+
+/// The `p`th percentile, interpolating linearly between the closest ranks,
+/// so the 50th is the median.
+pub fn percentile<T: Copy + Into<f64>>(
+    values: impl IntoIterator<Item = T>,
+    p: f64,
+) -> Result<f64, StatsError> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err(StatsError::InvalidPercentile(p));
+    }
+    let mut sorted: Vec<f64> = values.into_iter().map(Into::into).collect();
+    if sorted.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    if sorted.iter().any(|x| x.is_nan()) {
+        return Err(StatsError::NotANumber);
+    }
+    sorted.sort_by(f64::total_cmp);
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    let fraction = rank - below as f64;
+    Ok(sorted[below] + (sorted[above] - sorted[below]) * fraction)
+}
+
+/// The index and value of the first smallest value; NaNs are never the minimum.
+pub fn min_with_index<T: Copy + PartialOrd>(
+    values: impl IntoIterator<Item = T>,
+) -> Option<(usize, T)> {
+    extreme(values, Ordering::Less)
+}
+
+/// The index and value of the first largest value; NaNs are never the maximum.
+pub fn max_with_index<T: Copy + PartialOrd>(
+    values: impl IntoIterator<Item = T>,
+) -> Option<(usize, T)> {
+    extreme(values, Ordering::Greater)
+}
+
+fn extreme<T: Copy + PartialOrd>(
+    values: impl IntoIterator<Item = T>,
+    wanted: Ordering,
+) -> Option<(usize, T)> {
+    let mut best: Option<(usize, T)> = None;
+    for (index, value) in values.into_iter().enumerate() {
+        // a NaN is not even equal to itself
+        if value.partial_cmp(&value).is_none() {
+            continue;
+        }
+        match best {
+            Some((_, current)) if value.partial_cmp(&current) != Some(wanted) => {}
+            _ => best = Some((index, value)),
+        }
+    }
+    best
+}
+
+/// Streaming mean and variance by Welford's method: one pass, constant
+/// memory, and no catastrophic cancellation from summing squares.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Welford {
+    count: u64,
+    mean: f64,
+    /// Sum of squared differences from the current mean.
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Welford {
+    pub fn new() -> Welford {
+        Welford::default()
+    }
+
+    pub fn push(&mut self, x: impl Into<f64>) {
+        let x = x.into();
+        if self.count == 0 {
+            self.min = x;
+            self.max = x;
+        } else {
+            self.min = self.min.min(x);
+            self.max = self.max.max(x);
+        }
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Combines two accumulators, e.g. of chunks summarised on separate threads.
+    pub fn merge(&self, other: &Welford) -> Welford {
+        match (self.count, other.count) {
+            (0, _) => *other,
+            (_, 0) => *self,
+            (n, m) => {
+                let count = n + m;
+                let delta = other.mean - self.mean;
+                let weight = m as f64 / count as f64;
+                Welford {
+                    count,
+                    mean: self.mean + delta * weight,
+                    m2: self.m2 + other.m2 + delta * delta * n as f64 * weight,
+                    min: self.min.min(other.min),
+                    max: self.max.max(other.max),
+                }
+            }
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    pub fn variance(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.m2 / self.count as f64)
+    }
+
+    pub fn sample_variance(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.m2 / (self.count - 1) as f64)
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    pub fn sample_std_dev(&self) -> Option<f64> {
+        self.sample_variance().map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+}
+
+impl<T: Into<f64>> Extend<T> for Welford {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        for x in values {
+            self.push(x);
+        }
+    }
+}
+
+impl<T: Into<f64>> FromIterator<T> for Welford {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Welford {
+        let mut welford = Welford::new();
+        welford.extend(values);
+        welford
+    }
+}
+
+/// Counts of values in equal-width buckets over `min..max`, printed as bars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    min: f64,
+    max: f64,
+    counts: Vec<u64>,
+    below: u64,
+    above: u64,
+    bar_width: usize,
+}
+
+impl Histogram {
+    pub fn new(min: f64, max: f64, buckets: usize) -> Result<Histogram, StatsError> {
+        if !min.is_finite() || !max.is_finite() || min >= max {
+            return Err(StatsError::InvalidRange { min, max });
+        }
+        if buckets == 0 {
+            return Err(StatsError::NoBuckets);
+        }
+        Ok(Histogram {
+            min,
+            max,
+            counts: vec![0; buckets],
+            below: 0,
+            above: 0,
+            bar_width: 40,
+        })
+    }
+
+    /// Buckets spanning the smallest to the largest of `values`.
+    pub fn of<T: Copy + Into<f64>>(
+        values: impl IntoIterator<Item = T>,
+        buckets: usize,
+    ) -> Result<Histogram, StatsError> {
+        // two passes, one for the range and one to count
+        let values: Vec<f64> = values.into_iter().map(Into::into).collect();
+        let summary: Welford = values.iter().copied().collect();
+        let (min, max) = match (summary.min(), summary.max()) {
+            (Some(min), Some(max)) => (min, max),
+            _ => return Err(StatsError::Empty),
+        };
+        // all values equal still needs a range of some width; past 2^53 adding
+        // one changes nothing, so at least step to the next float
+        let (min, max) = if max > min {
+            (min, max)
+        } else if min < f64::MAX {
+            (min, (min + 1.0).max(min.next_up()))
+        } else {
+            (min.next_down(), max)
+        };
+        let mut histogram = Histogram::new(min, max, buckets)?;
+        histogram.extend(values);
+        Ok(histogram)
+    }
+
+    /// Characters in the longest bar.
+    pub fn bar_width(mut self, width: usize) -> Histogram {
+        self.bar_width = width;
+        self
+    }
+
+    /// Values outside the range are counted separately; `max` itself goes in the last bucket.
+    pub fn add(&mut self, x: impl Into<f64>) {
+        let x = x.into();
+        if x < self.min {
+            self.below += 1;
+        } else if x > self.max {
+            self.above += 1;
+        } else if x == self.max {
+            if let Some(last) = self.counts.last_mut() {
+                *last += 1;
+            }
+        } else if x >= self.min {
+            // NaN fails every comparison and is not counted at all
+            let width = (self.max - self.min) / self.counts.len() as f64;
+            let bucket = (((x - self.min) / width) as usize).min(self.counts.len() - 1);
+            self.counts[bucket] += 1;
+        }
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Values below and above the range.
+    pub fn outliers(&self) -> (u64, u64) {
+        (self.below, self.above)
+    }
+
+    /// The `min..max` range of each bucket.
+    pub fn bounds(&self, bucket: usize) -> (f64, f64) {
+        let width = (self.max - self.min) / self.counts.len() as f64;
+        let start = self.min + width * bucket as f64;
+        let end = if bucket + 1 == self.counts.len() {
+            self.max
+        } else {
+            start + width
+        };
+        (start, end)
+    }
+}
+
+impl<T: Into<f64>> Extend<T> for Histogram {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        for x in values {
+            self.add(x);
+        }
+    }
+}
+
+impl fmt::Display for Histogram {
+    /// One line per bucket, e.g. `[ 0.00,  2.50) ####### 7`; a precision
+    /// such as `{:.1}` applies to the bounds.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precision = f.precision().unwrap_or(2);
+        let most = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let labels: Vec<(String, String)> = (0..self.counts.len())
+            .map(|bucket| {
+                let (start, end) = self.bounds(bucket);
+                (
+                    format!("{:.*}", precision, start),
+                    format!("{:.*}", precision, end),
+                )
+            })
+            .collect();
+        let start_width = labels.iter().map(|(s, _)| s.len()).max().unwrap_or(0);
+        let end_width = labels.iter().map(|(_, e)| e.len()).max().unwrap_or(0);
+
+        for (bucket, (&count, (start, end))) in self.counts.iter().zip(&labels).enumerate() {
+            let close = if bucket + 1 == self.counts.len() {
+                ']'
+            } else {
+                ')'
+            };
+            // round up, so that any non-empty bucket shows at least one mark
+            let length = (count as usize * self.bar_width).div_ceil(most as usize);
+            let bar: String = std::iter::repeat_n('#', length).collect();
+            writeln!(
+                f,
+                "[{:>sw$}, {:>ew$}{} {:<bw$} {}",
+                start,
+                end,
+                close,
+                bar,
+                count,
+                sw = start_width,
+                ew = end_width,
+                bw = self.bar_width
+            )?;
+        }
+        if self.below > 0 || self.above > 0 {
+            writeln!(f, "{} below, {} above", self.below, self.above)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Option<f64>, b: f64) -> bool {
+        a.is_some_and(|a| (a - b).abs() <= 1e-8 * b.abs().max(1.0))
+    }
+
+    #[test]
+    fn summaries() {
+        let values = [2, 4, 4, 4, 5, 5, 7, 9];
+        assert_eq!(mean(values), Some(5.0));
+        assert_eq!(median(values), Some(4.5));
+        assert_eq!(mode(values), Some(4));
+        assert_eq!(variance(values), Some(4.0));
+        assert_eq!(std_dev(values), Some(2.0));
+        assert!(close(sample_variance(values), 32.0 / 7.0));
+        assert_eq!(median([3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(mode([1.5, 2.5, 2.5, 1.5]), Some(1.5));
+    }
+
+    #[test]
+    fn slices_vecs_and_iterators() {
+        let values = vec![2u8, 4, 4, 4, 5, 5, 7, 9];
+        assert_eq!(median(values[..4].iter().copied()), Some(4.0));
+        assert_eq!(mode(values.iter().copied().rev()), Some(4));
+        assert_eq!(percentile((1..=5).map(|i| i * 10), 25.0), Ok(20.0));
+        assert_eq!(mean(values.iter().map(|&x| f64::from(x) * 2.0)), Some(10.0));
+        assert_eq!(std_dev(values), Some(2.0));
+    }
+
+    #[test]
+    fn nans_are_skipped_or_rejected() {
+        // either NaN sign would have sorted to one end under total_cmp
+        for nan in [f64::NAN, -f64::NAN] {
+            assert_eq!(mode([nan, nan, 1.0]), Some(1.0));
+            assert_eq!(mode([3.0, nan, 2.0, 3.0, nan]), Some(3.0));
+            assert_eq!(
+                percentile([nan, 1.0, 2.0], 0.0),
+                Err(StatsError::NotANumber)
+            );
+            assert_eq!(median([1.0, 2.0, nan]), None);
+        }
+        assert_eq!(mode([f64::NAN]), None);
+    }
+
+    #[test]
+    fn empty_input_is_not_a_panic() {
+        let empty: [i32; 0] = [];
+        assert_eq!(mean(empty), None);
+        assert_eq!(median(empty), None);
+        assert_eq!(mode(empty), None);
+        assert_eq!(variance(empty), None);
+        assert_eq!(sample_variance([1]), None);
+        assert_eq!(min_with_index(empty), None);
+        assert_eq!(percentile(empty, 10.0), Err(StatsError::Empty));
+        assert_eq!(Histogram::of(empty, 4), Err(StatsError::Empty));
+    }
+
+    #[test]
+    fn percentiles_and_extremes() {
+        let values = [15, 20, 35, 40, 50];
+        assert_eq!(percentile(values, 0.0), Ok(15.0));
+        assert_eq!(percentile(values, 100.0), Ok(50.0));
+        assert_eq!(percentile(values, 40.0), Ok(29.0));
+        assert_eq!(
+            percentile(values, 101.0),
+            Err(StatsError::InvalidPercentile(101.0))
+        );
+        assert!(percentile(values, f64::NAN).is_err());
+
+        assert_eq!(min_with_index([3, 1, 4, 1, 5]), Some((1, 1)));
+        assert_eq!(max_with_index([3, 1, 4, 1, 5]), Some((4, 5)));
+        assert_eq!(max_with_index([f64::NAN, 2.0, 7.0]), Some((2, 7.0)));
+    }
+
+    #[test]
+    fn streaming_matches_slices() {
+        let values: Vec<f64> = (0..1000)
+            .map(|i| (i as f64 * 0.37).sin() * 10.0 + 1e9)
+            .collect();
+        let all: Welford = values.iter().copied().collect();
+        assert!(close(all.mean(), mean(values.iter().copied()).unwrap()));
+        assert!(close(
+            all.variance(),
+            variance(values.iter().copied()).unwrap()
+        ));
+
+        // summing squares would lose every digit at this offset
+        let naive =
+            values.iter().map(|x| x * x).sum::<f64>() / 1000.0 - all.mean().unwrap().powi(2);
+        assert!((naive - all.variance().unwrap()).abs() > 1.0);
+
+        let (left, right) = values.split_at(300);
+        let left: Welford = left.iter().copied().collect();
+        let right: Welford = right.iter().copied().collect();
+        let merged = left.merge(&right);
+        assert_eq!(merged.count(), 1000);
+        assert!(close(merged.mean(), all.mean().unwrap()));
+        assert!(close(
+            merged.sample_variance(),
+            all.sample_variance().unwrap()
+        ));
+        assert_eq!(merged.min(), all.min());
+    }
+
+    #[test]
+    fn histograms() {
+        let mut histogram = Histogram::new(0.0, 10.0, 4).unwrap().bar_width(8);
+        histogram.extend([0.0, 1.0, 2.5, 3.0, 4.0, 9.9, 10.0, -1.0, 11.0]);
+        assert_eq!(histogram.counts(), &[2, 3, 0, 2]);
+        assert_eq!(histogram.outliers(), (1, 1));
+        assert_eq!(
+            format!("{:.1}", histogram),
+            "[0.0,  2.5) ######   2\n\
+             [2.5,  5.0) ######## 3\n\
+             [5.0,  7.5)          0\n\
+             [7.5, 10.0] ######   2\n\
+             1 below, 1 above\n"
+        );
+        assert_eq!(
+            Histogram::new(1.0, 1.0, 3),
+            Err(StatsError::InvalidRange { min: 1.0, max: 1.0 })
+        );
+        assert_eq!(Histogram::new(0.0, 1.0, 0), Err(StatsError::NoBuckets));
+        assert_eq!(Histogram::of([5, 5, 5], 2).unwrap().counts(), &[3, 0]);
+        // adding one to 1e17 is lost to rounding
+        assert_eq!(
+            Histogram::of([1e17, 1e17], 4).unwrap().counts(),
+            &[2, 0, 0, 0]
+        );
+        assert_eq!(Histogram::of([f64::MAX; 3], 2).unwrap().counts(), &[0, 3]);
+        let values = [1, 9, 5];
+        assert_eq!(
+            Histogram::of(values.iter().copied(), 2).unwrap().counts(),
+            &[1, 2]
+        );
+        assert_eq!(min_with_index(values.iter().copied().skip(1)), Some((1, 5)));
+    }
+}
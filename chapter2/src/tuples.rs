@@ -0,0 +1,301 @@
+/* Tuple utilities */
+// std only implements `Debug`, `PartialEq` and friends for tuples of up to
+// 12 elements; these traits are generated by a macro for every arity up to
+// 16, so wider heterogeneous records need no hand-written struct
+
+// `Show(&t)` prints like `{:?}` at any arity, `reverse`, `zip` and
+// `map_each` rebuild tuples, `map_with` applies a `Poly` function to
+// elements of different types, and `TupleArray` converts tuples whose
+// elements share a type to and from arrays
+
+use std::fmt;
+
+pub trait Reverse {
+    type Output;
+    /// `(a, b, c)` becomes `(c, b, a)`.
+    fn reverse(self) -> Self::Output;
+}
+
+pub trait Zip<Other> {
+    type Output;
+    /// `(a, b).zip((x, y))` is `((a, x), (b, y))`; both tuples have the same arity.
+    fn zip(self, other: Other) -> Self::Output;
+}
+
+/// A function generic over its argument type, which a closure cannot be.
+pub trait Poly<T> {
+    type Output;
+    fn call(&mut self, x: T) -> Self::Output;
+}
+
+pub trait MapWith<F> {
+    type Output;
+    /// Applies `f` to each element in order, whatever their types.
+    fn map_with(self, f: &mut F) -> Self::Output;
+}
+
+/// Tuples whose elements all have type `T`.
+pub trait TupleArray<T, const N: usize>: Sized {
+    type Map<U>: TupleArray<U, N>;
+
+    fn into_array(self) -> [T; N];
+    fn from_array(array: [T; N]) -> Self;
+
+    fn map_each<U>(self, f: impl FnMut(T) -> U) -> Self::Map<U> {
+        TupleArray::from_array(self.into_array().map(f))
+    }
+}
+
+/// Formats each element of a tuple into a `DebugTuple`.
+pub trait DebugFields {
+    const ARITY: usize;
+    fn debug_fields(&self, tuple: &mut fmt::DebugTuple);
+}
+
+/// `Debug` and `Display` for tuples of any supported arity, in the `{:?}`
+/// layout, e.g. `println!("{}", Show(&sixteen))`; `{:#?}` works too.
+pub struct Show<'a, T: ?Sized>(pub &'a T);
+
+impl<T: DebugFields + ?Sized> fmt::Debug for Show<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // an unnamed `DebugTuple` is exactly how std prints tuples, including
+        // `(1,)`, except that with no fields it prints nothing at all
+        if T::ARITY == 0 {
+            return f.write_str("()");
+        }
+        let mut tuple = f.debug_tuple("");
+        self.0.debug_fields(&mut tuple);
+        tuple.finish()
+    }
+}
+
+impl<T: DebugFields + ?Sized> fmt::Display for Show<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+// `(T2, T1, T0)` from `T0 T1 T2`
+macro_rules! reversed_type {
+    ([$($done:ident)*]) => { ($($done,)*) };
+    ([$($done:ident)*] $head:ident $($rest:ident)*) => {
+        reversed_type!([$head $($done)*] $($rest)*)
+    };
+}
+
+// `(t.2, t.1, t.0)` from `0 1 2`
+macro_rules! reversed_fields {
+    ($t:ident [$($done:tt)*]) => { ($($t.$done,)*) };
+    ($t:ident [$($done:tt)*] $head:tt $($rest:tt)*) => {
+        reversed_fields!($t [$head $($done)*] $($rest)*)
+    };
+}
+
+// the type `T`, once per element
+macro_rules! each {
+    ($_element:ident $t:ty) => {
+        $t
+    };
+}
+
+// implements every trait for one arity; each element is
+// `[type, second type for zip, variable name, index]`
+macro_rules! tuple_impl {
+    ($([$T:ident $U:ident $v:ident $idx:tt])+) => {
+        impl<$($T),+> Reverse for ($($T,)+) {
+            type Output = reversed_type!([] $($T)+);
+
+            fn reverse(self) -> Self::Output {
+                let tuple = self;
+                reversed_fields!(tuple [] $($idx)+)
+            }
+        }
+
+        impl<$($T,)+ $($U),+> Zip<($($U,)+)> for ($($T,)+) {
+            type Output = ($(($T, $U),)+);
+
+            fn zip(self, other: ($($U,)+)) -> Self::Output {
+                ($((self.$idx, other.$idx),)+)
+            }
+        }
+
+        impl<F, $($T),+> MapWith<F> for ($($T,)+)
+        where
+            $(F: Poly<$T>,)+
+        {
+            type Output = ($(<F as Poly<$T>>::Output,)+);
+
+            fn map_with(self, f: &mut F) -> Self::Output {
+                ($(f.call(self.$idx),)+)
+            }
+        }
+
+        impl<T> TupleArray<T, { [$($idx),+].len() }> for ($(each!($T T),)+) {
+            type Map<U> = ($(each!($T U),)+);
+
+            fn into_array(self) -> [T; [$($idx),+].len()] {
+                [$(self.$idx),+]
+            }
+
+            fn from_array(array: [T; [$($idx),+].len()]) -> Self {
+                let [$($v),+] = array;
+                ($($v,)+)
+            }
+        }
+
+        impl<$($T: fmt::Debug),+> DebugFields for ($($T,)+) {
+            const ARITY: usize = [$($idx),+].len();
+
+            fn debug_fields(&self, tuple: &mut fmt::DebugTuple) {
+                $(tuple.field(&self.$idx);)+
+            }
+        }
+    };
+}
+
+// implements the traits for every prefix of the list, so arities 1 to 16
+macro_rules! tuple_impls {
+    ([$($done:tt)*]) => {};
+    ([$($done:tt)*] $next:tt $($rest:tt)*) => {
+        tuple_impl!($($done)* $next);
+        tuple_impls!([$($done)* $next] $($rest)*);
+    };
+}
+
+tuple_impls!([]
+    [T0 U0 v0 0] [T1 U1 v1 1] [T2 U2 v2 2] [T3 U3 v3 3]
+    [T4 U4 v4 4] [T5 U5 v5 5] [T6 U6 v6 6] [T7 U7 v7 7]
+    [T8 U8 v8 8] [T9 U9 v9 9] [T10 U10 v10 10] [T11 U11 v11 11]
+    [T12 U12 v12 12] [T13 U13 v13 13] [T14 U14 v14 14] [T15 U15 v15 15]
+);
+
+impl DebugFields for () {
+    const ARITY: usize = 0;
+
+    fn debug_fields(&self, _tuple: &mut fmt::DebugTuple) {}
+}
+
+/// `Poly` function which formats any `Debug` value, e.g. to print a wide
+/// record as columns.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToDebugString;
+
+impl<T: fmt::Debug> Poly<T> for ToDebugString {
+    type Output = String;
+
+    fn call(&mut self, x: T) -> String {
+        format!("{:?}", x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Sixteen = (
+        u8,
+        i16,
+        char,
+        &'static str,
+        bool,
+        f32,
+        (),
+        Option<i32>,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        u8,
+        &'static str,
+    );
+
+    type Ints = (
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+    );
+
+    fn sixteen() -> Sixteen {
+        (
+            1,
+            -2,
+            'c',
+            "d",
+            true,
+            6.5,
+            (),
+            Some(8),
+            9,
+            10,
+            11,
+            12,
+            13,
+            14,
+            15,
+            "last",
+        )
+    }
+
+    #[test]
+    fn show_matches_std_debug() {
+        let twelve = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, "twelve");
+        assert_eq!(format!("{}", Show(&twelve)), format!("{:?}", twelve));
+        assert_eq!(format!("{:#?}", Show(&twelve)), format!("{:#?}", twelve));
+        assert_eq!(format!("{:?}", Show(&(1,))), "(1,)");
+        assert_eq!(format!("{:?}", Show(&())), "()");
+        assert_eq!(
+            Show(&sixteen()).to_string(),
+            "(1, -2, 'c', \"d\", true, 6.5, (), Some(8), 9, 10, 11, 12, 13, 14, 15, \"last\")"
+        );
+        assert_eq!(<Sixteen as DebugFields>::ARITY, 16);
+    }
+
+    #[test]
+    fn reverse_zip_and_map() {
+        assert_eq!((1, "two", 3.0).reverse(), (3.0, "two", 1));
+        // std has no `PartialEq` past 12 elements, so compare the output
+        assert_eq!(
+            Show(&sixteen().reverse().reverse()).to_string(),
+            Show(&sixteen()).to_string()
+        );
+        assert_eq!(sixteen().reverse().0, "last");
+        assert_eq!((1, 'a').zip(("x", true)), ((1, "x"), ('a', true)));
+        assert_eq!((1, 2, 3).map_each(|x| x * 10), (10, 20, 30));
+        assert_eq!(
+            (1, 'b', "c").map_with(&mut ToDebugString),
+            (
+                String::from("1"),
+                String::from("'b'"),
+                String::from("\"c\"")
+            )
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        let tuple = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+        let array = tuple.into_array();
+        assert_eq!(array.iter().sum::<i32>(), 136);
+        let doubled = tuple.map_each(|x| x * 2);
+        assert_eq!(doubled.into_array(), array.map(|x| x * 2));
+        let rebuilt: Ints = TupleArray::from_array(array);
+        assert_eq!(rebuilt.15, 16);
+        let (a, b): (char, char) = TupleArray::from_array(['a', 'b']);
+        assert_eq!((a, b), ('a', 'b'));
+    }
+}
@@ -0,0 +1,11 @@
+/* RPN calculator REPL */
+// reads lines of tokens from stdin and prints the stack after each one,
+// e.g. `echo "2 3 4 * +" | cargo run --bin rpn`
+
+use std::io;
+
+use chapter3::rpn;
+
+fn main() -> io::Result<()> {
+    rpn::repl(io::stdin().lock(), &mut io::stdout().lock())
+}
@@ -0,0 +1,293 @@
+/* Event bus */
+// handlers subscribe to one kind of `WebEvent`, or to any event matching a
+// predicate; publishing runs the matching handlers from the highest
+// priority down, in subscription order within a priority, until one of
+// them returns `Flow::Stop`
+
+// every published event is logged with the time since the bus was made.
+// the clock can be replaced, and `replay` feeds a log into another bus with
+// the original timestamps, so a session can be re-run exactly
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::WebEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    PageLoad,
+    PageUnload,
+    KeyPress,
+    Paste,
+    Click,
+}
+
+impl WebEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            WebEvent::PageLoad => EventKind::PageLoad,
+            WebEvent::PageUnload => EventKind::PageUnload,
+            WebEvent::KeyPress(_) => EventKind::KeyPress,
+            WebEvent::Paste(_) => EventKind::Paste,
+            WebEvent::Click { .. } => EventKind::Click,
+        }
+    }
+}
+
+/// What a handler wants to happen next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    /// Handlers after this one do not see the event.
+    Stop,
+}
+
+pub enum Filter {
+    All,
+    Kind(EventKind),
+    Matching(Box<dyn Fn(&WebEvent) -> bool>),
+}
+
+impl Filter {
+    pub fn matching(predicate: impl Fn(&WebEvent) -> bool + 'static) -> Filter {
+        Filter::Matching(Box::new(predicate))
+    }
+
+    fn accepts(&self, event: &WebEvent) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Kind(kind) => event.kind() == *kind,
+            Filter::Matching(predicate) => predicate(event),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubscriptionId(u64);
+
+impl fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+struct Subscription {
+    id: SubscriptionId,
+    priority: i32,
+    filter: Filter,
+    handler: Box<dyn FnMut(&WebEvent) -> Flow>,
+}
+
+/// A published event and how long after the bus was made it arrived.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Logged {
+    pub at: Duration,
+    pub event: WebEvent,
+}
+
+/// What publishing one event did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dispatch {
+    /// The handlers that ran, in order.
+    pub handled_by: Vec<SubscriptionId>,
+    pub stopped: bool,
+}
+
+pub struct Bus {
+    subscriptions: Vec<Subscription>,
+    next_id: u64,
+    clock: Box<dyn FnMut() -> Duration>,
+    log: Vec<Logged>,
+}
+
+impl Bus {
+    pub fn new() -> Bus {
+        let start = Instant::now();
+        Bus::with_clock(move || start.elapsed())
+    }
+
+    /// A bus that timestamps events with `clock`, e.g. a fake one in tests.
+    pub fn with_clock(clock: impl FnMut() -> Duration + 'static) -> Bus {
+        Bus {
+            subscriptions: Vec::new(),
+            next_id: 0,
+            clock: Box::new(clock),
+            log: Vec::new(),
+        }
+    }
+
+    pub fn subscribe(
+        &mut self,
+        filter: Filter,
+        priority: i32,
+        handler: impl FnMut(&WebEvent) -> Flow + 'static,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        // after every subscription of the same or a higher priority
+        let index = self
+            .subscriptions
+            .partition_point(|s| s.priority >= priority);
+        self.subscriptions.insert(
+            index,
+            Subscription {
+                id,
+                priority,
+                filter,
+                handler: Box::new(handler),
+            },
+        );
+        id
+    }
+
+    /// `false` when `id` was not subscribed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.subscriptions.len();
+        self.subscriptions.retain(|s| s.id != id);
+        self.subscriptions.len() != before
+    }
+
+    pub fn publish(&mut self, event: WebEvent) -> Dispatch {
+        let at = (self.clock)();
+        self.publish_at(at, event)
+    }
+
+    fn publish_at(&mut self, at: Duration, event: WebEvent) -> Dispatch {
+        let mut dispatch = Dispatch {
+            handled_by: Vec::new(),
+            stopped: false,
+        };
+        for subscription in &mut self.subscriptions {
+            if !subscription.filter.accepts(&event) {
+                continue;
+            }
+            dispatch.handled_by.push(subscription.id);
+            if (subscription.handler)(&event) == Flow::Stop {
+                dispatch.stopped = true;
+                break;
+            }
+        }
+        self.log.push(Logged { at, event });
+        dispatch
+    }
+
+    pub fn log(&self) -> &[Logged] {
+        &self.log
+    }
+
+    /// Publishes every logged event in order, keeping its timestamp rather
+    /// than asking the clock.
+    pub fn replay(&mut self, log: &[Logged]) -> Vec<Dispatch> {
+        log.iter()
+            .map(|logged| self.publish_at(logged.at, logged.event.clone()))
+            .collect()
+    }
+}
+
+impl Default for Bus {
+    fn default() -> Bus {
+        Bus::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    // a clock that advances 10ms every time it is read
+    fn ticking() -> impl FnMut() -> Duration {
+        let ticks = Cell::new(0);
+        move || {
+            ticks.set(ticks.get() + 1);
+            Duration::from_millis(10 * ticks.get())
+        }
+    }
+
+    fn recorder(
+        seen: &Rc<RefCell<Vec<String>>>,
+        name: &'static str,
+        flow: Flow,
+    ) -> impl FnMut(&WebEvent) -> Flow {
+        let seen = Rc::clone(seen);
+        move |event| {
+            seen.borrow_mut().push(format!("{} {:?}", name, event));
+            flow
+        }
+    }
+
+    // the same subscriptions, so that two buses can be compared
+    fn bus(seen: &Rc<RefCell<Vec<String>>>) -> Bus {
+        let mut bus = Bus::with_clock(ticking());
+        bus.subscribe(
+            Filter::Kind(EventKind::KeyPress),
+            0,
+            recorder(seen, "keys", Flow::Continue),
+        );
+        bus.subscribe(
+            Filter::matching(|e| matches!(e, WebEvent::KeyPress('q'))),
+            10,
+            recorder(seen, "quit", Flow::Stop),
+        );
+        bus.subscribe(Filter::All, 0, recorder(seen, "all", Flow::Continue));
+        bus
+    }
+
+    #[test]
+    fn priority_order_and_stopping() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut bus = bus(&seen);
+
+        let dispatch = bus.publish(WebEvent::KeyPress('a'));
+        assert_eq!(dispatch.handled_by, [SubscriptionId(0), SubscriptionId(2)]);
+        assert!(!dispatch.stopped);
+
+        let dispatch = bus.publish(WebEvent::KeyPress('q'));
+        assert_eq!(dispatch.handled_by, [SubscriptionId(1)]);
+        assert!(dispatch.stopped);
+
+        bus.publish(WebEvent::PageLoad);
+        assert_eq!(
+            *seen.borrow(),
+            [
+                "keys KeyPress('a')",
+                "all KeyPress('a')",
+                "quit KeyPress('q')",
+                "all PageLoad"
+            ]
+        );
+        assert_eq!(bus.log()[2].at, Duration::from_millis(30));
+    }
+
+    #[test]
+    fn unsubscribing() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut bus = bus(&seen);
+        assert!(bus.unsubscribe(SubscriptionId(2)));
+        assert!(!bus.unsubscribe(SubscriptionId(2)));
+        assert!(bus.publish(WebEvent::PageUnload).handled_by.is_empty());
+        // the log still has it
+        assert_eq!(bus.log().len(), 1);
+    }
+
+    #[test]
+    fn replay_reproduces_a_session() {
+        let events = [
+            WebEvent::PageLoad,
+            WebEvent::KeyPress('h'),
+            WebEvent::Paste(String::from("ello")),
+            WebEvent::KeyPress('q'),
+            WebEvent::Click { x: 3, y: 4 },
+        ];
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut original = bus(&seen);
+        let dispatches: Vec<Dispatch> = events.into_iter().map(|e| original.publish(e)).collect();
+
+        let replayed_seen = Rc::new(RefCell::new(Vec::new()));
+        let mut fresh = bus(&replayed_seen);
+        assert_eq!(fresh.replay(original.log()), dispatches);
+        assert_eq!(*replayed_seen.borrow(), *seen.borrow());
+        assert_eq!(fresh.log(), original.log());
+    }
+}
@@ -0,0 +1,635 @@
+/* WebEvent as JSON Lines */
+// one JSON object per line, with the variant in "event" and its data in
+// named fields:
+//   {"event":"PageLoad"}
+//   {"event":"KeyPress","key":"x"}
+//   {"event":"Paste","text":"my \"text\""}
+//   {"event":"Click","x":20,"y":80}
+
+// the decoder reads any key order and whitespace, but only the fields of
+// the variant: strings and integers, so no nested values. blank lines are
+// skipped, and errors point at the line and column (both from 1, columns
+// in characters) where the input went wrong
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::iter::FusedIterator;
+
+use crate::WebEvent;
+
+pub fn encode(event: &WebEvent) -> String {
+    match event {
+        WebEvent::PageLoad => String::from(r#"{"event":"PageLoad"}"#),
+        WebEvent::PageUnload => String::from(r#"{"event":"PageUnload"}"#),
+        WebEvent::KeyPress(c) => {
+            format!(r#"{{"event":"KeyPress","key":{}}}"#, quote(&c.to_string()))
+        }
+        WebEvent::Paste(text) => format!(r#"{{"event":"Paste","text":{}}}"#, quote(text)),
+        WebEvent::Click { x, y } => format!(r#"{{"event":"Click","x":{},"y":{}}}"#, x, y),
+    }
+}
+
+/// Writes each event on its own line.
+pub fn write_all<'a>(
+    out: &mut dyn Write,
+    events: impl IntoIterator<Item = &'a WebEvent>,
+) -> io::Result<()> {
+    for event in events {
+        writeln!(out, "{}", encode(event))?;
+    }
+    Ok(())
+}
+
+/// A JSON string literal, escaping quotes, backslashes and control
+/// characters; everything else is written as it is, in UTF-8.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if u32::from(c) < 0x20 => quoted.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// Reading the input failed; the message of the `io::Error`.
+    Io(String),
+    Unexpected {
+        found: char,
+        expected: &'static str,
+    },
+    UnexpectedEnd {
+        expected: &'static str,
+    },
+    InvalidEscape,
+    /// A number with a fraction or exponent, or one too big for an i64.
+    InvalidNumber,
+    UnknownVariant(String),
+    UnknownField(String),
+    DuplicateField(String),
+    MissingField(&'static str),
+    WrongType {
+        field: String,
+        expected: &'static str,
+    },
+    /// A "key" that is not exactly one character.
+    NotOneCharacter,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ErrorKind::Io(message) => write!(f, "could not read input: {}", message),
+            ErrorKind::Unexpected { found, expected } => {
+                write!(f, "expected {}, found {:?}", expected, found)
+            }
+            ErrorKind::UnexpectedEnd { expected } => {
+                write!(f, "expected {}, found the end of the line", expected)
+            }
+            ErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            ErrorKind::InvalidNumber => write!(f, "expected an integer that fits 64 bits"),
+            ErrorKind::UnknownVariant(name) => write!(
+                f,
+                "unknown event {:?}, expected one of PageLoad, PageUnload, KeyPress, Paste or Click",
+                name
+            ),
+            ErrorKind::UnknownField(name) => write!(f, "unknown field {:?}", name),
+            ErrorKind::DuplicateField(name) => write!(f, "duplicate field {:?}", name),
+            ErrorKind::MissingField(name) => write!(f, "missing field {:?}", name),
+            ErrorKind::WrongType { field, expected } => {
+                write!(f, "field {:?} should be {}", field, expected)
+            }
+            ErrorKind::NotOneCharacter => write!(f, "\"key\" should be exactly one character"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes one line, which is line `line` of its input.
+pub fn decode(text: &str, line: usize) -> Result<WebEvent, DecodeError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+        line,
+    };
+    let start = parser.skip_whitespace_to_column();
+    let fields = parser.object()?;
+    parser.skip_whitespace();
+    if let Some(&found) = parser.chars.get(parser.pos) {
+        return Err(parser.error(ErrorKind::Unexpected {
+            found,
+            expected: "the end of the line",
+        }));
+    }
+    event_from(fields, line, start)
+}
+
+/// Reads events from `input` one line at a time, as they arrive. Stops
+/// after the end of the input or the first error reading it.
+pub struct Decoder<R> {
+    input: R,
+    line: usize,
+    buffer: String,
+    done: bool,
+}
+
+impl<R: BufRead> Decoder<R> {
+    pub fn new(input: R) -> Decoder<R> {
+        Decoder {
+            input,
+            line: 0,
+            buffer: String::new(),
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Decoder<R> {
+    type Item = Result<WebEvent, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.buffer.clear();
+            self.line += 1;
+            match self.input.read_line(&mut self.buffer) {
+                Ok(0) => self.done = true,
+                Ok(_) if self.buffer.trim().is_empty() => continue,
+                Ok(_) => {
+                    return Some(decode(
+                        self.buffer.trim_end_matches(['\n', '\r']),
+                        self.line,
+                    ))
+                }
+                Err(e) => {
+                    // a reader that failed once may fail forever
+                    self.done = true;
+                    return Some(Err(DecodeError {
+                        line: self.line,
+                        column: 1,
+                        kind: ErrorKind::Io(e.to_string()),
+                    }));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: BufRead> FusedIterator for Decoder<R> {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Int(i64),
+}
+
+// columns are kept for the error messages
+struct Field {
+    key: String,
+    key_column: usize,
+    value: Value,
+    column: usize,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn error(&self, kind: ErrorKind) -> DecodeError {
+        DecodeError {
+            line: self.line,
+            column: self.pos + 1,
+            kind,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_whitespace_to_column(&mut self) -> usize {
+        self.skip_whitespace();
+        self.pos + 1
+    }
+
+    fn next(&mut self, expected: &'static str) -> Result<char, DecodeError> {
+        let c = *self
+            .chars
+            .get(self.pos)
+            .ok_or_else(|| self.error(ErrorKind::UnexpectedEnd { expected }))?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn expect(&mut self, wanted: char, expected: &'static str) -> Result<(), DecodeError> {
+        self.skip_whitespace();
+        match self.next(expected)? {
+            c if c == wanted => Ok(()),
+            found => {
+                self.pos -= 1;
+                Err(self.error(ErrorKind::Unexpected { found, expected }))
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Vec<Field>, DecodeError> {
+        self.expect('{', "'{'")?;
+        let mut fields: Vec<Field> = Vec::new();
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&'}') {
+            self.pos += 1;
+            return Ok(fields);
+        }
+        loop {
+            self.skip_whitespace();
+            let key_column = self.pos + 1;
+            if self.chars.get(self.pos) != Some(&'"') {
+                let found = self.next("a field name")?;
+                self.pos -= 1;
+                return Err(self.error(ErrorKind::Unexpected {
+                    found,
+                    expected: "a field name",
+                }));
+            }
+            let key = self.string()?;
+            if fields.iter().any(|field| field.key == key) {
+                return Err(DecodeError {
+                    line: self.line,
+                    column: key_column,
+                    kind: ErrorKind::DuplicateField(key),
+                });
+            }
+            self.expect(':', "':'")?;
+            let column = self.skip_whitespace_to_column();
+            let value = self.value()?;
+            fields.push(Field {
+                key,
+                key_column,
+                value,
+                column,
+            });
+            self.skip_whitespace();
+            match self.next("',' or '}'")? {
+                ',' => continue,
+                '}' => return Ok(fields),
+                found => {
+                    self.pos -= 1;
+                    return Err(self.error(ErrorKind::Unexpected {
+                        found,
+                        expected: "',' or '}'",
+                    }));
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, DecodeError> {
+        match self.chars.get(self.pos) {
+            Some('"') => self.string().map(Value::Str),
+            Some(c) if *c == '-' || c.is_ascii_digit() => self.integer().map(Value::Int),
+            Some(&found) => Err(self.error(ErrorKind::Unexpected {
+                found,
+                expected: "a string or an integer",
+            })),
+            None => Err(self.error(ErrorKind::UnexpectedEnd {
+                expected: "a string or an integer",
+            })),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, DecodeError> {
+        let start = self.pos;
+        if self.chars[self.pos] == '-' {
+            self.pos += 1;
+        }
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        let fraction = matches!(self.chars.get(self.pos), Some('.' | 'e' | 'E'));
+        // JSON allows no leading zeros, like "01"
+        let leading_zero =
+            text.trim_start_matches('-').len() > 1 && text.trim_start_matches('-').starts_with('0');
+        match text.parse() {
+            Ok(n) if !fraction && !leading_zero => Ok(n),
+            _ => {
+                self.pos = start;
+                Err(self.error(ErrorKind::InvalidNumber))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        self.expect('"', "'\"'")?;
+        let mut text = String::new();
+        loop {
+            match self.next("a closing '\"'")? {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escape = self.pos - 1;
+                    let c = match self.next("an escape sequence")? {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => self.unicode_escape(escape)?,
+                        _ => return Err(self.error_at(escape, ErrorKind::InvalidEscape)),
+                    };
+                    text.push(c);
+                }
+                c if u32::from(c) < 0x20 => {
+                    self.pos -= 1;
+                    return Err(self.error(ErrorKind::Unexpected {
+                        found: c,
+                        expected: "an escaped control character",
+                    }));
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn error_at(&self, pos: usize, kind: ErrorKind) -> DecodeError {
+        DecodeError {
+            line: self.line,
+            column: pos + 1,
+            kind,
+        }
+    }
+
+    fn hex4(&mut self, escape: usize) -> Result<u32, DecodeError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .next("four hex digits")?
+                .to_digit(16)
+                .ok_or_else(|| self.error_at(escape, ErrorKind::InvalidEscape))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    // "\uXXXX", where characters outside the BMP are a surrogate pair
+    fn unicode_escape(&mut self, escape: usize) -> Result<char, DecodeError> {
+        let high = self.hex4(escape)?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            if self.next("a low surrogate")? != '\\' || self.next("a low surrogate")? != 'u' {
+                return Err(self.error_at(escape, ErrorKind::InvalidEscape));
+            }
+            let low = self.hex4(escape)?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error_at(escape, ErrorKind::InvalidEscape));
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error_at(escape, ErrorKind::InvalidEscape))
+    }
+}
+
+fn event_from(fields: Vec<Field>, line: usize, object: usize) -> Result<WebEvent, DecodeError> {
+    let at = |column: usize, kind: ErrorKind| DecodeError { line, column, kind };
+    let mut fields = fields;
+    let take =
+        |fields: &mut Vec<Field>, name: &'static str| -> Result<(Value, usize), DecodeError> {
+            let index = fields
+                .iter()
+                .position(|field| field.key == name)
+                .ok_or_else(|| at(object, ErrorKind::MissingField(name)))?;
+            let Field { value, column, .. } = fields.remove(index);
+            Ok((value, column))
+        };
+    let string = |(value, column): (Value, usize), field: &str| match value {
+        Value::Str(text) => Ok(text),
+        Value::Int(_) => Err(at(
+            column,
+            ErrorKind::WrongType {
+                field: field.to_string(),
+                expected: "a string",
+            },
+        )),
+    };
+    let integer = |(value, column): (Value, usize), field: &str| match value {
+        Value::Int(n) => Ok(n),
+        Value::Str(_) => Err(at(
+            column,
+            ErrorKind::WrongType {
+                field: field.to_string(),
+                expected: "an integer",
+            },
+        )),
+    };
+
+    let (variant, variant_column) = take(&mut fields, "event")?;
+    let variant = string((variant, variant_column), "event")?;
+    let event = match variant.as_str() {
+        "PageLoad" => WebEvent::PageLoad,
+        "PageUnload" => WebEvent::PageUnload,
+        "KeyPress" => {
+            let (value, column) = take(&mut fields, "key")?;
+            let key = string((value, column), "key")?;
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => WebEvent::KeyPress(c),
+                _ => return Err(at(column, ErrorKind::NotOneCharacter)),
+            }
+        }
+        "Paste" => WebEvent::Paste(string(take(&mut fields, "text")?, "text")?),
+        "Click" => WebEvent::Click {
+            x: integer(take(&mut fields, "x")?, "x")?,
+            y: integer(take(&mut fields, "y")?, "y")?,
+        },
+        _ => return Err(at(variant_column, ErrorKind::UnknownVariant(variant))),
+    };
+    // anything left over does not belong to the variant
+    match fields.into_iter().min_by_key(|field| field.key_column) {
+        Some(field) => Err(at(field.key_column, ErrorKind::UnknownField(field.key))),
+        None => Ok(event),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events() -> Vec<WebEvent> {
+        vec![
+            WebEvent::PageLoad,
+            WebEvent::KeyPress('X'),
+            WebEvent::KeyPress('"'),
+            WebEvent::KeyPress('é'),
+            WebEvent::Paste(String::from("line one\nsaid \"hi\" \\ \t\u{1} 🦀")),
+            WebEvent::Click {
+                x: -20,
+                y: i64::MAX,
+            },
+            WebEvent::PageUnload,
+        ]
+    }
+
+    fn error(line: &str) -> (usize, ErrorKind) {
+        let e = decode(line, 1).unwrap_err();
+        (e.column, e.kind)
+    }
+
+    #[test]
+    fn every_variant_round_trips() {
+        let mut out = Vec::new();
+        write_all(&mut out, &events()).unwrap();
+        let decoded: Result<Vec<WebEvent>, DecodeError> = Decoder::new(&out[..]).collect();
+        assert_eq!(decoded.unwrap(), events());
+        assert_eq!(
+            encode(&WebEvent::Paste(String::from("a\"b\u{1}"))),
+            r#"{"event":"Paste","text":"a\"b\u0001"}"#
+        );
+    }
+
+    #[test]
+    fn any_layout_and_escapes() {
+        let line = r#"  { "y" : 3 , "x":-4,"event":"Click" }  "#;
+        assert_eq!(decode(line, 1), Ok(WebEvent::Click { x: -4, y: 3 }));
+        let line = r#"{"event":"Paste","text":"é🦀\/"}"#;
+        assert_eq!(decode(line, 1), Ok(WebEvent::Paste(String::from("é🦀/"))));
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        assert_eq!(
+            error(r#"{"event":"Scroll"}"#),
+            (10, ErrorKind::UnknownVariant(String::from("Scroll")))
+        );
+        assert_eq!(
+            error(r#"{"event":"Click","x":1}"#),
+            (1, ErrorKind::MissingField("y"))
+        );
+        assert_eq!(
+            error(r#"{"event":"Click","x":1,"y":2,"z":3}"#),
+            (30, ErrorKind::UnknownField(String::from("z")))
+        );
+        assert_eq!(
+            error(r#"{"event":"Click","x":"1","y":2}"#),
+            (
+                22,
+                ErrorKind::WrongType {
+                    field: String::from("x"),
+                    expected: "an integer"
+                }
+            )
+        );
+        assert_eq!(
+            error(r#"{"event":"Click","x":1.5,"y":2}"#),
+            (22, ErrorKind::InvalidNumber)
+        );
+        assert_eq!(
+            error(r#"{"event":"KeyPress","key":"ab"}"#),
+            (27, ErrorKind::NotOneCharacter)
+        );
+        assert_eq!(
+            error(r#"{"event":"Paste","text":"\q"}"#),
+            (26, ErrorKind::InvalidEscape)
+        );
+        assert_eq!(
+            error(r#"{"event":"PageLoad""#),
+            (
+                20,
+                ErrorKind::UnexpectedEnd {
+                    expected: "',' or '}'"
+                }
+            )
+        );
+        assert_eq!(
+            error(r#"{"event":"PageLoad"} x"#),
+            (
+                22,
+                ErrorKind::Unexpected {
+                    found: 'x',
+                    expected: "the end of the line"
+                }
+            )
+        );
+        assert_eq!(
+            error(r#"{"event":"PageLoad","event":"PageLoad"}"#),
+            (21, ErrorKind::DuplicateField(String::from("event")))
+        );
+    }
+
+    #[test]
+    fn the_decoder_counts_lines() {
+        let input = "{\"event\":\"PageLoad\"}\n\n{\"event\":\"Click\",\"x\":1}\n{\"event\":\"PageUnload\"}\n";
+        let results: Vec<_> = Decoder::new(input.as_bytes()).collect();
+        assert_eq!(results.len(), 3);
+        let e = results[1].clone().unwrap_err();
+        assert_eq!((e.line, e.column), (3, 1));
+        assert_eq!(e.to_string(), "line 3, column 1: missing field \"y\"");
+        assert_eq!(results[2], Ok(WebEvent::PageUnload));
+    }
+
+    // one good line, then an error on every read
+    struct Failing(bool);
+
+    impl io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk on fire"))
+        }
+    }
+
+    impl BufRead for Failing {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            match self.0 {
+                true => Ok(b"{\"event\":\"PageLoad\"}\n"),
+                false => Err(io::Error::other("disk on fire")),
+            }
+        }
+
+        fn consume(&mut self, _: usize) {
+            self.0 = false;
+        }
+    }
+
+    #[test]
+    fn the_decoder_stops_at_the_first_read_error() {
+        let mut decoder = Decoder::new(Failing(true));
+        assert_eq!(decoder.next(), Some(Ok(WebEvent::PageLoad)));
+        let e = decoder.next().unwrap().unwrap_err();
+        assert_eq!(e.line, 2);
+        assert_eq!(e.kind, ErrorKind::Io(String::from("disk on fire")));
+        assert_eq!(decoder.next(), None);
+        assert_eq!(decoder.next(), None);
+        // skipping errors no longer spins forever
+        let skipped = Decoder::new(Failing(true)).filter(Result::is_ok).count();
+        assert_eq!(skipped, 1);
+    }
+}
@@ -0,0 +1,5 @@
+/* chapter3 library */
+// code shared by the chapter3 walkthrough and the other binaries in
+// src/bin, such as the `rpn` calculator
+
+pub mod rpn;
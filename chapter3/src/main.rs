@@ -1,4 +1,11 @@
 #![allow(dead_code)]
+use std::io::{self, Write};
+
+mod bus;
+mod jsonl;
+mod textinput;
+mod widget;
+
 /* structs */
 
 // There are 3 types of structs:-
@@ -8,11 +15,11 @@
 
 use std::fmt;
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let name = String::from("Peter");
     let age = 14;
     let peter = Person { name, age };
-    println!("{}", peter);
+    writeln!(out, "{}", peter)?;
 
     let point = Point { x: 5, y: 10 };
     let another_point = Point { x: 7, ..point }; // use field of other point
@@ -34,10 +41,11 @@ fn part1() {
     // tuple struct
     let _unit = Unit;
     let rgb = RGB(255, 0, 0);
-    println!("Color: {:?}", rgb);
+    writeln!(out, "Color: {:?}", rgb)?;
     // destructuring tuple struct
     let RGB(red, blue, green) = rgb;
-    println!("red: {}, blue: {}, green: {}", red, blue, green);
+    writeln!(out, "red: {}, blue: {}, green: {}", red, blue, green)?;
+    Ok(())
 }
 
 struct Person {
@@ -55,9 +63,17 @@ impl fmt::Display for Person {
 struct Unit;
 
 // tuple struct
-#[derive(Debug)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct RGB(u8, u8, u8);
 
+// lets the geometry crate's canvas write RGB pixels to PPM images
+impl From<RGB> for [u8; 3] {
+    fn from(RGB(red, green, blue): RGB) -> [u8; 3] {
+        [red, green, blue]
+    }
+}
+
 struct Point {
     x: i32,
     y: i32,
@@ -70,21 +86,38 @@ struct Rectangle {
 
 // structs activity
 
-fn part1a() {
+fn part1a(out: &mut dyn Write) -> io::Result<()> {
     let rect = Rectangle {
         top_left: Point { x: 1, y: 2 },
         bottom_right: Point { x: 3, y: 5 },
     };
-    println!("Area = {}", rect.area());
+    if let Some(area) = rect.area() {
+        writeln!(out, "Area = {}", area)?;
+    }
     let my_square = square(Point { x: 1, y: 2 }, 5);
-    println!("Area of square: {}", my_square.area());
+    if let Some(area) = my_square.area() {
+        writeln!(out, "Area of square: {}", area)?;
+    }
+    Ok(())
 }
 
 impl Rectangle {
-    fn area(&self) -> i32 {
-        let Point { x: x1, y: y1 } = self.top_left;
-        let Point { x: x2, y: y2 } = self.bottom_right;
-        (x2 - x1) * (y2 - y1)
+    // the corners may be given in either order, so measure the normalised
+    // rectangle; `None` when the area does not fit an i32
+    fn area(&self) -> Option<i32> {
+        geometry::Rectangle::from(self).area()
+    }
+}
+
+impl From<&Point> for geometry::Point<i32> {
+    fn from(point: &Point) -> geometry::Point<i32> {
+        geometry::Point::new(point.x, point.y)
+    }
+}
+
+impl From<&Rectangle> for geometry::Rectangle<i32> {
+    fn from(rect: &Rectangle) -> geometry::Rectangle<i32> {
+        geometry::Rectangle::new((&rect.top_left).into(), (&rect.bottom_right).into())
     }
 }
 
@@ -101,21 +134,23 @@ fn square(top_left: Point, side: i32) -> Rectangle {
 
 // enums:-
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let pressed = WebEvent::KeyPress('X');
     let pasted = WebEvent::Paste("my_text".to_owned());
     let click = WebEvent::Click { x: 20, y: 80 };
     let load = WebEvent::PageLoad;
     let unload = WebEvent::PageUnload;
 
-    inspect(pressed);
-    inspect(pasted);
-    inspect(click);
-    inspect(load);
-    inspect(unload);
+    inspect(out, pressed)?;
+    inspect(out, pasted)?;
+    inspect(out, click)?;
+    inspect(out, load)?;
+    inspect(out, unload)?;
+    Ok(())
 }
 
-enum WebEvent {
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebEvent {
     PageLoad,
     PageUnload,
     KeyPress(char), // just like tuple struct
@@ -123,41 +158,34 @@ enum WebEvent {
     Click { x: i64, y: i64 }, // C like struct
 }
 
-fn inspect(event: WebEvent) {
+fn inspect(out: &mut dyn Write, event: WebEvent) -> io::Result<()> {
     match event {
-        WebEvent::PageLoad => println!("Page load"),
-        WebEvent::PageUnload => println!("Page unloaded"),
-        WebEvent::KeyPress(c) => println!("Pressed {}", c),
-        WebEvent::Paste(s) => println!("Pasted \"{}\"", s),
+        WebEvent::PageLoad => writeln!(out, "Page load")?,
+        WebEvent::PageUnload => writeln!(out, "Page unloaded")?,
+        WebEvent::KeyPress(c) => writeln!(out, "Pressed {}", c)?,
+        WebEvent::Paste(s) => writeln!(out, "Pasted \"{}\"", s)?,
         WebEvent::Click { x, y } => {
-            println!("clicked at x={}, y={}", x, y);
+            writeln!(out, "clicked at x={}, y={}", x, y)?;
         }
     };
+    Ok(())
 }
 
 // type aliases e.g. self alias in implementation blocks
 
-fn part3() {
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     let x = Operations::Add;
-    println!("{}", x.run(5, 6));
-}
-
-enum VeryVeryVerboseEnumOfThingsToDoWithNumbers {
-    Add,
-    Subtract,
-}
-
-type Operations = VeryVeryVerboseEnumOfThingsToDoWithNumbers;
-
-impl Operations {
-    fn run(&self, x: i32, y: i32) -> i32 {
-        match self {
-            Self::Add => x + y,
-            Self::Subtract => x - y,
-        }
+    match x.run(5, 6) {
+        Ok(result) => writeln!(out, "{}", result)?,
+        Err(e) => writeln!(out, "{}", e)?,
     }
+    Ok(())
 }
 
+// the enum, its alias and the rest of the operators live in the library's
+// rpn.rs, the engine behind the `rpn` calculator binary
+use chapter3::rpn::{self, Operations};
+
 // use declaration
 
 enum Status {
@@ -170,7 +198,7 @@ enum Work {
     Soldier,
 }
 
-fn part4() {
+fn part4(out: &mut dyn Write) -> io::Result<()> {
     use crate::Status::{Poor, Rich};
     use crate::Work::*;
 
@@ -178,13 +206,14 @@ fn part4() {
     let work = Civilian;
 
     match status {
-        Rich => println!("The rich have lots of money"),
-        Poor => println!("The poor have no money"),
+        Rich => writeln!(out, "The rich have lots of money")?,
+        Poor => writeln!(out, "The poor have no money")?,
     }
     match work {
-        Civilian => println!("Civilians work!"),
-        Soldier => println!("Soldiers fight!"),
+        Civilian => writeln!(out, "Civilians work!")?,
+        Soldier => writeln!(out, "Soldiers fight!")?,
     }
+    Ok(())
 }
 
 // c-like enums
@@ -201,13 +230,14 @@ enum Color {
     Blue = 0x0000ff,
 }
 
-fn part5() {
-    println!("zero is {}", Number::Zero as i32);
-    println!("one is {}", Number::One as i32);
-    println!("two is {}", Number::Two as i32);
+fn part5(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "zero is {}", Number::Zero as i32)?;
+    writeln!(out, "one is {}", Number::One as i32)?;
+    writeln!(out, "two is {}", Number::Two as i32)?;
 
-    println!("roses are #{:06x}", Color::Red as i32);
-    println!("violets are #{:06x}", Color::Blue as i32);
+    writeln!(out, "roses are #{:06x}", Color::Red as i32)?;
+    writeln!(out, "violets are #{:06x}", Color::Blue as i32)?;
+    Ok(())
 }
 
 // Linked List
@@ -241,20 +271,21 @@ impl List {
             Cons(head, tail) => {
                 format!("{}, {}", head, tail.stringify())
             }
-            Nil => format!("Nil"),
+            Nil => String::from("Nil"),
         }
     }
 }
 
-fn part6() {
+fn part6(out: &mut dyn Write) -> io::Result<()> {
     let mut list = List::new();
 
     list = list.prepend(3);
     list = list.prepend(4);
     list = list.prepend(5);
 
-    println!("Length of linked list: {}", list.len());
-    println!("{}", list.stringify());
+    writeln!(out, "Length of linked list: {}", list.len())?;
+    writeln!(out, "{}", list.stringify())?;
+    Ok(())
 }
 
 // constants
@@ -270,23 +301,684 @@ fn is_big(n: i32) -> bool {
     n > THRESHOLD
 }
 
-fn part7() {
+fn part7(out: &mut dyn Write) -> io::Result<()> {
     let n = 16;
 
-    println!("This is {}", LANGUAGE);
-    println!("THe threshold is {}", THRESHOLD);
-    println!("{} is {}", n, if is_big(n) { "big" } else { "small" });
+    writeln!(out, "This is {}", LANGUAGE)?;
+    writeln!(out, "THe threshold is {}", THRESHOLD)?;
+    writeln!(out, "{} is {}", n, if is_big(n) { "big" } else { "small" })?;
 
     // THRESHOLD = 5;
+    Ok(())
+}
+
+/* geometry */
+// the shared geometry crate's rectangles are normalised on construction, so
+// swapped corners still give a positive area, and the integer ones use
+// checked arithmetic, reporting overflow as `None`
+
+fn part8(out: &mut dyn Write) -> io::Result<()> {
+    use geometry::Point as P;
+
+    let swapped = Rectangle {
+        top_left: Point { x: 3, y: 5 },
+        bottom_right: Point { x: 1, y: 2 },
+    };
+    let rect = geometry::Rectangle::from(&swapped);
+    writeln!(out, "Swapped corners: {}, area {:?}", rect, swapped.area())?;
+    if let (Some(center), Some(perimeter)) = (rect.center(), rect.perimeter()) {
+        writeln!(out, "Center {}, perimeter {}", center, perimeter)?;
+    }
+    writeln!(out, "Contains (2, 5): {}", rect.contains(P::new(2, 5)))?;
+
+    let other = geometry::Rectangle::new(P::new(2, 0), P::new(6, 4));
+    match rect.intersection(&other) {
+        Some(overlap) => writeln!(out, "{} overlaps {} in {}", rect, other, overlap)?,
+        None => writeln!(out, "{} and {} are apart", rect, other)?,
+    }
+    writeln!(out, "Union: {}", rect.union(&other))?;
+    if let Some(bigger) = rect.inflate(1, 1) {
+        writeln!(out, "Inflated by 1: {}", bigger)?;
+    }
+    if let Some((left, right)) = other.split_at_x(3) {
+        writeln!(out, "Split at x = 3: {} | {}", left, right)?;
+    }
+
+    let huge = geometry::Rectangle::new(P::new(0, 0), P::new(100_000, 100_000));
+    writeln!(out, "Area of {}: {:?}", huge, huge.area())?;
+
+    let unit = geometry::Rectangle::new(P::new(0.5, 0.5), P::new(-0.5, -1.0));
+    if let (Some(area), Some(center)) = (unit.area(), unit.center()) {
+        writeln!(out, "f64: {}, area {}, center {}", unit, area, center)?;
+    }
+    Ok(())
+}
+
+/* quadtree */
+// searching many points linearly visits every one of them; the geometry
+// crate's `QuadTree` only visits the parts of the plane a query touches
+
+// a deterministic scatter of named points, so the output is repeatable
+fn scatter(n: usize, size: i32) -> Vec<(Point, String)> {
+    let mut state: u32 = 2024;
+    let mut next = move || {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (state >> 8) as i32 % size
+    };
+    (0..n)
+        .map(|i| {
+            (
+                Point {
+                    x: next(),
+                    y: next(),
+                },
+                format!("p{}", i),
+            )
+        })
+        .collect()
+}
+
+fn keyed(points: &[(Point, String)]) -> Vec<(geometry::Point<i32>, &str)> {
+    points
+        .iter()
+        .map(|(point, name)| (point.into(), name.as_str()))
+        .collect()
+}
+
+fn part9(out: &mut dyn Write) -> io::Result<()> {
+    let points = scatter(10_000, 1000);
+    let Some(mut tree) = geometry::QuadTree::bulk_load(keyed(&points)) else {
+        return Ok(());
+    };
+    writeln!(
+        out,
+        "{} points in {}, {} levels deep",
+        tree.len(),
+        tree.bounds(),
+        tree.depth()
+    )?;
+
+    let area = Rectangle {
+        top_left: Point { x: 500, y: 500 },
+        bottom_right: Point { x: 480, y: 520 },
+    };
+    let mut found: Vec<_> = tree.range(&(&area).into());
+    found.sort_by_key(|&(point, _)| (point.y, point.x));
+    writeln!(
+        out,
+        "{} points in {}:",
+        found.len(),
+        geometry::Rectangle::from(&area)
+    )?;
+    for (point, name) in found {
+        writeln!(out, "  {} at {}", name, point)?;
+    }
+
+    let target = geometry::Point::new(250, 750);
+    writeln!(out, "Nearest to {}:", target)?;
+    for (point, name) in tree.nearest(target, 3) {
+        writeln!(
+            out,
+            "  {} at {}, {:.2} away",
+            name,
+            point,
+            point.distance(target)
+        )?;
+    }
+    writeln!(
+        out,
+        "{} points within 25 of {}",
+        tree.within_radius(target, 25.0).len(),
+        target
+    )?;
+
+    let (first, _) = tree.nearest(target, 1)[0];
+    if let Some(name) = tree.remove(first) {
+        writeln!(out, "Removed {}, {} points left", name, tree.len())?;
+    }
+    if let Some(&(point, name)) = tree.nearest(target, 1).first() {
+        writeln!(out, "Now nearest: {} at {}", name, point)?;
+    }
+    Ok(())
+}
+
+fn part10(out: &mut dyn Write) -> io::Result<()> {
+    use std::time::Instant;
+
+    let count = if cfg!(debug_assertions) {
+        20_000
+    } else {
+        200_000
+    };
+    let points = scatter(count, 10_000);
+    let entries = keyed(&points);
+    let queries: Vec<_> = scatter(200, 10_000)
+        .iter()
+        .map(|(point, _)| geometry::Point::from(point))
+        .collect();
+    writeln!(
+        out,
+        "{} points, {} queries of each kind",
+        count,
+        queries.len()
+    )?;
+
+    let start = Instant::now();
+    let Some(tree) = geometry::QuadTree::bulk_load(entries.clone()) else {
+        return Ok(());
+    };
+    writeln!(out, "bulk load: {:.2?}", start.elapsed())?;
+
+    let mut table = support::Table::new(["query", "linear scan", "quadtree", "agrees"])
+        .align(1, support::table::Align::Right)
+        .align(2, support::table::Align::Right);
+
+    // 100 by 100 windows around each query point
+    let windows: Vec<_> = queries
+        .iter()
+        .filter_map(|q| {
+            q.translate(100, 100)
+                .map(|c| geometry::Rectangle::new(*q, c))
+        })
+        .collect();
+    let start = Instant::now();
+    let scanned: usize = windows
+        .iter()
+        .map(|w| entries.iter().filter(|(p, _)| w.contains(*p)).count())
+        .sum();
+    let scan_time = start.elapsed();
+    let start = Instant::now();
+    let indexed: usize = windows.iter().map(|w| tree.range(w).len()).sum();
+    let tree_time = start.elapsed();
+    table.row([
+        String::from("range"),
+        format!("{:.2?}", scan_time),
+        format!("{:.2?}", tree_time),
+        (scanned == indexed).to_string(),
+    ]);
+
+    // the distance to the 5th nearest point
+    let start = Instant::now();
+    let scanned: Vec<f64> = queries
+        .iter()
+        .map(|q| {
+            let mut distances: Vec<f64> = entries
+                .iter()
+                .map(|(p, _)| p.distance_squared(*q))
+                .collect();
+            distances.select_nth_unstable_by(4, f64::total_cmp);
+            distances[4]
+        })
+        .collect();
+    let scan_time = start.elapsed();
+    let start = Instant::now();
+    let indexed: Vec<f64> = queries
+        .iter()
+        .map(|q| {
+            tree.nearest(*q, 5)
+                .last()
+                .map_or(f64::NAN, |(p, _)| p.distance_squared(*q))
+        })
+        .collect();
+    let tree_time = start.elapsed();
+    table.row([
+        String::from("5 nearest"),
+        format!("{:.2?}", scan_time),
+        format!("{:.2?}", tree_time),
+        (scanned == indexed).to_string(),
+    ]);
+    write!(out, "{}", table)?;
+    Ok(())
 }
 
-fn main() {
-    part1();
-    part1a();
-    part2();
-    part3();
-    part4();
-    part5();
-    part6();
-    part7();
+/* raster canvas */
+// the shapes above drawn on the geometry crate's `Canvas` of RGB pixels,
+// printed as ASCII art and saved as PPM images, which most image viewers
+// open
+
+const BLACK: RGB = RGB(0, 0, 0);
+const RED: RGB = RGB(255, 0, 0);
+const GREEN: RGB = RGB(0, 255, 0);
+const BLUE: RGB = RGB(0, 0, 255);
+const YELLOW: RGB = RGB(255, 255, 0);
+
+fn glyph(pixel: RGB) -> char {
+    match pixel {
+        BLACK => '.',
+        RED => '#',
+        GREEN => '+',
+        BLUE => 'o',
+        YELLOW => '*',
+        _ => '?',
+    }
+}
+
+fn part11(out: &mut dyn Write) -> io::Result<()> {
+    use geometry::{Canvas, Point as P, PpmFormat};
+
+    let mut canvas = Canvas::new(40, 16, BLACK);
+    let rect = Rectangle {
+        top_left: Point { x: 12, y: 10 },
+        bottom_right: Point { x: 2, y: 2 },
+    };
+    canvas.fill_rect(&(&rect).into(), RED);
+    let outline = square(Point { x: 4, y: 4 }, 9);
+    canvas.stroke_rect(&(&outline).into(), GREEN);
+    // chapter6's circle of radius 5
+    canvas.fill_circle(P::new(22, 7), 5, BLUE);
+    canvas.stroke_circle(P::new(22, 7), 6, YELLOW);
+    canvas.fill_triangle(P::new(30, 14), P::new(38, 14), P::new(34, 2), GREEN);
+    canvas.line(P::new(0, 15), P::new(39, 12), YELLOW);
+    write!(out, "{}", canvas.to_ascii(glyph))?;
+
+    // encoded in memory only, so the part has no side effects; write the
+    // bytes to a .ppm file to view the image
+    for format in [PpmFormat::Plain, PpmFormat::Binary] {
+        let mut image = Vec::new();
+        canvas.write_ppm(&mut image, format)?;
+        let header: Vec<&[u8]> = image.split(|&b| b == b'\n').take(3).collect();
+        writeln!(
+            out,
+            "{:?} PPM: {} bytes, header {:?}",
+            format,
+            image.len(),
+            header.join(&b' ').escape_ascii().to_string()
+        )?;
+    }
+    Ok(())
+}
+
+/* SVG export */
+// the same shapes as vector graphics, with labels, a group that shares a
+// style and a rotated copy; the viewBox is worked out from what is drawn
+
+fn part12(out: &mut dyn Write) -> io::Result<()> {
+    use geometry::svg::{Group, Scene, Shape, Style, Transform};
+    use geometry::{Point as P, Polygon};
+
+    let rect = Rectangle {
+        top_left: Point { x: 12, y: 10 },
+        bottom_right: Point { x: 2, y: 2 },
+    };
+    let rect = geometry::Rectangle::<f64>::from(geometry::Rectangle::from(&rect));
+    let square = geometry::Rectangle::from(&square(Point { x: 4, y: 4 }, 9));
+
+    let mut scene = Scene::new().margin(2.0);
+    scene
+        .add(Shape::Rect(rect), Style::new().fill(RED))
+        .add(
+            Shape::Rect(square.into()),
+            Style::new().no_fill().stroke(GREEN, 1.0),
+        )
+        .add(
+            Shape::Circle {
+                center: P::new(22.0, 7.0),
+                radius: 5.0,
+            },
+            Style::new().fill(BLUE).stroke(YELLOW, 0.5),
+        );
+    if let Ok(triangle) = Polygon::new(vec![
+        P::new(30.0, 14.0),
+        P::new(38.0, 14.0),
+        P::new(34.0, 2.0),
+    ]) {
+        scene.add(Shape::Polygon(triangle), Style::new().fill(GREEN));
+    }
+
+    let mut labels = Group::new().style(Style::new().fill(BLACK));
+    for (x, text) in [
+        (2.0, "Rectangle"),
+        (17.0, "Circle of radius 5"),
+        (30.0, "Triangle"),
+    ] {
+        labels.add(
+            Shape::Text {
+                position: P::new(x, 20.0),
+                text: text.to_string(),
+                size: 2.0,
+            },
+            Style::new(),
+        );
+    }
+    scene.group(labels);
+
+    let mut turned = Group::new()
+        .transform(Transform::Translate(46.0, 4.0))
+        .transform(Transform::Rotate(30.0));
+    turned.add(
+        Shape::Rect(geometry::Rectangle::new(P::new(0.0, 0.0), P::new(8.0, 4.0))),
+        Style::new().fill(YELLOW),
+    );
+    scene.group(turned);
+
+    // printed rather than saved, so the part has no side effects
+    let svg = scene.to_svg();
+    write!(out, "{}", svg)?;
+    writeln!(out, "{} bytes of SVG", svg.len())?;
+    Ok(())
+}
+
+/* event bus */
+// instead of one hard-coded match, handlers subscribe to the events they
+// care about; see bus.rs
+
+fn part13(out: &mut dyn Write) -> io::Result<()> {
+    use bus::{Bus, EventKind, Filter, Flow};
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    // a fake clock, so that the timestamps are the same on every run
+    let now = Rc::new(Cell::new(Duration::ZERO));
+    let clock = Rc::clone(&now);
+    let transcript = Rc::new(RefCell::new(Vec::new()));
+    let subscribe = |bus: &mut Bus| {
+        let log = Rc::clone(&transcript);
+        bus.subscribe(Filter::All, 0, move |event| {
+            // the old `inspect` is now just one subscriber among others
+            let _ = inspect(&mut *log.borrow_mut(), event.clone());
+            Flow::Continue
+        });
+        let log = Rc::clone(&transcript);
+        let shortcut = bus.subscribe(
+            Filter::matching(
+                |event| matches!(event, WebEvent::KeyPress(c) if c.is_ascii_uppercase()),
+            ),
+            10,
+            move |event| {
+                let _ = writeln!(
+                    log.borrow_mut(),
+                    "  shortcut {:?} handled, not typed",
+                    event
+                );
+                Flow::Stop
+            },
+        );
+        let log = Rc::clone(&transcript);
+        bus.subscribe(Filter::Kind(EventKind::Click), 5, move |event| {
+            if let WebEvent::Click { x, y } = event {
+                let _ = writeln!(log.borrow_mut(), "  click in cell ({}, {})", x / 10, y / 10);
+            }
+            Flow::Continue
+        });
+        shortcut
+    };
+
+    let mut bus = Bus::with_clock(move || clock.get());
+    let shortcut = subscribe(&mut bus);
+    let session = [
+        WebEvent::PageLoad,
+        WebEvent::KeyPress('h'),
+        WebEvent::KeyPress('S'),
+        WebEvent::Click { x: 20, y: 80 },
+        WebEvent::Paste("my_text".to_owned()),
+        WebEvent::PageUnload,
+    ];
+    for event in session {
+        now.set(now.get() + Duration::from_millis(250));
+        let dispatch = bus.publish(event);
+        let handlers: Vec<String> = dispatch
+            .handled_by
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        writeln!(
+            out,
+            "[{}{}]",
+            handlers.join(" "),
+            if dispatch.stopped { ", stopped" } else { "" }
+        )?;
+        out.write_all(&transcript.borrow_mut().split_off(0))?;
+    }
+
+    writeln!(out, "Log:")?;
+    for logged in bus.log() {
+        writeln!(out, "  {:>6.2?} {:?}", logged.at, logged.event)?;
+    }
+
+    // a fresh bus without the shortcut sees every key
+    let mut fresh = Bus::new();
+    subscribe(&mut fresh);
+    fresh.unsubscribe(shortcut);
+    let stopped = fresh.replay(bus.log()).iter().filter(|d| d.stopped).count();
+    writeln!(
+        out,
+        "Replayed without the shortcut, {} events stopped:",
+        stopped
+    )?;
+    out.write_all(&transcript.borrow())?;
+    Ok(())
+}
+
+/* JSON Lines */
+// a wire format for WebEvent, one JSON object per line; see jsonl.rs
+
+fn part14(out: &mut dyn Write) -> io::Result<()> {
+    let events = [
+        WebEvent::PageLoad,
+        WebEvent::KeyPress('X'),
+        WebEvent::Paste("say \"hi\"\n\tand 🦀".to_owned()),
+        WebEvent::Click { x: 20, y: 80 },
+        WebEvent::PageUnload,
+    ];
+    let mut wire = Vec::new();
+    jsonl::write_all(&mut wire, &events)?;
+    out.write_all(&wire)?;
+
+    // what another process would read back
+    let decoded: Result<Vec<WebEvent>, _> = jsonl::Decoder::new(&wire[..]).collect();
+    match decoded {
+        Ok(decoded) => writeln!(
+            out,
+            "Decoded {} events, same as sent: {}",
+            decoded.len(),
+            decoded == events
+        )?,
+        Err(e) => writeln!(out, "Could not decode: {}", e)?,
+    }
+
+    let damaged = concat!(
+        "{\"event\":\"KeyPress\",\"key\":\"a\"}\n",
+        "{\"event\":\"Scroll\",\"dy\":3}\n",
+        "\n",
+        "{\"event\":\"Click\",\"x\":20}\n",
+        "{\"event\":\"Click\",\"x\":20,\"y\":8.5}\n",
+        "{\"event\":\"Paste\",\"text\":\"unterminated}\n",
+        "{\"event\":\"PageUnload\"}\n",
+    );
+    for result in jsonl::Decoder::new(damaged.as_bytes()) {
+        match result {
+            Ok(event) => writeln!(out, "ok: {:?}", event)?,
+            Err(e) => writeln!(out, "error: {}", e)?,
+        }
+    }
+    Ok(())
+}
+
+/* Text input */
+// a text field editing itself from a scripted session of events
+fn part15(out: &mut dyn Write) -> io::Result<()> {
+    use textinput::{TextField, BACKSPACE, REDO, UNDO};
+
+    // 8 by 16 pixel cells
+    let mut field = TextField::new(8, 16);
+    let mut script: Vec<WebEvent> = "Hello wrld".chars().map(WebEvent::KeyPress).collect();
+    script.extend([
+        // between "w" and "r"
+        WebEvent::Click { x: 53, y: 4 },
+        WebEvent::KeyPress('o'),
+        WebEvent::Click { x: 200, y: 4 },
+        WebEvent::Paste(String::from("!\nsecond line")),
+        WebEvent::KeyPress(BACKSPACE),
+        WebEvent::KeyPress(BACKSPACE),
+        WebEvent::KeyPress(BACKSPACE),
+        WebEvent::KeyPress(UNDO),
+        WebEvent::KeyPress(UNDO),
+        WebEvent::KeyPress(REDO),
+        WebEvent::Click { x: 20, y: 30 },
+        WebEvent::KeyPress('>'),
+    ]);
+    for event in &script {
+        let changed = field.apply(event);
+        let (line, column) = field.cursor_position();
+        writeln!(
+            out,
+            "{:<28} {} {}:{} {:?}",
+            format!("{:?}", event),
+            if changed { '*' } else { ' ' },
+            line,
+            column,
+            field.to_string()
+        )?;
+    }
+    Ok(())
+}
+
+/* Widget tree */
+// a dialog with a text box and two buttons, clicked and typed into headlessly
+fn part16(out: &mut dyn Write) -> io::Result<()> {
+    use bus::Flow;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use textinput::TextField;
+    use widget::{Phase, Tree};
+
+    let rect = |x0, y0, x1, y1| Rectangle {
+        top_left: Point { x: x0, y: y0 },
+        bottom_right: Point { x: x1, y: y1 },
+    };
+    let mut tree = Tree::new("dialog", rect(0, 0, 200, 120));
+    let dialog = tree.root();
+    let name = tree.add(dialog, "name", rect(10, 10, 190, 30));
+    let buttons = tree.add(dialog, "buttons", rect(0, 80, 200, 120));
+    let ok = tree.add(buttons, "ok", rect(110, 90, 150, 110));
+    let cancel = tree.add(buttons, "cancel", rect(150, 90, 190, 110));
+    tree.set_focusable(name, true);
+
+    // the text box edits a field laid out in 8 by 20 pixel cells
+    let field = Rc::new(RefCell::new(TextField::new(8, 20)));
+    let edited = Rc::clone(&field);
+    tree.on(name, move |d| {
+        if let WebEvent::Click { x, y } = d.event {
+            // relative to the box
+            edited.borrow_mut().apply(&WebEvent::Click {
+                x: x - 10,
+                y: y - 10,
+            });
+        } else {
+            edited.borrow_mut().apply(d.event);
+        }
+        Flow::Continue
+    });
+    // the button row notices clicks on either button as they bubble up
+    let transcript = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&transcript);
+    let names = [(ok, "ok"), (cancel, "cancel")];
+    tree.on(buttons, move |d| {
+        if d.phase == Phase::Bubble {
+            if let Some((_, button)) = names.iter().find(|(id, _)| *id == d.target) {
+                log.borrow_mut().push(format!("{} pressed", button));
+            }
+        }
+        Flow::Continue
+    });
+    // a modal dialog swallows the escape key before anything sees it
+    let log = Rc::clone(&transcript);
+    tree.on(dialog, move |d| match (d.phase, d.event) {
+        (Phase::Capture, WebEvent::KeyPress('\u{1b}')) => {
+            log.borrow_mut().push(String::from("escape: closing"));
+            Flow::Stop
+        }
+        _ => Flow::Continue,
+    });
+
+    let mut script = vec![WebEvent::KeyPress('x'), WebEvent::Click { x: 50, y: 20 }];
+    script.extend("Ferris".chars().map(WebEvent::KeyPress));
+    script.extend([
+        WebEvent::Click { x: 10, y: 15 },
+        WebEvent::Paste(String::from("Dr. ")),
+        WebEvent::Click { x: 120, y: 100 },
+        WebEvent::KeyPress('!'),
+        WebEvent::Click { x: 150, y: 100 },
+        WebEvent::Click { x: 250, y: 100 },
+        WebEvent::Click { x: 50, y: 15 },
+        WebEvent::KeyPress('\u{1b}'),
+    ]);
+    for event in &script {
+        let route = tree.dispatch(event);
+        let target = route.target.map_or("nothing", |id| tree.name(id));
+        let focus = tree.focused().map_or("-", |id| tree.name(id));
+        writeln!(
+            out,
+            "{:<24} -> {:<7} focus {:<4} {} handler(s){}",
+            format!("{:?}", event),
+            target,
+            focus,
+            route.visited.len(),
+            if route.stopped { ", stopped" } else { "" }
+        )?;
+        for line in transcript.borrow_mut().drain(..) {
+            writeln!(out, "  {}", line)?;
+        }
+    }
+    writeln!(out, "Name field: {}", field.borrow())?;
+    Ok(())
+}
+
+/* RPN calculator */
+// the same session the `rpn` binary would print for this input on stdin
+fn part17(out: &mut dyn Write) -> io::Result<()> {
+    let session = concat!(
+        "2 3 4 * +\n",
+        "dup 7 % swap 7 / \n",
+        "2 31 ^\n",
+        "drop drop 5 neg 2 ^\n",
+        "0 /\n",
+        "2147483647 1 +\n",
+        "sqrt\n",
+        "swap\n",
+    );
+    rpn::repl(session.as_bytes(), out)
+}
+
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "structs: regular, tuple and unit structs",
+        part1a => "structs activity: Rectangle area and square",
+        part2 => "enums: inspecting WebEvent variants",
+        part3 => "type aliases: the Operations enum",
+        part4 => "use declarations for enum variants",
+        part5 => "C-like enums with explicit discriminants",
+        part6 => "linked list built from an enum",
+        part7 => "constants: const and static",
+        part8 => "geometry: normalised rectangles with checked arithmetic",
+        part9 => "quadtree: range, nearest and radius queries",
+        part10 => "quadtree benchmark against a linear scan",
+        part11 => "raster canvas: shapes as ASCII art and PPM images",
+        part12 => "SVG export: styled shapes, labels, groups and transforms",
+        part13 => "event bus: subscribers, priorities and replay",
+        part14 => "WebEvent as JSON Lines, with decoding errors",
+        part15 => "text input: cursor, clicks and coalesced undo",
+        part16 => "widget tree: hit-testing, capture and bubble, focus",
+        part17 => "RPN calculator: checked operators on a stack",
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspected(event: WebEvent) -> String {
+        let mut out = Vec::new();
+        inspect(&mut out, event).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn inspect_describes_each_event() {
+        assert_eq!(inspected(WebEvent::PageLoad), "Page load\n");
+        assert_eq!(inspected(WebEvent::KeyPress('X')), "Pressed X\n");
+        assert_eq!(inspected(WebEvent::Paste("my_text".to_owned())), "Pasted \"my_text\"\n");
+        assert_eq!(inspected(WebEvent::Click { x: 20, y: 80 }), "clicked at x=20, y=80\n");
+    }
 }
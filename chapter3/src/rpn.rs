@@ -0,0 +1,298 @@
+/* RPN calculator */
+// a stack machine for reverse Polish notation: numbers are pushed, and each
+// operator pops its operands and pushes its results, so `2 3 4 * +` is 14
+
+// arithmetic is checked, so overflow and division by zero are errors
+// rather than panics or wrapped values, and a line that fails leaves the
+// stack as it was before the line
+
+use std::error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VeryVeryVerboseEnumOfThingsToDoWithNumbers {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    /// The remainder, with the sign of the dividend like `%`.
+    Modulo,
+    Power,
+    Negate,
+    Dup,
+    Swap,
+    Drop,
+}
+
+pub type Operations = VeryVeryVerboseEnumOfThingsToDoWithNumbers;
+
+impl Operations {
+    pub const ALL: [Operations; 10] = [
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::Divide,
+        Self::Modulo,
+        Self::Power,
+        Self::Negate,
+        Self::Dup,
+        Self::Swap,
+        Self::Drop,
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+            Self::Modulo => "%",
+            Self::Power => "^",
+            Self::Negate => "neg",
+            Self::Dup => "dup",
+            Self::Swap => "swap",
+            Self::Drop => "drop",
+        }
+    }
+
+    pub fn parse(token: &str) -> Option<Operations> {
+        Self::ALL.into_iter().find(|op| op.symbol() == token)
+    }
+
+    /// How many values the operation pops.
+    pub fn arity(&self) -> usize {
+        match self {
+            Self::Negate | Self::Dup | Self::Drop => 1,
+            _ => 2,
+        }
+    }
+
+    /// Evaluates `x y op` and returns the value left on top.
+    pub fn run(&self, x: i32, y: i32) -> Result<i32, RpnError> {
+        let mut stack = vec![x, y];
+        self.apply(&mut stack)?;
+        Ok(*stack.last().expect("every operation leaves a value here"))
+    }
+
+    /// Pops the operands and pushes the results; on error `stack` is
+    /// unchanged.
+    pub fn apply(&self, stack: &mut Vec<i32>) -> Result<(), RpnError> {
+        let arity = self.arity();
+        if stack.len() < arity {
+            return Err(RpnError::StackUnderflow {
+                op: *self,
+                needed: arity,
+                found: stack.len(),
+            });
+        }
+        let operands = &stack[stack.len() - arity..];
+        let results = match (self, operands) {
+            (Self::Negate, &[x]) => vec![x.checked_neg().ok_or(RpnError::Overflow(*self))?],
+            (Self::Dup, &[x]) => vec![x, x],
+            (Self::Drop, &[_]) => vec![],
+            (Self::Swap, &[x, y]) => vec![y, x],
+            (_, &[x, y]) => vec![self.arithmetic(x, y)?],
+            _ => unreachable!("operands are sliced to the arity"),
+        };
+        stack.truncate(stack.len() - arity);
+        stack.extend(results);
+        Ok(())
+    }
+
+    fn arithmetic(&self, x: i32, y: i32) -> Result<i32, RpnError> {
+        if y == 0 && matches!(self, Self::Divide | Self::Modulo) {
+            return Err(RpnError::DivisionByZero(*self));
+        }
+        let result = match self {
+            Self::Add => x.checked_add(y),
+            Self::Subtract => x.checked_sub(y),
+            Self::Multiply => x.checked_mul(y),
+            Self::Divide => x.checked_div(y),
+            Self::Modulo => x.checked_rem(y),
+            Self::Power => {
+                let exponent = u32::try_from(y).map_err(|_| RpnError::NegativeExponent)?;
+                x.checked_pow(exponent)
+            }
+            _ => unreachable!("{} is not arithmetic", self),
+        };
+        result.ok_or(RpnError::Overflow(*self))
+    }
+}
+
+impl fmt::Display for Operations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpnError {
+    /// Neither a number nor an operator.
+    UnknownToken(String),
+    /// A number that does not fit an i32.
+    OutOfRange(String),
+    StackUnderflow {
+        op: Operations,
+        needed: usize,
+        found: usize,
+    },
+    Overflow(Operations),
+    DivisionByZero(Operations),
+    NegativeExponent,
+}
+
+impl fmt::Display for RpnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpnError::UnknownToken(token) => write!(f, "unknown token `{}`", token),
+            RpnError::OutOfRange(token) => write!(f, "{} does not fit in an i32", token),
+            RpnError::StackUnderflow { op, needed, found } => write!(
+                f,
+                "`{}` needs {} value(s) but the stack has {}",
+                op, needed, found
+            ),
+            RpnError::Overflow(op) => write!(f, "`{}` overflowed", op),
+            RpnError::DivisionByZero(op) => write!(f, "`{}` by zero", op),
+            RpnError::NegativeExponent => write!(f, "`^` needs a non-negative exponent"),
+        }
+    }
+}
+
+impl error::Error for RpnError {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Calculator {
+    stack: Vec<i32>,
+}
+
+impl Calculator {
+    pub fn new() -> Calculator {
+        Calculator::default()
+    }
+
+    /// From the bottom up.
+    pub fn stack(&self) -> &[i32] {
+        &self.stack
+    }
+
+    /// Evaluates whitespace-separated tokens; when one fails, the whole
+    /// line is undone.
+    pub fn eval(&mut self, line: &str) -> Result<(), RpnError> {
+        let mut stack = self.stack.clone();
+        for token in line.split_whitespace() {
+            match Operations::parse(token) {
+                Some(op) => op.apply(&mut stack)?,
+                None => stack.push(parse_number(token)?),
+            }
+        }
+        self.stack = stack;
+        Ok(())
+    }
+}
+
+fn parse_number(token: &str) -> Result<i32, RpnError> {
+    token.parse().map_err(|_| {
+        let digits = token.strip_prefix(['-', '+']).unwrap_or(token);
+        if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            RpnError::OutOfRange(token.to_owned())
+        } else {
+            RpnError::UnknownToken(token.to_owned())
+        }
+    })
+}
+
+/// Evaluates each line of `input`, then prints the stack, or the error.
+pub fn repl(input: impl BufRead, out: &mut dyn Write) -> io::Result<()> {
+    let mut calculator = Calculator::new();
+    for line in input.lines() {
+        match calculator.eval(&line?) {
+            Ok(()) => {
+                let values: Vec<String> = calculator.stack().iter().map(i32::to_string).collect();
+                writeln!(out, "[{}]", values.join(" "))?;
+            }
+            Err(e) => writeln!(out, "error: {}", e)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(line: &str) -> Result<Vec<i32>, RpnError> {
+        let mut calculator = Calculator::new();
+        calculator.eval(line)?;
+        Ok(calculator.stack().to_vec())
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(eval("2 3 4 * +"), Ok(vec![14]));
+        assert_eq!(eval("7 -2 / 7 -2 % -7 2 %"), Ok(vec![-3, 1, -1]));
+        assert_eq!(eval("2 10 ^ 5 0 ^"), Ok(vec![1024, 1]));
+        assert_eq!(eval("1 2 swap 3 dup neg drop"), Ok(vec![2, 1, 3]));
+        assert_eq!(eval("  "), Ok(vec![]));
+        for op in Operations::ALL {
+            assert_eq!(Operations::parse(op.symbol()), Some(op));
+        }
+        assert_eq!(Operations::Add.run(5, 6), Ok(11));
+        assert_eq!(Operations::Negate.run(5, 6), Ok(-6));
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let max = i32::MAX.to_string();
+        let min = i32::MIN.to_string();
+        assert_eq!(
+            eval(&format!("{} 1 +", max)),
+            Err(RpnError::Overflow(Operations::Add))
+        );
+        assert_eq!(
+            eval(&format!("{} neg", min)),
+            Err(RpnError::Overflow(Operations::Negate))
+        );
+        assert_eq!(
+            eval(&format!("{} -1 /", min)),
+            Err(RpnError::Overflow(Operations::Divide))
+        );
+        assert_eq!(eval("2 31 ^"), Err(RpnError::Overflow(Operations::Power)));
+        assert_eq!(
+            eval("1 0 %"),
+            Err(RpnError::DivisionByZero(Operations::Modulo))
+        );
+        assert_eq!(eval("2 -1 ^"), Err(RpnError::NegativeExponent));
+    }
+
+    #[test]
+    fn bad_input() {
+        assert_eq!(
+            eval("1 +"),
+            Err(RpnError::StackUnderflow {
+                op: Operations::Add,
+                needed: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            eval("2147483648"),
+            Err(RpnError::OutOfRange(String::from("2147483648")))
+        );
+        assert_eq!(eval("1 x"), Err(RpnError::UnknownToken(String::from("x"))));
+        assert_eq!(
+            eval("--1"),
+            Err(RpnError::UnknownToken(String::from("--1")))
+        );
+    }
+
+    #[test]
+    fn a_failing_line_is_undone() {
+        let mut out = Vec::new();
+        repl("1 2\n3 0 /\n+ 10 *\nswap\n".as_bytes(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[1 2]\nerror: `/` by zero\n[30]\nerror: `swap` needs 2 value(s) but the stack has 1\n"
+        );
+    }
+}
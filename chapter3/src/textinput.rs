@@ -0,0 +1,343 @@
+/* Text field driven by WebEvent */
+// `KeyPress` types a character at the cursor, `Paste` inserts text there
+// and `Click` moves the cursor to the character under the pointer, with the
+// text laid out on a monospace grid of `cell_width` by `cell_height` pixels
+
+// a few control characters stand for editing keys, as terminals send them:
+// backspace deletes before the cursor, delete after it, and ctrl-z and
+// ctrl-y undo and redo. other control characters, except newline, are ignored
+
+// every change can be undone; consecutive keypresses of the same kind,
+// typing or deleting, are coalesced into one step, and typing whitespace
+// after a word starts a new one, so undo removes a whole typed word rather
+// than one letter. moving the cursor or pasting also ends a step
+
+use std::fmt;
+
+use crate::WebEvent;
+
+pub const BACKSPACE: char = '\u{8}';
+pub const DELETE: char = '\u{7f}';
+pub const UNDO: char = '\u{1a}';
+pub const REDO: char = '\u{19}';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct State {
+    text: String,
+    /// In characters, from 0 up to the text's length.
+    cursor: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Typing,
+    Deleting,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextField {
+    state: State,
+    undo: Vec<State>,
+    redo: Vec<State>,
+    /// The kind of step the next keypress may join.
+    open: Option<Step>,
+    cell_width: i64,
+    cell_height: i64,
+}
+
+impl TextField {
+    /// An empty field; cell sizes below one pixel count as one.
+    pub fn new(cell_width: i64, cell_height: i64) -> TextField {
+        TextField {
+            state: State {
+                text: String::new(),
+                cursor: 0,
+            },
+            undo: Vec::new(),
+            redo: Vec::new(),
+            open: None,
+            cell_width: cell_width.max(1),
+            cell_height: cell_height.max(1),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.state.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.state.cursor
+    }
+
+    /// The cursor's line and column, both from 0.
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let before: String = self.state.text.chars().take(self.state.cursor).collect();
+        let line = before.matches('\n').count();
+        let column = before.chars().rev().take_while(|&c| c != '\n').count();
+        (line, column)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Applies one event, returning whether the text or the cursor changed.
+    pub fn apply(&mut self, event: &WebEvent) -> bool {
+        match event {
+            WebEvent::KeyPress(UNDO) => self.undo(),
+            WebEvent::KeyPress(REDO) => self.redo(),
+            WebEvent::KeyPress(BACKSPACE) => self.delete_before(),
+            WebEvent::KeyPress(DELETE) => self.delete_after(),
+            WebEvent::KeyPress(c) if c.is_control() && *c != '\n' => false,
+            WebEvent::KeyPress(c) => {
+                let after_word = self.before_cursor().is_some_and(|b| !b.is_whitespace());
+                if c.is_whitespace() && after_word {
+                    self.open = None;
+                }
+                self.record(Some(Step::Typing));
+                self.insert(&c.to_string());
+                true
+            }
+            WebEvent::Paste(text) if text.is_empty() => false,
+            WebEvent::Paste(text) => {
+                self.record(None);
+                self.insert(text);
+                true
+            }
+            WebEvent::Click { x, y } => self.click(*x, *y),
+            WebEvent::PageLoad | WebEvent::PageUnload => false,
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        self.open = None;
+        let Some(previous) = self.undo.pop() else {
+            return false;
+        };
+        self.redo.push(std::mem::replace(&mut self.state, previous));
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.open = None;
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push(std::mem::replace(&mut self.state, next));
+        true
+    }
+
+    // saves the state before a change, unless the change joins the open step
+    fn record(&mut self, step: Option<Step>) {
+        if step.is_none() || step != self.open {
+            self.undo.push(self.state.clone());
+        }
+        self.redo.clear();
+        self.open = step;
+    }
+
+    fn before_cursor(&self) -> Option<char> {
+        let cursor = self.state.cursor.checked_sub(1)?;
+        self.state.text.chars().nth(cursor)
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.state
+            .text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.state.text.len(), |(i, _)| i)
+    }
+
+    fn insert(&mut self, text: &str) {
+        let at = self.byte_index(self.state.cursor);
+        self.state.text.insert_str(at, text);
+        self.state.cursor += text.chars().count();
+    }
+
+    fn delete_before(&mut self) -> bool {
+        if self.state.cursor == 0 {
+            return false;
+        }
+        self.record(Some(Step::Deleting));
+        self.state.cursor -= 1;
+        let at = self.byte_index(self.state.cursor);
+        self.state.text.remove(at);
+        true
+    }
+
+    fn delete_after(&mut self) -> bool {
+        if self.state.cursor == self.state.text.chars().count() {
+            return false;
+        }
+        self.record(Some(Step::Deleting));
+        let at = self.byte_index(self.state.cursor);
+        self.state.text.remove(at);
+        true
+    }
+
+    // the nearest character boundary to the click: clicks past the end of
+    // a line go to its end, and clicks below the text to the last line
+    fn click(&mut self, x: i64, y: i64) -> bool {
+        self.open = None;
+        let row = usize::try_from(y.div_euclid(self.cell_height)).unwrap_or(0);
+        // rounding, so that the right half of a cell means after it
+        let column = x
+            .saturating_add(self.cell_width / 2)
+            .div_euclid(self.cell_width);
+        let column = usize::try_from(column).unwrap_or(0);
+
+        let lines: Vec<&str> = self.state.text.split('\n').collect();
+        let row = row.min(lines.len() - 1);
+        let before: usize = lines[..row]
+            .iter()
+            .map(|line| line.chars().count() + 1)
+            .sum();
+        let cursor = before + column.min(lines[row].chars().count());
+        let moved = cursor != self.state.cursor;
+        self.state.cursor = cursor;
+        moved
+    }
+}
+
+/// The text with a `|` at the cursor.
+impl fmt::Display for TextField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let at = self.byte_index(self.state.cursor);
+        write!(f, "{}|{}", &self.state.text[..at], &self.state.text[at..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> Vec<WebEvent> {
+        text.chars().map(WebEvent::KeyPress).collect()
+    }
+
+    fn run(field: &mut TextField, script: impl IntoIterator<Item = WebEvent>) {
+        for event in script {
+            field.apply(&event);
+        }
+    }
+
+    #[test]
+    fn typing_pasting_and_deleting() {
+        let mut field = TextField::new(10, 20);
+        run(&mut field, typed("hello"));
+        run(
+            &mut field,
+            [
+                WebEvent::Paste(String::from(", wörld")),
+                WebEvent::KeyPress(BACKSPACE),
+                WebEvent::KeyPress('D'),
+            ],
+        );
+        assert_eq!(field.to_string(), "hello, wörlD|");
+        assert_eq!(field.cursor(), 12);
+        assert!(!field.apply(&WebEvent::KeyPress(DELETE)));
+        assert!(!field.apply(&WebEvent::PageLoad));
+        assert!(!field.apply(&WebEvent::KeyPress('\u{1b}')));
+        assert!(field.apply(&WebEvent::KeyPress('\n')));
+        assert_eq!(field.cursor(), 13);
+    }
+
+    #[test]
+    fn clicks_place_the_cursor_on_the_grid() {
+        let mut field = TextField::new(10, 20);
+        run(
+            &mut field,
+            [WebEvent::Paste(String::from("first\nsecond line"))],
+        );
+        assert_eq!(field.cursor_position(), (1, 11));
+
+        // the left half of the third cell, then its right half
+        field.apply(&WebEvent::Click { x: 21, y: 5 });
+        assert_eq!(field.cursor_position(), (0, 2));
+        field.apply(&WebEvent::Click { x: 26, y: 5 });
+        assert_eq!(field.cursor_position(), (0, 3));
+        run(&mut field, typed("X"));
+        assert_eq!(field.to_string(), "firX|st\nsecond line");
+
+        // past the end of the line, and below the last line
+        field.apply(&WebEvent::Click { x: 500, y: 10 });
+        assert_eq!(field.cursor_position(), (0, 6));
+        assert!(field.apply(&WebEvent::Click { x: 30, y: 400 }));
+        assert_eq!(field.cursor_position(), (1, 3));
+        assert!(!field.apply(&WebEvent::Click { x: 30, y: 400 }));
+        field.apply(&WebEvent::Click { x: -50, y: -50 });
+        assert_eq!(field.cursor(), 0);
+
+        // the extremes the JSON Lines decoder accepts
+        field.apply(&WebEvent::Click {
+            x: i64::MAX,
+            y: i64::MAX,
+        });
+        assert_eq!(field.cursor_position(), (1, 11));
+        field.apply(&WebEvent::Click {
+            x: i64::MIN,
+            y: i64::MIN,
+        });
+        assert_eq!(field.cursor(), 0);
+    }
+
+    #[test]
+    fn keypresses_coalesce_into_one_undo_step() {
+        let mut field = TextField::new(10, 20);
+        run(&mut field, typed("one"));
+        run(&mut field, [WebEvent::Paste(String::from(" two"))]);
+        run(&mut field, typed(" three"));
+        run(
+            &mut field,
+            [WebEvent::KeyPress(BACKSPACE), WebEvent::KeyPress(BACKSPACE)],
+        );
+        assert_eq!(field.text(), "one two thr");
+
+        let mut history = Vec::new();
+        while field.apply(&WebEvent::KeyPress(UNDO)) {
+            history.push(field.to_string());
+        }
+        assert_eq!(history, ["one two three|", "one two|", "one|", "|"]);
+        assert!(!field.can_undo());
+
+        run(
+            &mut field,
+            [WebEvent::KeyPress(REDO), WebEvent::KeyPress(REDO)],
+        );
+        assert_eq!(field.to_string(), "one two|");
+        // a new edit drops what could have been redone
+        run(&mut field, typed("!"));
+        assert!(!field.can_redo());
+        run(&mut field, [WebEvent::KeyPress(UNDO)]);
+        assert_eq!(field.to_string(), "one two|");
+    }
+
+    #[test]
+    fn typed_words_undo_one_at_a_time() {
+        let mut field = TextField::new(10, 20);
+        run(&mut field, typed("hello  big world"));
+        let mut history = Vec::new();
+        while field.undo() {
+            history.push(field.to_string());
+        }
+        assert_eq!(history, ["hello  big|", "hello|", "|"]);
+    }
+
+    #[test]
+    fn moving_the_cursor_ends_a_step() {
+        let mut field = TextField::new(10, 20);
+        run(&mut field, typed("ac"));
+        run(&mut field, [WebEvent::Click { x: 10, y: 0 }]);
+        run(&mut field, typed("b"));
+        assert_eq!(field.to_string(), "ab|c");
+        field.undo();
+        assert_eq!(field.to_string(), "a|c");
+        field.undo();
+        assert_eq!(field.to_string(), "|");
+    }
+}
@@ -0,0 +1,352 @@
+/* Widget tree */
+// a retained tree of widgets, each with `Rectangle` bounds, that routes
+// `WebEvent`s the way a browser routes DOM events: a click goes to the
+// top-most widget under the pointer, keys and pastes to the focused one
+
+// the event first travels down from the root through the target's
+// ancestors (capture), then reaches the target, then travels back up
+// (bubble). any handler can stop it with `Flow::Stop`
+
+// children are drawn over their parent and later siblings over earlier
+// ones, and a child only receives clicks inside its parent's bounds
+
+use crate::bus::Flow;
+use crate::{Rectangle, WebEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WidgetId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Capture,
+    Target,
+    Bubble,
+}
+
+/// What a handler is told about the event reaching it.
+#[derive(Debug, Clone, Copy)]
+pub struct Delivery<'a> {
+    pub event: &'a WebEvent,
+    pub phase: Phase,
+    pub target: WidgetId,
+    /// The widget whose handler is running.
+    pub current: WidgetId,
+}
+
+/// How one event travelled through the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// `None` when nothing was under the click, or nothing had focus.
+    pub target: Option<WidgetId>,
+    /// The widgets with handlers that ran, in order.
+    pub visited: Vec<(WidgetId, Phase)>,
+    pub stopped: bool,
+}
+
+type Handler = Box<dyn FnMut(&Delivery) -> Flow>;
+
+struct Widget {
+    name: String,
+    bounds: Rectangle,
+    parent: Option<WidgetId>,
+    children: Vec<WidgetId>,
+    focusable: bool,
+    handler: Option<Handler>,
+}
+
+pub struct Tree {
+    widgets: Vec<Widget>,
+    focus: Option<WidgetId>,
+}
+
+impl Tree {
+    pub fn new(name: &str, bounds: Rectangle) -> Tree {
+        let mut tree = Tree {
+            widgets: Vec::new(),
+            focus: None,
+        };
+        tree.push(name, bounds, None);
+        tree
+    }
+
+    pub fn root(&self) -> WidgetId {
+        WidgetId(0)
+    }
+
+    /// Adds a widget on top of `parent`'s existing children.
+    ///
+    /// Panics if `parent` is out of range for this tree. Ids are plain
+    /// indices, so one from another tree that happens to be in range is not
+    /// caught and attaches to whichever widget has that index.
+    pub fn add(&mut self, parent: WidgetId, name: &str, bounds: Rectangle) -> WidgetId {
+        let id = self.push(name, bounds, Some(parent));
+        self.widgets[parent.0].children.push(id);
+        id
+    }
+
+    fn push(&mut self, name: &str, bounds: Rectangle, parent: Option<WidgetId>) -> WidgetId {
+        let id = WidgetId(self.widgets.len());
+        self.widgets.push(Widget {
+            name: name.to_owned(),
+            bounds,
+            parent,
+            children: Vec::new(),
+            focusable: false,
+            handler: None,
+        });
+        id
+    }
+
+    /// Replaces the widget's handler.
+    pub fn on(&mut self, id: WidgetId, handler: impl FnMut(&Delivery) -> Flow + 'static) {
+        self.widgets[id.0].handler = Some(Box::new(handler));
+    }
+
+    pub fn set_focusable(&mut self, id: WidgetId, focusable: bool) {
+        self.widgets[id.0].focusable = focusable;
+        if !focusable && self.focus == Some(id) {
+            self.focus = None;
+        }
+    }
+
+    pub fn name(&self, id: WidgetId) -> &str {
+        &self.widgets[id.0].name
+    }
+
+    pub fn bounds(&self, id: WidgetId) -> &Rectangle {
+        &self.widgets[id.0].bounds
+    }
+
+    pub fn parent(&self, id: WidgetId) -> Option<WidgetId> {
+        self.widgets[id.0].parent
+    }
+
+    pub fn children(&self, id: WidgetId) -> &[WidgetId] {
+        &self.widgets[id.0].children
+    }
+
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focus
+    }
+
+    /// `false`, leaving the focus alone, when the widget is not focusable.
+    pub fn focus(&mut self, id: WidgetId) -> bool {
+        if !self.widgets[id.0].focusable {
+            return false;
+        }
+        self.focus = Some(id);
+        true
+    }
+
+    pub fn blur(&mut self) {
+        self.focus = None;
+    }
+
+    /// The top-most widget containing the point.
+    pub fn hit_test(&self, x: i64, y: i64) -> Option<WidgetId> {
+        let (x, y) = (i32::try_from(x).ok()?, i32::try_from(y).ok()?);
+        let point = geometry::Point::new(x, y);
+        let contains = |id: WidgetId| geometry::Rectangle::from(self.bounds(id)).contains(point);
+
+        let mut hit = self.root();
+        if !contains(hit) {
+            return None;
+        }
+        // the last child drawn over the point, until there is none
+        while let Some(&child) = self.children(hit).iter().rev().find(|&&c| contains(c)) {
+            hit = child;
+        }
+        Some(hit)
+    }
+
+    /// The widgets from the root down to `id`.
+    pub fn path(&self, id: WidgetId) -> Vec<WidgetId> {
+        let mut path = vec![id];
+        while let Some(parent) = self.parent(*path.last().unwrap()) {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /// Routes one event. A click also moves the focus to the nearest
+    /// focusable widget on its path, or clears it when there is none.
+    pub fn dispatch(&mut self, event: &WebEvent) -> Route {
+        let target = match event {
+            WebEvent::Click { x, y } => {
+                let target = self.hit_test(*x, *y);
+                self.focus = target.and_then(|target| {
+                    let path = self.path(target);
+                    path.into_iter()
+                        .rev()
+                        .find(|&id| self.widgets[id.0].focusable)
+                });
+                target
+            }
+            WebEvent::KeyPress(_) | WebEvent::Paste(_) => self.focus,
+            WebEvent::PageLoad | WebEvent::PageUnload => Some(self.root()),
+        };
+        let mut route = Route {
+            target,
+            visited: Vec::new(),
+            stopped: false,
+        };
+        let Some(target) = target else {
+            return route;
+        };
+
+        let path = self.path(target);
+        let (ancestors, _) = path.split_at(path.len() - 1);
+        let stops = ancestors
+            .iter()
+            .map(|&id| (id, Phase::Capture))
+            .chain([(target, Phase::Target)])
+            .chain(ancestors.iter().rev().map(|&id| (id, Phase::Bubble)));
+        for (current, phase) in stops {
+            let Some(handler) = &mut self.widgets[current.0].handler else {
+                continue;
+            };
+            route.visited.push((current, phase));
+            let delivery = Delivery {
+                event,
+                phase,
+                target,
+                current,
+            };
+            if handler(&delivery) == Flow::Stop {
+                route.stopped = true;
+                break;
+            }
+        }
+        route
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn rect(x0: i32, y0: i32, x1: i32, y1: i32) -> Rectangle {
+        Rectangle {
+            top_left: Point { x: x0, y: y0 },
+            bottom_right: Point { x: x1, y: y1 },
+        }
+    }
+
+    fn click(x: i64, y: i64) -> WebEvent {
+        WebEvent::Click { x, y }
+    }
+
+    // a window with a toolbar holding two overlapping buttons, and a
+    // focusable text box; the ids are in that order
+    fn window() -> (Tree, [WidgetId; 5]) {
+        let mut tree = Tree::new("window", rect(0, 0, 100, 100));
+        let window = tree.root();
+        let toolbar = tree.add(window, "toolbar", rect(0, 0, 100, 20));
+        let open = tree.add(toolbar, "open", rect(0, 0, 30, 20));
+        let save = tree.add(toolbar, "save", rect(25, 0, 60, 40));
+        let text = tree.add(window, "text", rect(0, 20, 100, 100));
+        tree.set_focusable(text, true);
+        (tree, [window, toolbar, open, save, text])
+    }
+
+    fn record_all(tree: &mut Tree, ids: &[WidgetId]) -> Rc<RefCell<Vec<String>>> {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        for &id in ids {
+            let seen = Rc::clone(&seen);
+            let name = tree.name(id).to_owned();
+            tree.on(id, move |d| {
+                seen.borrow_mut().push(format!("{} {:?}", name, d.phase));
+                Flow::Continue
+            });
+        }
+        seen
+    }
+
+    #[test]
+    fn hit_testing_prefers_the_top_most_widget() {
+        let (tree, [window, toolbar, open, save, text]) = window();
+        assert_eq!(tree.hit_test(10, 10), Some(open));
+        // open and save overlap; save was added later
+        assert_eq!(tree.hit_test(27, 10), Some(save));
+        assert_eq!(tree.hit_test(80, 10), Some(toolbar));
+        // save reaches below the toolbar, but is clipped to it
+        assert_eq!(tree.hit_test(40, 30), Some(text));
+        assert_eq!(tree.hit_test(100, 100), Some(text));
+        assert_eq!(tree.hit_test(101, 50), None);
+        assert_eq!(tree.hit_test(i64::MAX, 0), None);
+        assert_eq!(tree.path(save), [window, toolbar, save]);
+    }
+
+    #[test]
+    fn clicks_capture_then_bubble() {
+        let (mut tree, ids) = window();
+        let seen = record_all(&mut tree, &ids);
+        let route = tree.dispatch(&click(40, 10));
+        assert_eq!(route.target, Some(ids[3]));
+        assert!(!route.stopped);
+        assert_eq!(
+            *seen.borrow(),
+            [
+                "window Capture",
+                "toolbar Capture",
+                "save Target",
+                "toolbar Bubble",
+                "window Bubble"
+            ]
+        );
+        assert!(tree.dispatch(&click(-1, 0)).target.is_none());
+    }
+
+    #[test]
+    fn a_handler_can_stop_the_event() {
+        let (mut tree, ids) = window();
+        let seen = record_all(&mut tree, &ids);
+        // the toolbar swallows clicks before its buttons see them
+        tree.on(ids[1], |d| match d.phase {
+            Phase::Capture => Flow::Stop,
+            _ => Flow::Continue,
+        });
+        let route = tree.dispatch(&click(10, 10));
+        assert_eq!(
+            route.visited,
+            [(ids[0], Phase::Capture), (ids[1], Phase::Capture)]
+        );
+        assert!(route.stopped);
+        assert_eq!(*seen.borrow(), ["window Capture"]);
+    }
+
+    #[test]
+    fn keys_go_to_the_focused_widget() {
+        let (mut tree, [window, _, open, _, text]) = window();
+        let typed = Rc::new(RefCell::new(String::new()));
+        let sink = Rc::clone(&typed);
+        tree.on(text, move |d| {
+            if let (WebEvent::KeyPress(c), Phase::Target) = (d.event, d.phase) {
+                sink.borrow_mut().push(*c);
+            }
+            Flow::Continue
+        });
+
+        assert_eq!(tree.dispatch(&WebEvent::KeyPress('a')).target, None);
+        tree.dispatch(&click(50, 50));
+        assert_eq!(tree.focused(), Some(text));
+        tree.dispatch(&WebEvent::KeyPress('h'));
+        tree.dispatch(&WebEvent::KeyPress('i'));
+
+        // clicking something that cannot take focus blurs the text box
+        tree.dispatch(&click(10, 10));
+        assert_eq!(tree.focused(), None);
+        tree.dispatch(&WebEvent::KeyPress('!'));
+        assert_eq!(*typed.borrow(), "hi");
+
+        assert!(!tree.focus(open));
+        assert!(tree.focus(text));
+        tree.set_focusable(text, false);
+        assert_eq!(tree.focused(), None);
+        assert_eq!(tree.dispatch(&WebEvent::PageLoad).target, Some(window));
+    }
+}
@@ -0,0 +1,25 @@
+// the calculator binary, driven through stdin
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn repl_prints_the_stack_after_each_line() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rpn"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"1 2 3\n* -\n0 %\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "[1 2 3]\n[-5]\nerror: `%` by zero\n"
+    );
+}
@@ -1,28 +1,31 @@
+use std::io::{self, Write};
+
 // Variable bindings
 
 // mutability
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let _immutable_binding = 1;
     let mut mutable_binding = 1;
 
-    println!("Before mutation: {}", mutable_binding);
+    writeln!(out, "Before mutation: {}", mutable_binding)?;
 
     mutable_binding += 1;
 
-    println!("Before mutation: {}", mutable_binding);
+    writeln!(out, "Before mutation: {}", mutable_binding)?;
+    Ok(())
 }
 
 // scope and shadowing
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let outer_variable = 5;
     {
         let outer_variable = "abc";
-        println!("Inside block: {}", outer_variable);
+        writeln!(out, "Inside block: {}", outer_variable)?;
     }
-    println!("Outside block: {}", outer_variable);
+    writeln!(out, "Outside block: {}", outer_variable)?;
     let outer_variable = "hello";
-    println!("After shadowing: {}", outer_variable);
+    writeln!(out, "After shadowing: {}", outer_variable)?;
 
     let number;
     // if we use number before initialization, we get error
@@ -32,7 +35,7 @@ fn part2() {
         number = x * x;
     }
 
-    println!("Number: {}", number);
+    writeln!(out, "Number: {}", number)?;
 
     // Freezing
 
@@ -43,9 +46,12 @@ fn part2() {
     }
     _mutable_integer = 7;
     // not frozen in this scope
+    Ok(())
 }
 
-fn main() {
-    part1();
-    part2();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "mutability of variable bindings",
+        part2 => "scope, shadowing, declare first and freezing",
+    ])
 }
@@ -1,20 +1,23 @@
+use std::io::{self, Write};
+
 // Types
 
 // casting
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     // rust does not implicitly cast
     let decimal = 654.432__f32;
 
     let integer = decimal as u8;
     // floor for decimal
     // takes max value in case of overflow
-    println!("Decimal: {}", decimal);
-    println!("Integer: {}", integer);
+    writeln!(out, "Decimal: {}", decimal)?;
+    writeln!(out, "Integer: {}", integer)?;
 
     unsafe {
-        println!("300.0 as u8 = {}", 300.0_f32.to_int_unchecked::<u8>());
+        writeln!(out, "300.0 as u8 = {}", 300.0_f32.to_int_unchecked::<u8>())?;
     }
+    Ok(())
 }
 
 // aliasing
@@ -23,19 +26,22 @@ type Nanosecond = u64;
 type Inch = u64;
 type U64 = u64;
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let nanoseconds: Nanosecond = 5 as U64;
     let inches: Inch = 7 as U64;
     // alias is just another name and not a new type
-    println!(
+    writeln!(out, 
         "{} nanoseconds + {} inches = {} -- no type safety",
         nanoseconds,
         inches,
         nanoseconds + inches
-    );
+    )?;
+    Ok(())
 }
 
-fn main() {
-    part1();
-    part2();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "casting between primitive types",
+        part2 => "type aliasing",
+    ])
 }
@@ -5,6 +5,7 @@
 use std::convert::From;
 
 use std::fmt::{self, Formatter};
+use std::io::{self, Write};
 
 struct Number {
     value: i32,
@@ -24,14 +25,15 @@ impl From<i32> for Number {
 // note that Into is automatically implemented by primitive type if From is implemented by desired type.
 // One will have to specially typecast to get value
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let number = 5i32;
     let my_number = Number::from(number);
-    println!("My number: {}", my_number);
+    writeln!(out, "My number: {}", my_number)?;
 
     let int = 5;
     let num: Number = int.into();
-    println!("Now my number is {}", num);
+    writeln!(out, "Now my number is {}", num)?;
+    Ok(())
 }
 
 // TryFrom and TryInto
@@ -56,7 +58,7 @@ impl TryFrom<i32> for EvenNumber {
     }
 }
 
-fn part2() {
+fn part2(_out: &mut dyn Write) -> io::Result<()> {
     assert_eq!(EvenNumber::try_from(8), Ok(EvenNumber(8)));
     assert_eq!(EvenNumber::try_from(5), Err(()));
 
@@ -64,6 +66,7 @@ fn part2() {
     assert_eq!(result, Ok(EvenNumber(8)));
     let result: Result<EvenNumber, ()> = 5i32.try_into();
     assert_eq!(result, Err(()));
+    Ok(())
 }
 
 // To and From Strings
@@ -80,19 +83,24 @@ impl fmt::Display for Circle {
     }
 }
 
-fn part3() {
+// calling `to_string` explicitly is the point of the example
+#[allow(clippy::to_string_in_format_args)]
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     let circle = Circle { radius: 5 };
-    println!("{}", circle.to_string());
+    writeln!(out, "{}", circle.to_string())?;
 
     // to get number from a string
 
     let parsed: i32 = "5".parse().unwrap();
     let turbo_parsed = "10".parse::<i32>().unwrap();
-    println!("{}, {}", parsed, turbo_parsed);
+    writeln!(out, "{}, {}", parsed, turbo_parsed)?;
+    Ok(())
 }
 
-fn main() {
-    part1();
-    part2();
-    part3();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "From and Into",
+        part2 => "TryFrom and TryInto",
+        part3 => "converting to and from strings",
+    ])
 }
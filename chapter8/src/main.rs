@@ -1,68 +1,73 @@
 #![allow(dead_code)]
+use std::io::{self, Write};
+
 // if else
 
-fn part1() {
+fn part1(out: &mut dyn Write) -> io::Result<()> {
     let n = 5;
 
     if n < 0 {
-        println!("{} is negative", n);
+        writeln!(out, "{} is negative", n)?;
     } else if n > 0 {
-        println!("{} is positive", n);
+        writeln!(out, "{} is positive", n)?;
     } else {
-        println!("{} is zero", n);
+        writeln!(out, "{} is zero", n)?;
     }
 
     let big_n = if n < 10 && n > -10 {
-        println!("increase 10 times");
+        writeln!(out, "increase 10 times")?;
         10 * n
     } else {
-        println!("half the number");
+        writeln!(out, "half the number")?;
         n / 2
     };
-    println!("Now big_n: {}", big_n);
+    writeln!(out, "Now big_n: {}", big_n)?;
+    Ok(())
 }
 
 // loops: loop
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     // loop means an infinite loop
     let mut count = 0u32;
     loop {
         count += 1;
 
         if count == 3 {
-            println!("three!");
+            writeln!(out, "three!")?;
             continue;
         }
-        println!("Count: {}", count);
+        writeln!(out, "Count: {}", count)?;
 
         if count == 5 {
-            println!("Exiting the loop");
+            writeln!(out, "Exiting the loop")?;
             break;
         }
     }
+    Ok(())
 }
 
 // loop labels
 
-#[allow(unused_labels, unreachable_code)]
-fn part3() {
+#[allow(unused_labels, unreachable_code, clippy::never_loop)]
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     'outer: loop {
-        println!("Inside outer loop");
+        writeln!(out, "Inside outer loop")?;
 
         'inner: loop {
-            println!("Inside inner loop");
+            writeln!(out, "Inside inner loop")?;
 
             break 'outer;
         }
-        println!("Never reached here");
+        writeln!(out, "Never reached here")?;
     }
-    println!("Out of outer loop");
+    writeln!(out, "Out of outer loop")?;
+    Ok(())
 }
 
 // returning from loop
 
-fn part4() {
+fn part4(out: &mut dyn Write) -> io::Result<()> {
     let mut counter = 0;
 
     let result = loop {
@@ -72,48 +77,52 @@ fn part4() {
             // counter*2 is returned
         }
     };
-    println!("Result = {}", result);
+    writeln!(out, "Result = {}", result)?;
+    Ok(())
 }
 
 // while loop
 
-fn part5() {
+fn part5(out: &mut dyn Write) -> io::Result<()> {
     let mut n = 1;
 
     while n < 101 {
         if n % 15 == 0 {
-            println!("FizzBuzz");
+            writeln!(out, "FizzBuzz")?;
         } else if n % 3 == 0 {
-            println!("Fizz");
+            writeln!(out, "Fizz")?;
         } else if n % 5 == 0 {
-            println!("Buzz")
+            writeln!(out, "Buzz")?
         } else {
-            println!("{}", n);
+            writeln!(out, "{}", n)?;
         }
         n += 1;
     }
+    Ok(())
 }
 
 // for-in loop
 
-fn part6() {
+fn part6(out: &mut dyn Write) -> io::Result<()> {
     for n in 1..101 {
         // or for n in 1..=100
         if n % 15 == 0 {
-            println!("FizzBuzz");
+            writeln!(out, "FizzBuzz")?;
         } else if n % 3 == 0 {
-            println!("Fizz");
+            writeln!(out, "Fizz")?;
         } else if n % 5 == 0 {
-            println!("Buzz")
+            writeln!(out, "Buzz")?
         } else {
-            println!("{}", n);
+            writeln!(out, "{}", n)?;
         }
     }
+    Ok(())
 }
 
 // for with iterators
 
-fn part7() {
+#[allow(clippy::useless_vec)]
+fn part7(out: &mut dyn Write) -> io::Result<()> {
     // for loop applies into_iter method on a collection
 
     // iter - borrows element therefore allows reuse of collection
@@ -121,8 +130,8 @@ fn part7() {
 
     for name in names.iter() {
         match name {
-            &"nameC" => println!("Special welcome to {}", name),
-            _ => println!("Hello {}", name),
+            &"nameC" => writeln!(out, "Special welcome to {}", name)?,
+            _ => writeln!(out, "Hello {}", name)?,
         }
     }
 
@@ -137,19 +146,20 @@ fn part7() {
             _ => "Hello",
         }
     }
-    println!("Modified: {:?}", names);
+    writeln!(out, "Modified: {:?}", names)?;
+    Ok(())
 }
 
 // match
 
-fn part8() {
+fn part8(out: &mut dyn Write) -> io::Result<()> {
     // match can be used like a C switch
     let number = 7;
     match number {
-        1 => println!("One"),
-        2 | 3 | 5 | 7 | 9 | 11 => println!("Prime number"),
-        13..=19 => println!("Teen"),
-        _ => println!("Too big to analyze"),
+        1 => writeln!(out, "One")?,
+        2 | 3 | 5 | 7 | 9 | 11 => writeln!(out, "Prime number")?,
+        13..=19 => writeln!(out, "Teen")?,
+        _ => writeln!(out, "Too big to analyze")?,
     }
 
     let boolean = true;
@@ -157,42 +167,44 @@ fn part8() {
         true => 1,
         false => 0,
     };
+    Ok(())
 }
 
 // match destructuring
 
-fn part9() {
-    println!("Tuples :-");
+#[allow(unused_variables, clippy::disallowed_names)]
+fn part9(out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "Tuples :-")?;
 
     let triple = (3, 1, 5);
     match triple {
-        (0, y, z) => println!("`0` : `{:?}` : `{:?}", y, z),
-        (1, ..) => println!("1 : something"),
-        (.., 2) => println!("something : 2"),
-        (3, .., 4) => println!("3 : something : 4"),
-        _ => println!("It is something not special"),
+        (0, y, z) => writeln!(out, "`0` : `{:?}` : `{:?}", y, z)?,
+        (1, ..) => writeln!(out, "1 : something")?,
+        (.., 2) => writeln!(out, "something : 2")?,
+        (3, .., 4) => writeln!(out, "3 : something : 4")?,
+        _ => writeln!(out, "It is something not special")?,
     }
 
-    println!("Arrays :-");
+    writeln!(out, "Arrays :-")?;
     let array = [2, 5, 9, 7];
     match array {
-        [0, y, z, ..] => println!("0, {y}, {z}"),
+        [0, y, z, ..] => writeln!(out, "0, {y}, {z}")?,
         // use _ to ignore single value
-        [1, y, ..] => println!("starts with 1 then has {y}"),
+        [1, y, ..] => writeln!(out, "starts with 1 then has {y}")?,
 
-        [3, second, tail @ ..] => println!(
+        [3, second, tail @ ..] => writeln!(out, 
             "array[0] = 3, array[1] = {second} and other elements are {:?}",
             tail
-        ),
+        )?,
 
-        [first, middle @ .., last] => println!(
+        [first, middle @ .., last] => writeln!(out, 
             "array[0] = {}, middle = {:?}, array[2] = {}",
             first, middle, last
-        ),
+        )?,
     }
 
     // enum and structs can also be destructured in obvious manner
-    println!("Structs");
+    writeln!(out, "Structs")?;
     struct Foo {
         x: (u32, u32),
         y: u32,
@@ -201,23 +213,25 @@ fn part9() {
     let foo = Foo { x: (1, 2), y: 3 };
 
     match foo {
-        Foo { x: (1, a), y } => println!("Got {a}"),
+        Foo { x: (1, a), y } => writeln!(out, "Got {a}")?,
         Foo { x, .. } => (),
     }
+    Ok(())
 }
 
 // pointers/ref
 // Dereferencing => `*`
 // Destructuring => `&`, `ref`, and `ref mut`
 
-fn part10() {
+#[allow(clippy::match_single_binding, clippy::toplevel_ref_arg)]
+fn part10(out: &mut dyn Write) -> io::Result<()> {
     let reference = &4;
     match reference {
-        &val => println!("Got by destructuring, {:?}", val),
+        &val => writeln!(out, "Got by destructuring, {:?}", val)?,
     }
 
     match *reference {
-        val => println!("Got by dereferencing: {:?}", val),
+        val => writeln!(out, "Got by dereferencing: {:?}", val)?,
     }
 
     // another way to assign reference
@@ -225,7 +239,7 @@ fn part10() {
 
     let value = 4;
     match value {
-        ref r => println!("Got a reference: {:?}", r),
+        ref r => writeln!(out, "Got a reference: {:?}", r)?,
     }
 
     // similarly ref mut can be used
@@ -233,21 +247,24 @@ fn part10() {
     match mut_value {
         ref mut m => {
             *m += 10; // deref is mandatory before adding
-            println!("New value of mut_value is {:?}", m);
+            writeln!(out, "New value of mut_value is {:?}", m)?;
         }
     }
+    Ok(())
 }
 
 // match guards
 
-fn part11() {
+#[allow(clippy::redundant_guards)]
+fn part11(out: &mut dyn Write) -> io::Result<()> {
     let number = 4u8;
 
     match number {
-        i if i == 0 => println!("Zero"),
-        i if i > 0 => println!("Greater then zero"),
+        i if i == 0 => writeln!(out, "Zero")?,
+        i if i > 0 => writeln!(out, "Greater then zero")?,
         _ => unreachable!("Should never happen, but mandatory because compiler does not check guard condition in match"),
     }
+    Ok(())
 }
 
 // @ sigil to prevent rebinding
@@ -256,47 +273,49 @@ fn age() -> u32 {
     19
 }
 
-fn part12() {
+fn part12(out: &mut dyn Write) -> io::Result<()> {
     match age() {
-        1 => println!("Haven't celebrated first birthday"),
+        1 => writeln!(out, "Haven't celebrated first birthday")?,
         // this will allow to use range condition and get value
-        n @ 1..=12 => println!("A child of age {}", n),
-        n @ 13..=19 => println!("A teenager of age {}", n),
-        n => println!("A old person of age {}", n),
+        n @ 1..=12 => writeln!(out, "A child of age {}", n)?,
+        n @ 13..=19 => writeln!(out, "A teenager of age {}", n)?,
+        n => writeln!(out, "A old person of age {}", n)?,
     }
 
     // can also use in enum destructuring
     // MyEnum::Variant(n @ 15) => printlN!("15!")
+    Ok(())
 }
 
 // if let -> when we know the situation will never differ
 
-fn part13() {
+fn part13(out: &mut dyn Write) -> io::Result<()> {
     let number = Some(7);
     if let Some(i) = number {
-        println!("Number matched: {}", i);
+        writeln!(out, "Number matched: {}", i)?;
     }
 
     let letter: Option<char> = None;
     // for using _,
     if let Some(i) = letter {
-        println!("Got letter {}", i)
+        writeln!(out, "Got letter {}", i)?
     } else {
-        println!("Could not match with Some")
+        writeln!(out, "Could not match with Some")?
     }
 
     // for particular cases
     let i_like_letters = false;
     let emoticon: Option<i32> = None;
     if let Some(i) = emoticon {
-        println!("Matched {}", i);
+        writeln!(out, "Matched {}", i)?;
     } else if i_like_letters {
-        println!("Letters, which you like ");
+        writeln!(out, "Letters, which you like ")?;
     } else {
-        println!("Neither letters nor emoticons..");
+        writeln!(out, "Neither letters nor emoticons..")?;
     }
 
     // note that we can use if-let to match enum variants even if it does not implement PartialEq
+    Ok(())
 }
 
 // let-else
@@ -317,22 +336,25 @@ fn get_count_item(s: &str) -> (u64, &str) {
     (count, item)
 }
 
-fn part14() {
+fn part14(_out: &mut dyn Write) -> io::Result<()> {
     assert_eq!(get_count_item("3 chairs"), (3, "chairs"));
+    Ok(())
 }
 
 // while let
 
-fn part15() {
+// the long-hand loop is kept on purpose to compare it with `while let`
+#[allow(clippy::while_let_loop)]
+fn part15(out: &mut dyn Write) -> io::Result<()> {
     let mut optional = Some(0);
     loop {
         match optional {
             Some(i) => {
                 if i > 9 {
-                    println!("Exiting");
+                    writeln!(out, "Exiting")?;
                     optional = None;
                 } else {
-                    println!("i is {} still", i);
+                    writeln!(out, "i is {} still", i)?;
                     optional = Some(i + 1);
                 }
             }
@@ -346,29 +368,32 @@ fn part15() {
     let mut optional = Some(0);
     while let Some(i) = optional {
         if i > 9 {
-            println!("Exiting");
+            writeln!(out, "Exiting")?;
             optional = None;
         } else {
-            println!("i is {} yet", i);
+            writeln!(out, "i is {} yet", i)?;
             optional = Some(i + 1);
         }
     }
+    Ok(())
 }
 
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    // part6();
-    // part7();
-    // part8();
-    // part9();
-    // part10();
-    // part11();
-    // part12();
-    // part13();
-    // part14();
-    part15();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "if/else branches and if as an expression",
+        part2 => "infinite loop with continue and break",
+        part3 => "nested loops and loop labels",
+        part4 => "returning a value from a loop",
+        part5 => "FizzBuzz with a while loop",
+        part6 => "FizzBuzz with a for-in range",
+        part7 => "for loops over iter and iter_mut",
+        part8 => "match as a C-like switch",
+        part9 => "destructuring tuples, arrays and structs in match",
+        part10 => "pointers and ref patterns in match",
+        part11 => "match guards",
+        part12 => "binding with the @ sigil",
+        part13 => "if let",
+        part14 => "let-else",
+        part15 => "while let",
+    ])
 }
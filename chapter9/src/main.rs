@@ -1,6 +1,9 @@
 #![allow(dead_code)]
+use std::io::{self, Write};
+
 // intro
 
+#[allow(clippy::manual_is_multiple_of)]
 fn is_divisible_by(lhs: u32, rhs: u32) -> bool {
     if rhs == 0 {
         return false;
@@ -8,26 +11,29 @@ fn is_divisible_by(lhs: u32, rhs: u32) -> bool {
     lhs % rhs == 0
 }
 
-fn fizzbuzz(n: u32) {
+fn fizzbuzz(out: &mut dyn Write, n: u32) -> io::Result<()> {
     if is_divisible_by(n, 15) {
-        println!("fizzbuzz");
+        writeln!(out, "fizzbuzz")?;
     } else if is_divisible_by(n, 3) {
-        println!("fizz");
+        writeln!(out, "fizz")?;
     } else if is_divisible_by(n, 5) {
-        println!("buzz");
+        writeln!(out, "buzz")?;
     } else {
-        println!("{}", n);
+        writeln!(out, "{}", n)?;
     }
+    Ok(())
 }
 
-fn fizzbuzz_to(n: u32) {
+fn fizzbuzz_to(out: &mut dyn Write, n: u32) -> io::Result<()> {
     for n in 1..=n {
-        fizzbuzz(n);
+        fizzbuzz(out, n)?;
     }
+    Ok(())
 }
 
-fn part1() {
-    fizzbuzz_to(100);
+fn part1(out: &mut dyn Write) -> io::Result<()> {
+    fizzbuzz_to(out, 100)?;
+    Ok(())
 }
 
 // Associated functions and methods
@@ -52,18 +58,42 @@ struct Rectangle {
 impl Rectangle {
     // method
     // &self is sugar for self: &Self
-    fn area(&self) -> f64 {
-        let Point { x: x1, y: y1 } = self.p1;
-        let Point { x: x2, y: y2 } = self.p2;
-        ((x1 - x2) * (y1 - y2)).abs()
+    // the geometry crate does the arithmetic; `None` when the area is not
+    // finite
+    fn area(&self) -> Option<f64> {
+        geometry::Rectangle::from(self).area()
     }
 
+    // a translation that would make a coordinate infinite or NaN leaves the
+    // rectangle where it was
     fn translate(&mut self, x: f64, y: f64) {
-        self.p1.x += x;
-        self.p2.x += x;
+        let p1 = geometry::Point::from(&self.p1).translate(x, y);
+        let p2 = geometry::Point::from(&self.p2).translate(x, y);
+        if let (Some(p1), Some(p2)) = (p1, p2) {
+            self.p1 = p1.into();
+            self.p2 = p2.into();
+        }
+    }
+}
+
+impl From<&Point> for geometry::Point<f64> {
+    fn from(point: &Point) -> geometry::Point<f64> {
+        geometry::Point::new(point.x, point.y)
+    }
+}
 
-        self.p1.y += y;
-        self.p2.y += y;
+impl From<geometry::Point<f64>> for Point {
+    fn from(point: geometry::Point<f64>) -> Point {
+        Point {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl From<&Rectangle> for geometry::Rectangle<f64> {
+    fn from(rect: &Rectangle) -> geometry::Rectangle<f64> {
+        geometry::Rectangle::new((&rect.p1).into(), (&rect.p2).into())
     }
 }
 
@@ -72,21 +102,32 @@ impl Rectangle {
 struct Pair(Box<i32>, Box<i32>);
 
 impl Pair {
-    fn destroy(self) {
+    fn destroy(self, out: &mut dyn Write) -> io::Result<()> {
         let Pair(first, second) = self;
-        println!("Destroying ({}, {})", first, second);
+        writeln!(out, "Destroying ({}, {})", first, second)?;
+        Ok(())
     }
 }
 
-fn part2() {
+fn part2(out: &mut dyn Write) -> io::Result<()> {
     let rectangle = Rectangle {
         p1: Point::origin(),
         p2: Point { x: 3.0, y: 4.0 },
     };
-    println!("Area of rectangle = {}", rectangle.area());
+    let huge = Rectangle {
+        p1: Point { x: -f64::MAX, y: 0.0 },
+        p2: Point { x: f64::MAX, y: 2.0 },
+    };
+    for rectangle in [rectangle, huge] {
+        match rectangle.area() {
+            Some(area) => writeln!(out, "Area of rectangle = {}", area)?,
+            None => writeln!(out, "Area of rectangle is too large for an f64")?,
+        }
+    }
 
     let pair = Pair(Box::new(1), Box::new(2));
-    pair.destroy();
+    pair.destroy(out)?;
+    Ok(())
 }
 
 // closures
@@ -94,19 +135,20 @@ fn part2() {
 // they can capture enclosing environment
 // input and return type can be inferred
 
-fn part3() {
+fn part3(out: &mut dyn Write) -> io::Result<()> {
     let outer_var = 42;
     // fn function(i: i32) -> i32 {
     //     i + outer_val
     // }
     let closure_annotated = |i: i32| -> i32 { i + outer_var };
     let closure_inferred = |i| i + outer_var;
-    println!("Closure1: {}", closure_annotated(1));
-    println!("Closure2: {}", closure_inferred(1));
+    writeln!(out, "Closure1: {}", closure_annotated(1))?;
+    writeln!(out, "Closure2: {}", closure_inferred(1))?;
     // note that if a type has been inferred, then other type cannot be used for the same closure.
     // e.g. if we now call for 1i64, it will be invalid
     let one = || 1;
-    println!("{}", one());
+    writeln!(out, "{}", one())?;
+    Ok(())
 }
 
 // capturing in closures
@@ -116,54 +158,58 @@ fn part3() {
 // 3. by value
 // they can automatically determine it reducing requirement for explicit declaration
 
-fn part4() {
+#[allow(clippy::useless_vec)]
+fn part4(out: &mut dyn Write) -> io::Result<()> {
     use std::mem;
 
     let color = String::from("green");
     // reference borrowed immutably
-    let print = || println!("color: {}", color);
-    print();
+    // the sink is a parameter so that it does not change how `color` is captured
+    let print = |out: &mut dyn Write| writeln!(out, "color: {}", color);
+    print(out)?;
 
     // these are allowed
     let _reborrow = &color;
-    print();
+    print(out)?;
 
     let _color_moved = color;
 
     // reference borrowed mutably
     let mut count = 0;
-    let mut inc = || {
+    let mut inc = |out: &mut dyn Write| {
         count += 1;
-        println!("Count is {}", count);
+        writeln!(out, "Count is {}", count)
     };
-    inc();
+    inc(out)?;
 
     // let _reborrow = &count;
     // cant do let _reborrow = &count because mutably borrowed
 
-    inc();
+    inc(out)?;
 
     // here we can reborrow
     let _count_reborrowed = &count;
 
     let movable = Box::new(3);
 
-    let consume = || {
-        println!("movable: {:?}", movable);
+    let consume = |out: &mut dyn Write| -> io::Result<()> {
+        writeln!(out, "movable: {:?}", movable)?;
         mem::drop(movable);
+        Ok(())
     };
 
-    consume();
+    consume(out)?;
 
     // to force move, move keyword can be used e.g. in multithreading
 
     let haystack = vec![1, 2, 3];
 
     let contains = move |needle| haystack.contains(needle);
-    println!("{}", contains(&1));
-    println!("{}", contains(&4));
+    writeln!(out, "{}", contains(&1))?;
+    writeln!(out, "{}", contains(&4))?;
 
     // now we cant use haystack in this scope because it has been moved to closure
+    Ok(())
 }
 
 // closures as input parameters for functions
@@ -175,11 +221,11 @@ fn part4() {
 
 // compiler captures variable in least restrictive manner possible, even though only more restrictive trait has been implemented
 
-fn apply<F>(f: F)
+fn apply<F>(out: &mut dyn Write, f: F) -> io::Result<()>
 where
-    F: FnOnce(),
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
 {
-    f();
+    f(out)
 }
 
 fn apply_to_3<F>(f: F) -> i32
@@ -189,7 +235,7 @@ where
     f(3)
 }
 
-fn part5() {
+fn part5(out: &mut dyn Write) -> io::Result<()> {
     use std::mem;
 
     let greeting = "hello";
@@ -197,71 +243,77 @@ fn part5() {
     // this is an owned data from borrowed one
     let mut farewell = "goodbye".to_owned();
 
-    let diary = || {
-        println!("I said {}", greeting);
+    let diary = |out: &mut dyn Write| -> io::Result<()> {
+        writeln!(out, "I said {}", greeting)?;
 
         farewell.push_str("!!!");
 
-        println!("Then I screamed {}", farewell);
-        println!("Now I can sleep...");
+        writeln!(out, "Then I screamed {}", farewell)?;
+        writeln!(out, "Now I can sleep...")?;
         mem::drop(farewell);
+        Ok(())
     };
 
-    apply(diary);
+    apply(out, diary)?;
     // this wont work if FnMut or Fn only was implemented because we have moved the value
 
     let double = |x| x * 2;
-    println!("3 doubled {}", apply_to_3(double));
+    writeln!(out, "3 doubled {}", apply_to_3(double))?;
+    Ok(())
 }
 
 // closure as output
 // Fn, FnMut and FnOnce trait implementations can be passed
 // must use move keyword to prevent invalid references in closure
 
-fn create_fn() -> impl Fn() {
+fn create_fn() -> impl Fn(&mut dyn Write) -> io::Result<()> {
     let text = "function!".to_owned();
 
-    move || println!("This is a {}", text)
+    move |out: &mut dyn Write| writeln!(out, "This is a {}", text)
 }
 
-fn create_fn_mut() -> impl FnMut() {
+fn create_fn_mut() -> impl FnMut(&mut dyn Write) -> io::Result<()> {
     let text = "mutable function!".to_owned();
-    move || println!("This is a {}", text)
+    move |out: &mut dyn Write| writeln!(out, "This is a {}", text)
 }
 
-fn create_fn_once() -> impl FnOnce() {
+fn create_fn_once() -> impl FnOnce(&mut dyn Write) -> io::Result<()> {
     let text = "FnOnce function!".to_owned();
-    move || println!("This is a {}", text)
+    move |out: &mut dyn Write| writeln!(out, "This is a {}", text)
 }
 
-fn part6() {
+fn part6(out: &mut dyn Write) -> io::Result<()> {
     let fn_plain = create_fn();
     let mut fn_mut = create_fn_mut();
     let fn_once = create_fn_once();
 
-    fn_plain();
-    fn_mut();
-    fn_once();
+    fn_plain(out)?;
+    fn_mut(out)?;
+    fn_once(out)?;
+    Ok(())
 }
 
 // some examples of use of closures
 
 // Iterator::any -> function which if passed an iterator will return true if any element satisfies a predicate
 
-fn part7() {
+#[allow(clippy::useless_vec, clippy::manual_contains)]
+fn part7(out: &mut dyn Write) -> io::Result<()> {
     let vec1 = vec![1, 2, 3];
     let vec2 = vec![4, 5, 6];
 
     // destructuring needed for .iter() not for .into_iter()
-    println!("2 in vec1: {}", vec1.iter().any(|&x| x == 2));
+    writeln!(out, "2 in vec1: {}", vec1.iter().any(|&x| x == 2))?;
 
-    println!("2 in vec2: {}", vec2.into_iter().any(|x| x == 2));
+    writeln!(out, "2 in vec2: {}", vec2.into_iter().any(|x| x == 2))?;
+    Ok(())
 }
 
 // Iterator::find
 // It searches for first value which satisfies a condition. If none satisfy, returns None.
 
-fn part8() {
+#[allow(clippy::useless_vec)]
+fn part8(out: &mut dyn Write) -> io::Result<()> {
     let vec1 = vec![1, 2, 3];
     let vec2 = vec![4, 5, 6];
 
@@ -269,11 +321,12 @@ fn part8() {
     let mut into_iter = vec2.into_iter();
 
     // && because we want to refer an item of &i32 returned by .iter()
-    println!("Find two in vec1: {:?}", iter.find(|&&x| x == 2));
+    writeln!(out, "Find two in vec1: {:?}", iter.find(|&&x| x == 2))?;
 
-    println!("Find two in vec2: {:?}", into_iter.find(|&x| x == 2));
+    writeln!(out, "Find two in vec2: {:?}", into_iter.find(|&x| x == 2))?;
 
     // for index of a position, use Iterator::position
+    Ok(())
 }
 
 // Higher order functions
@@ -285,9 +338,9 @@ fn is_odd(n: u32) -> bool {
     n % 2 == 1
 }
 
-fn part9() {
+fn part9(out: &mut dyn Write) -> io::Result<()> {
     let upper = 1000;
-    println!("Sum of squared odd numbers");
+    writeln!(out, "Sum of squared odd numbers")?;
     // imperative approach
     let mut acc = 0;
     for n in 0.. {
@@ -298,7 +351,7 @@ fn part9() {
             acc += n_square;
         }
     }
-    println!("First Approach: {}", acc);
+    writeln!(out, "First Approach: {}", acc)?;
 
     // functional approach
     let required_sum: u32 = (0..)
@@ -306,7 +359,8 @@ fn part9() {
         .take_while(|&n_square| n_square < upper)
         .filter(|&n_square| is_odd(n_square))
         .sum();
-    println!("Second Approach: {}", required_sum);
+    writeln!(out, "Second Approach: {}", required_sum)?;
+    Ok(())
 }
 
 // diverging functions
@@ -319,7 +373,7 @@ fn foo() -> ! {
 
 // also used in network servers. process terminators
 
-fn part10() {
+fn part10(out: &mut dyn Write) -> io::Result<()> {
     fn sum_odd(up_to: u32) -> u32 {
         let mut sum = 0;
         for i in 0..up_to {
@@ -331,18 +385,52 @@ fn part10() {
         }
         sum
     }
-    println!("Sum of odd numbers: {}", sum_odd(10));
+    writeln!(out, "Sum of odd numbers: {}", sum_odd(10))?;
+    Ok(())
 }
 
-fn main() {
-    // part1();
-    // part2();
-    // part3();
-    // part4();
-    // part5();
-    // part6();
-    // part7();
-    // part8();
-    // part9();
-    part10();
+fn main() -> std::process::ExitCode {
+    support::run(&support::parts![
+        part1 => "functions: FizzBuzz up to 100",
+        part2 => "associated functions and methods",
+        part3 => "closures with annotated and inferred types",
+        part4 => "capturing by reference, mutable reference and value",
+        part5 => "closures as input parameters",
+        part6 => "closures as output parameters",
+        part7 => "Iterator::any",
+        part8 => "Iterator::find",
+        part9 => "higher order functions",
+        part10 => "diverging functions",
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fizzbuzz_to_fifteen() {
+        let mut out = Vec::new();
+        fizzbuzz_to(&mut out, 15).unwrap();
+        let lines: Vec<_> = std::str::from_utf8(&out).unwrap().lines().collect();
+        assert_eq!(lines[..5], ["1", "2", "fizz", "4", "buzz"]);
+        assert_eq!(lines.last(), Some(&"fizzbuzz"));
+    }
+
+    #[test]
+    fn rectangles_use_the_geometry_crate() {
+        let mut rectangle = Rectangle {
+            p1: Point { x: 3.0, y: 4.0 },
+            p2: Point::origin(),
+        };
+        assert_eq!(rectangle.area(), Some(12.0));
+        rectangle.translate(1.0, -2.0);
+        assert_eq!((rectangle.p1.x, rectangle.p2.y), (4.0, -2.0));
+        rectangle.translate(f64::INFINITY, 0.0);
+        assert_eq!((rectangle.p1.x, rectangle.p2.x), (4.0, 1.0));
+
+        rectangle.p2.x = f64::MAX;
+        rectangle.p1.x = -f64::MAX;
+        assert_eq!(rectangle.area(), None);
+    }
 }
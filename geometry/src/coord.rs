@@ -0,0 +1,101 @@
+/* Coordinates */
+// the arithmetic the shapes need, with every operation that can overflow
+// returning an `Option`
+
+use std::fmt;
+
+pub trait Coord: Copy + PartialOrd + fmt::Debug + fmt::Display {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    /// Half of a non-negative value, rounded down for integers.
+    fn half(self) -> Self;
+    fn to_f64(self) -> f64;
+    /// `None` when `x` is not finite or out of range; integers round to nearest.
+    fn from_f64(x: f64) -> Option<Self>;
+}
+
+impl Coord for i32 {
+    const ZERO: i32 = 0;
+    const ONE: i32 = 1;
+
+    fn checked_add(self, other: i32) -> Option<i32> {
+        i32::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: i32) -> Option<i32> {
+        i32::checked_sub(self, other)
+    }
+
+    fn checked_mul(self, other: i32) -> Option<i32> {
+        i32::checked_mul(self, other)
+    }
+
+    fn half(self) -> i32 {
+        self / 2
+    }
+
+    fn to_f64(self) -> f64 {
+        self.into()
+    }
+
+    fn from_f64(x: f64) -> Option<i32> {
+        let rounded = x.round();
+        let in_range = rounded >= f64::from(i32::MIN) && rounded <= f64::from(i32::MAX);
+        in_range.then_some(rounded as i32)
+    }
+}
+
+/// Results that overflow to an infinity count as overflow, like for i32.
+fn finite(x: f64) -> Option<f64> {
+    x.is_finite().then_some(x)
+}
+
+impl Coord for f64 {
+    const ZERO: f64 = 0.0;
+    const ONE: f64 = 1.0;
+
+    fn checked_add(self, other: f64) -> Option<f64> {
+        finite(self + other)
+    }
+
+    fn checked_sub(self, other: f64) -> Option<f64> {
+        finite(self - other)
+    }
+
+    fn checked_mul(self, other: f64) -> Option<f64> {
+        finite(self * other)
+    }
+
+    fn half(self) -> f64 {
+        self / 2.0
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(x: f64) -> Option<f64> {
+        finite(x)
+    }
+}
+
+/// The smaller of two coordinates; `PartialOrd` has no `min`.
+pub fn min<T: Coord>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+pub fn max<T: Coord>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
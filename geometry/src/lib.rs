@@ -0,0 +1,24 @@
+/* Shared geometry */
+// points and shapes used by several chapters; coordinates are generic over
+// `Coord`, implemented for i32 (checked, so overflow is `None` rather than a
+// panic or a wrapped value) and f64 (where only infinities are rejected)
+
+// screen orientation: x grows to the right and y grows downwards, so the top
+// left corner of a rectangle has the smallest coordinates
+
+pub mod coord;
+pub mod point;
+pub mod polygon;
+pub mod quadtree;
+pub mod raster;
+pub mod rect;
+pub mod segment;
+pub mod svg;
+
+pub use coord::Coord;
+pub use point::Point;
+pub use polygon::{convex_hull, Polygon, PolygonError};
+pub use quadtree::QuadTree;
+pub use raster::{Canvas, PpmFormat};
+pub use rect::Rectangle;
+pub use segment::{Intersection, Orientation, Segment};
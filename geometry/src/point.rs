@@ -0,0 +1,60 @@
+/* Points */
+
+use std::fmt;
+
+use crate::coord::Coord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Coord> Point<T> {
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+
+    pub fn origin() -> Point<T> {
+        Point::new(T::ZERO, T::ZERO)
+    }
+
+    /// `None` when a coordinate overflows.
+    pub fn translate(self, dx: T, dy: T) -> Option<Point<T>> {
+        Some(Point::new(self.x.checked_add(dx)?, self.y.checked_add(dy)?))
+    }
+
+    /// Squared distance, computed in f64 so that it cannot overflow.
+    pub fn distance_squared(self, other: Point<T>) -> f64 {
+        let dx = self.x.to_f64() - other.x.to_f64();
+        let dy = self.y.to_f64() - other.y.to_f64();
+        dx * dx + dy * dy
+    }
+
+    pub fn distance(self, other: Point<T>) -> f64 {
+        self.distance_squared(other).sqrt()
+    }
+}
+
+// the precision, as in `{:.2}`, applies to both coordinates
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        self.x.fmt(f)?;
+        write!(f, ", ")?;
+        self.y.fmt(f)?;
+        write!(f, ")")
+    }
+}
+
+impl From<Point<i32>> for Point<f64> {
+    fn from(point: Point<i32>) -> Point<f64> {
+        Point::new(point.x.into(), point.y.into())
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Point<T> {
+        Point { x, y }
+    }
+}
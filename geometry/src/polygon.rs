@@ -0,0 +1,388 @@
+/* Polygons */
+// simple polygons with f64 vertices, stored in order without repeating the
+// first vertex at the end; orientation is as in `segment`, with y pointing up
+
+use std::fmt;
+
+use crate::point::Point;
+use crate::rect::Rectangle;
+use crate::segment::{cross, Orientation, Segment};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolygonError {
+    TooFewVertices(usize),
+    NonFinite { index: usize },
+    NotConvex,
+}
+
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolygonError::TooFewVertices(n) => {
+                write!(f, "a polygon needs at least 3 vertices, got {}", n)
+            }
+            PolygonError::NonFinite { index } => {
+                write!(f, "vertex {} has a coordinate that is not finite", index)
+            }
+            PolygonError::NotConvex => write!(f, "the clipping polygon is not convex"),
+        }
+    }
+}
+
+impl std::error::Error for PolygonError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Point<f64>>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point<f64>>) -> Result<Polygon, PolygonError> {
+        if vertices.len() < 3 {
+            return Err(PolygonError::TooFewVertices(vertices.len()));
+        }
+        if let Some(index) = vertices
+            .iter()
+            .position(|p| !p.x.is_finite() || !p.y.is_finite())
+        {
+            return Err(PolygonError::NonFinite { index });
+        }
+        Ok(Polygon { vertices })
+    }
+
+    pub fn vertices(&self) -> &[Point<f64>] {
+        &self.vertices
+    }
+
+    /// Every edge, including the closing one from the last vertex to the first.
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices
+            .iter()
+            .zip(next)
+            .map(|(&start, &end)| Segment::new(start, end))
+    }
+
+    /// The shoelace formula: positive for counter-clockwise vertices.
+    pub fn signed_area(&self) -> f64 {
+        self.edges()
+            .map(|e| e.start.x * e.end.y - e.end.x * e.start.y)
+            .sum::<f64>()
+            / 2.0
+    }
+
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges().map(|e| e.length()).sum()
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        let area = self.signed_area();
+        if area > 0.0 {
+            Orientation::CounterClockwise
+        } else if area < 0.0 {
+            Orientation::Clockwise
+        } else {
+            Orientation::Collinear
+        }
+    }
+
+    /// The centre of mass, `None` for a polygon without area.
+    pub fn centroid(&self) -> Option<Point<f64>> {
+        let area = self.signed_area();
+        if area == 0.0 {
+            return None;
+        }
+        let (x, y) = self.edges().fold((0.0, 0.0), |(x, y), e| {
+            let w = e.start.x * e.end.y - e.end.x * e.start.y;
+            (x + (e.start.x + e.end.x) * w, y + (e.start.y + e.end.y) * w)
+        });
+        Some(Point::new(x / (6.0 * area), y / (6.0 * area)))
+    }
+
+    /// Ray casting to the right; points on the boundary count as inside.
+    pub fn contains(&self, point: Point<f64>) -> bool {
+        if self.edges().any(|e| e.contains(point)) {
+            return true;
+        }
+        let mut inside = false;
+        for e in self.edges() {
+            let (a, b) = (e.start, e.end);
+            if (a.y > point.y) != (b.y > point.y) {
+                let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if point.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    /// Whether every turn goes the same way and the boundary goes round
+    /// only once; collinear vertices are allowed.
+    pub fn is_convex(&self) -> bool {
+        let n = self.vertices.len();
+        let (mut left, mut right) = (false, false);
+        let mut turning = 0.0;
+        for i in 0..n {
+            let (a, b, c) = (
+                self.vertices[i],
+                self.vertices[(i + 1) % n],
+                self.vertices[(i + 2) % n],
+            );
+            let turn = cross(a, b, c);
+            left |= turn > 0.0;
+            right |= turn < 0.0;
+            let dot = (b.x - a.x) * (c.x - b.x) + (b.y - a.y) * (c.y - b.y);
+            turning += turn.atan2(dot);
+        }
+        // a star turns the same way at every vertex too, but twice round;
+        // the halfway point between one and two turns absorbs rounding
+        !(left && right) && turning.abs() < 3.0 * std::f64::consts::PI
+    }
+
+    pub fn bounding_box(&self) -> Rectangle<f64> {
+        Rectangle::bounding_box(self.vertices.iter().copied())
+            .expect("a polygon has at least 3 vertices")
+    }
+
+    /// The part of `self` inside the convex `window`
+    /// (Sutherland-Hodgman), `Ok(None)` when nothing is left.
+    pub fn clip(&self, window: &Polygon) -> Result<Option<Polygon>, PolygonError> {
+        if !window.is_convex() {
+            return Err(PolygonError::NotConvex);
+        }
+        // keep what is left of every window edge, so walk it counter-clockwise
+        let mut window = window.clone();
+        if window.signed_area() < 0.0 {
+            window.vertices.reverse();
+        }
+        let inside = |edge: &Segment, p: Point<f64>| cross(edge.start, edge.end, p) >= 0.0;
+
+        let mut output = self.vertices.clone();
+        for edge in window.edges() {
+            let input = std::mem::take(&mut output);
+            for (i, &current) in input.iter().enumerate() {
+                let previous = input[(i + input.len() - 1) % input.len()];
+                let crossing = Segment::new(previous, current).line_intersection(&edge);
+                match (inside(&edge, previous), inside(&edge, current)) {
+                    (true, true) => output.push(current),
+                    (true, false) => output.extend(crossing),
+                    (false, true) => {
+                        output.extend(crossing);
+                        output.push(current);
+                    }
+                    (false, false) => {}
+                }
+            }
+            if output.is_empty() {
+                return Ok(None);
+            }
+        }
+        output.dedup();
+        if output.len() > 1 && output.first() == output.last() {
+            output.pop();
+        }
+        match Polygon::new(output) {
+            Ok(clipped) if clipped.area() > 0.0 => Ok(Some(clipped)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The smallest convex polygon holding every point, counter-clockwise and
+/// without collinear vertices (Andrew's monotone chain); `None` when the
+/// points are all on one line. Non-finite points are ignored.
+pub fn convex_hull(points: &[Point<f64>]) -> Option<Polygon> {
+    let mut points: Vec<Point<f64>> = points
+        .iter()
+        .copied()
+        .filter(|p| p.x.is_finite() && p.y.is_finite())
+        .collect();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return None;
+    }
+
+    // the lower hull left to right, then the upper hull back again; a point
+    // that does not make a left turn is popped, which drops collinear ones
+    let mut hull: Vec<Point<f64>> = Vec::with_capacity(2 * points.len());
+    fn push(hull: &mut Vec<Point<f64>>, floor: usize, p: Point<f64>) {
+        while let [.., a, b] = hull[floor..] {
+            if cross(a, b, p) > 0.0 {
+                break;
+            }
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    for &p in &points {
+        push(&mut hull, 0, p);
+    }
+    let lower = hull.len() - 1;
+    for &p in points.iter().rev().skip(1) {
+        push(&mut hull, lower, p);
+    }
+    // the last point is the first one again
+    hull.pop();
+    Polygon::new(hull).ok().filter(|hull| hull.area() > 0.0)
+}
+
+impl From<Rectangle<f64>> for Polygon {
+    /// The corners in counter-clockwise order.
+    fn from(rect: Rectangle<f64>) -> Polygon {
+        Polygon {
+            vertices: vec![
+                rect.top_left(),
+                rect.top_right(),
+                rect.bottom_right(),
+                rect.bottom_left(),
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Polygon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, vertex) in self.vertices.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            vertex.fmt(f)?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(f64, f64)]) -> Polygon {
+        Polygon::new(points.iter().map(|&p| Point::from(p)).collect()).unwrap()
+    }
+
+    fn square() -> Polygon {
+        polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)])
+    }
+
+    #[test]
+    fn shoelace_area_and_centroid() {
+        let s = square();
+        assert_eq!(s.signed_area(), 16.0);
+        assert_eq!(s.perimeter(), 16.0);
+        assert_eq!(s.centroid(), Some(Point::new(2.0, 2.0)));
+
+        let l_shape = polygon(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+        ]);
+        assert_eq!(l_shape.area(), 3.0);
+        let c = l_shape.centroid().unwrap();
+        assert!((c.x - 5.0 / 6.0).abs() < 1e-12 && (c.y - 5.0 / 6.0).abs() < 1e-12);
+        assert!(!l_shape.is_convex());
+
+        let reversed = polygon(&[(0.0, 4.0), (4.0, 4.0), (4.0, 0.0), (0.0, 0.0)]);
+        assert_eq!(reversed.signed_area(), -16.0);
+        assert_eq!(reversed.orientation(), Orientation::Clockwise);
+        let flat = polygon(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)]);
+        assert_eq!(flat.centroid(), None);
+    }
+
+    #[test]
+    fn invalid_polygons() {
+        assert_eq!(
+            Polygon::new(vec![Point::origin(); 2]),
+            Err(PolygonError::TooFewVertices(2))
+        );
+        assert_eq!(
+            Polygon::new(vec![
+                Point::origin(),
+                Point::new(f64::NAN, 1.0),
+                Point::new(1.0, 0.0)
+            ]),
+            Err(PolygonError::NonFinite { index: 1 })
+        );
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let s = square();
+        assert!(s.contains(Point::new(1.0, 3.0)));
+        assert!(s.contains(Point::new(4.0, 2.0)));
+        assert!(s.contains(Point::new(0.0, 0.0)));
+        assert!(!s.contains(Point::new(5.0, 2.0)));
+        // the ray passes through a vertex
+        let diamond = polygon(&[(2.0, 0.0), (4.0, 2.0), (2.0, 4.0), (0.0, 2.0)]);
+        assert!(diamond.contains(Point::new(1.0, 2.0)));
+        assert!(!diamond.contains(Point::new(-1.0, 2.0)));
+        assert!(!diamond.contains(Point::new(0.5, 0.5)));
+    }
+
+    #[test]
+    fn convex_hull_drops_inner_and_collinear_points() {
+        let points: Vec<Point<f64>> = [
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (4.0, 0.0),
+            (1.0, 1.0),
+            (4.0, 4.0),
+            (2.0, 3.0),
+            (0.0, 4.0),
+            (4.0, 0.0),
+        ]
+        .into_iter()
+        .map(Point::from)
+        .collect();
+        assert_eq!(convex_hull(&points), Some(square()));
+        let line = [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)].map(Point::from);
+        assert_eq!(convex_hull(&line), None);
+    }
+
+    #[test]
+    fn clipping_against_a_convex_window() {
+        let window = Polygon::from(Rectangle::new(Point::new(2.0, 2.0), Point::new(6.0, 6.0)));
+        let clipped = square().clip(&window).unwrap().unwrap();
+        assert_eq!(clipped.area(), 4.0);
+        assert_eq!(
+            clipped.bounding_box(),
+            Rectangle::new(Point::new(2.0, 2.0), Point::new(4.0, 4.0))
+        );
+
+        let far = Polygon::from(Rectangle::new(Point::new(9.0, 9.0), Point::new(10.0, 10.0)));
+        assert_eq!(square().clip(&far), Ok(None));
+
+        let l_shape = polygon(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+        ]);
+        assert_eq!(square().clip(&l_shape), Err(PolygonError::NotConvex));
+
+        // every other vertex of a regular pentagon: every turn is to the left
+        let pentagon: Vec<Point<f64>> = (0..5)
+            .map(|i| {
+                let angle = f64::from(i) * 2.0 * std::f64::consts::PI / 5.0;
+                Point::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        let pentagram = Polygon::new([0, 2, 4, 1, 3].map(|i| pentagon[i]).to_vec()).unwrap();
+        assert!(!pentagram.is_convex());
+        assert_eq!(square().clip(&pentagram), Err(PolygonError::NotConvex));
+        assert!(Polygon::new(pentagon).unwrap().is_convex());
+        // a concave subject is fine
+        assert_eq!(l_shape.clip(&square()).unwrap().unwrap().area(), 3.0);
+    }
+}
@@ -0,0 +1,495 @@
+/* Quadtree */
+// a spatial index over points: each node covers a rectangle, and a leaf
+// holding more than `LEAF_CAPACITY` entries splits into the four quadrants
+// of its rectangle, so a query only visits the nodes its area touches
+
+// points on a split line belong to the top or left quadrant; several
+// entries may share a point, and `MAX_DEPTH` stops the splitting when they
+// cannot be separated
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::coord::Coord;
+use crate::point::Point;
+use crate::rect::Rectangle;
+
+pub const LEAF_CAPACITY: usize = 8;
+pub const MAX_DEPTH: usize = 24;
+
+#[derive(Debug, Clone)]
+pub struct QuadTree<T, C = f64> {
+    root: Node<T, C>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T, C> {
+    bounds: Rectangle<C>,
+    depth: usize,
+    kind: Kind<T, C>,
+}
+
+#[derive(Debug, Clone)]
+enum Kind<T, C> {
+    Leaf(Vec<(Point<C>, T)>),
+    // top left, top right, bottom left, bottom right
+    Branch(Box<[Node<T, C>; 4]>),
+}
+
+impl<T, C: Coord> QuadTree<T, C> {
+    /// An empty tree accepting points inside `bounds`.
+    pub fn new(bounds: Rectangle<C>) -> QuadTree<T, C> {
+        QuadTree {
+            root: Node::leaf(bounds, 0),
+            len: 0,
+        }
+    }
+
+    /// Builds the tree top down from all entries at once, which is faster
+    /// than inserting them one by one; the bounds are the entries' bounding
+    /// box, `None` when there are no entries.
+    pub fn bulk_load(entries: Vec<(Point<C>, T)>) -> Option<QuadTree<T, C>> {
+        let bounds = Rectangle::bounding_box(entries.iter().map(|&(point, _)| point))?;
+        Some(QuadTree {
+            len: entries.len(),
+            root: Node::build(bounds, 0, entries),
+        })
+    }
+
+    pub fn bounds(&self) -> Rectangle<C> {
+        self.root.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of levels below the root.
+    pub fn depth(&self) -> usize {
+        self.root.depth()
+    }
+
+    /// Hands the value back when `point` is outside the tree's bounds.
+    pub fn insert(&mut self, point: Point<C>, value: T) -> Result<(), T> {
+        if !self.root.bounds.contains(point) {
+            return Err(value);
+        }
+        self.root.insert(point, value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes one entry at exactly `point`, merging leaves that get sparse.
+    pub fn remove(&mut self, point: Point<C>) -> Option<T> {
+        let value = self.root.remove(point)?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, C> {
+        Iter {
+            stack: vec![&self.root],
+            entries: [].iter(),
+        }
+    }
+
+    /// Every entry inside `area`, edges included.
+    pub fn range(&self, area: &Rectangle<C>) -> Vec<(Point<C>, &T)> {
+        let mut found = Vec::new();
+        self.root.range(area, &mut found);
+        found
+    }
+
+    /// Every entry at most `radius` away from `center`.
+    pub fn within_radius(&self, center: Point<C>, radius: f64) -> Vec<(Point<C>, &T)> {
+        let mut found = Vec::new();
+        self.root.within(center, radius * radius, &mut found);
+        found
+    }
+
+    /// The `k` entries nearest to `target`, nearest first. Nodes are visited
+    /// best first, in order of their distance from `target`, so the search
+    /// stops as soon as `k` entries are closer than every node left.
+    pub fn nearest(&self, target: Point<C>, k: usize) -> Vec<(Point<C>, &T)> {
+        let mut found = Vec::with_capacity(k);
+        let mut queue = BinaryHeap::new();
+        queue.push(Queued {
+            distance: self.root.bounds_distance(target),
+            item: Candidate::Node(&self.root),
+        });
+        while found.len() < k {
+            let Some(Queued { item, .. }) = queue.pop() else {
+                break;
+            };
+            match item {
+                Candidate::Entry(point, value) => found.push((point, value)),
+                Candidate::Node(node) => match &node.kind {
+                    Kind::Leaf(entries) => {
+                        queue.extend(entries.iter().map(|(point, value)| Queued {
+                            distance: point.distance_squared(target),
+                            item: Candidate::Entry(*point, value),
+                        }))
+                    }
+                    Kind::Branch(children) => queue.extend(children.iter().map(|child| Queued {
+                        distance: child.bounds_distance(target),
+                        item: Candidate::Node(child),
+                    })),
+                },
+            }
+        }
+        found
+    }
+}
+
+impl<T, C: Coord> Node<T, C> {
+    fn leaf(bounds: Rectangle<C>, depth: usize) -> Node<T, C> {
+        Node {
+            bounds,
+            depth,
+            kind: Kind::Leaf(Vec::new()),
+        }
+    }
+
+    fn build(bounds: Rectangle<C>, depth: usize, entries: Vec<(Point<C>, T)>) -> Node<T, C> {
+        let quadrants = match bounds.quadrants() {
+            Some(quadrants) if entries.len() > LEAF_CAPACITY && depth < MAX_DEPTH => quadrants,
+            _ => {
+                return Node {
+                    bounds,
+                    depth,
+                    kind: Kind::Leaf(entries),
+                }
+            }
+        };
+        let center = quadrants[0].bottom_right();
+        let mut parts: [Vec<(Point<C>, T)>; 4] = Default::default();
+        for (point, value) in entries {
+            parts[quadrant(center, point)].push((point, value));
+        }
+        let mut parts = parts.into_iter();
+        let children =
+            quadrants.map(|b| Node::build(b, depth + 1, parts.next().unwrap_or_default()));
+        Node {
+            bounds,
+            depth,
+            kind: Kind::Branch(Box::new(children)),
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match &self.kind {
+            Kind::Leaf(_) => 0,
+            Kind::Branch(children) => 1 + children.iter().map(Node::depth).max().unwrap_or(0),
+        }
+    }
+
+    fn insert(&mut self, point: Point<C>, value: T) {
+        match &mut self.kind {
+            Kind::Branch(children) => {
+                let center = children[0].bounds.bottom_right();
+                children[quadrant(center, point)].insert(point, value);
+            }
+            Kind::Leaf(entries) => {
+                entries.push((point, value));
+                if entries.len() > LEAF_CAPACITY {
+                    self.split();
+                }
+            }
+        }
+    }
+
+    fn split(&mut self) {
+        if self.depth >= MAX_DEPTH {
+            return;
+        }
+        let Some(quadrants) = self.bounds.quadrants() else {
+            return;
+        };
+        let Kind::Leaf(entries) = std::mem::replace(&mut self.kind, Kind::Leaf(Vec::new())) else {
+            return;
+        };
+        let center = quadrants[0].bottom_right();
+        let mut children = quadrants.map(|b| Node::leaf(b, self.depth + 1));
+        for (point, value) in entries {
+            children[quadrant(center, point)].insert(point, value);
+        }
+        self.kind = Kind::Branch(Box::new(children));
+    }
+
+    fn remove(&mut self, point: Point<C>) -> Option<T> {
+        match &mut self.kind {
+            Kind::Leaf(entries) => {
+                let index = entries.iter().position(|(p, _)| *p == point)?;
+                Some(entries.swap_remove(index).1)
+            }
+            Kind::Branch(children) => {
+                let center = children[0].bounds.bottom_right();
+                let value = children[quadrant(center, point)].remove(point)?;
+                self.merge();
+                Some(value)
+            }
+        }
+    }
+
+    // turns a branch back into a leaf once its leaves fit in one
+    fn merge(&mut self) {
+        let Kind::Branch(children) = &mut self.kind else {
+            return;
+        };
+        let mut total = 0;
+        for child in children.iter() {
+            match &child.kind {
+                Kind::Leaf(entries) => total += entries.len(),
+                Kind::Branch(_) => return,
+            }
+        }
+        if total > LEAF_CAPACITY {
+            return;
+        }
+        let mut merged = Vec::with_capacity(total);
+        for child in children.iter_mut() {
+            if let Kind::Leaf(entries) = &mut child.kind {
+                merged.append(entries);
+            }
+        }
+        self.kind = Kind::Leaf(merged);
+    }
+
+    fn range<'a>(&'a self, area: &Rectangle<C>, found: &mut Vec<(Point<C>, &'a T)>) {
+        if !self.bounds.intersects(area) {
+            return;
+        }
+        match &self.kind {
+            Kind::Leaf(entries) => found.extend(
+                entries
+                    .iter()
+                    .filter(|(point, _)| area.contains(*point))
+                    .map(|(point, value)| (*point, value)),
+            ),
+            Kind::Branch(children) => {
+                for child in children.iter() {
+                    child.range(area, found);
+                }
+            }
+        }
+    }
+
+    fn within<'a>(
+        &'a self,
+        center: Point<C>,
+        radius_squared: f64,
+        found: &mut Vec<(Point<C>, &'a T)>,
+    ) {
+        if self.bounds_distance(center) > radius_squared {
+            return;
+        }
+        match &self.kind {
+            Kind::Leaf(entries) => found.extend(
+                entries
+                    .iter()
+                    .filter(|(point, _)| point.distance_squared(center) <= radius_squared)
+                    .map(|(point, value)| (*point, value)),
+            ),
+            Kind::Branch(children) => {
+                for child in children.iter() {
+                    child.within(center, radius_squared, found);
+                }
+            }
+        }
+    }
+
+    /// The squared distance from `point` to the nearest point of the node.
+    fn bounds_distance(&self, point: Point<C>) -> f64 {
+        let gap = |p: C, low: C, high: C| {
+            let (p, low, high) = (p.to_f64(), low.to_f64(), high.to_f64());
+            (low - p).max(p - high).max(0.0)
+        };
+        let (top_left, bottom_right) = (self.bounds.top_left(), self.bounds.bottom_right());
+        let dx = gap(point.x, top_left.x, bottom_right.x);
+        let dy = gap(point.y, top_left.y, bottom_right.y);
+        dx * dx + dy * dy
+    }
+}
+
+/// The index of the quadrant around `center` that holds `point`.
+fn quadrant<C: Coord>(center: Point<C>, point: Point<C>) -> usize {
+    usize::from(point.x > center.x) + 2 * usize::from(point.y > center.y)
+}
+
+enum Candidate<'a, T, C> {
+    Node(&'a Node<T, C>),
+    Entry(Point<C>, &'a T),
+}
+
+// a min-heap entry for `nearest`, ordered only by distance
+struct Queued<'a, T, C> {
+    distance: f64,
+    item: Candidate<'a, T, C>,
+}
+
+impl<T, C> PartialEq for Queued<'_, T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T, C> Eq for Queued<'_, T, C> {}
+
+impl<T, C> PartialOrd for Queued<'_, T, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, C> Ord for Queued<'_, T, C> {
+    // reversed, so that `BinaryHeap` pops the nearest first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+/// Every entry, leaf by leaf.
+pub struct Iter<'a, T, C> {
+    stack: Vec<&'a Node<T, C>>,
+    entries: std::slice::Iter<'a, (Point<C>, T)>,
+}
+
+impl<'a, T, C: Coord> Iterator for Iter<'a, T, C> {
+    type Item = (Point<C>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((point, value)) = self.entries.next() {
+                return Some((*point, value));
+            }
+            match &self.stack.pop()?.kind {
+                Kind::Leaf(entries) => self.entries = entries.iter(),
+                Kind::Branch(children) => self.stack.extend(children.iter().rev()),
+            }
+        }
+    }
+}
+
+impl<'a, T, C: Coord> IntoIterator for &'a QuadTree<T, C> {
+    type Item = (Point<C>, &'a T);
+    type IntoIter = Iter<'a, T, C>;
+
+    fn into_iter(self) -> Iter<'a, T, C> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a small deterministic generator, enough to scatter points
+    fn scatter(n: usize, size: i32) -> Vec<(Point<i32>, usize)> {
+        let mut state: u32 = 12345;
+        let mut next = move || {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 8) as i32 % size
+        };
+        (0..n).map(|i| (Point::new(next(), next()), i)).collect()
+    }
+
+    fn tree(entries: &[(Point<i32>, usize)], size: i32) -> QuadTree<usize, i32> {
+        let mut tree = QuadTree::new(Rectangle::new(Point::origin(), Point::new(size, size)));
+        for &(point, i) in entries {
+            assert_eq!(tree.insert(point, i), Ok(()));
+        }
+        tree
+    }
+
+    fn sorted(mut values: Vec<usize>) -> Vec<usize> {
+        values.sort_unstable();
+        values
+    }
+
+    #[test]
+    fn range_matches_a_linear_scan() {
+        let entries = scatter(2000, 1000);
+        let inserted = tree(&entries, 1000);
+        let loaded = QuadTree::bulk_load(entries.clone()).unwrap();
+        assert_eq!((inserted.len(), loaded.len()), (2000, 2000));
+        assert!(inserted.depth() > 1);
+
+        let area = Rectangle::new(Point::new(100, 250), Point::new(400, 300));
+        let expected: Vec<usize> = entries
+            .iter()
+            .filter(|(p, _)| area.contains(*p))
+            .map(|&(_, i)| i)
+            .collect();
+        for tree in [&inserted, &loaded] {
+            let found = tree.range(&area).into_iter().map(|(_, &i)| i).collect();
+            assert_eq!(sorted(found), sorted(expected.clone()));
+        }
+        assert_eq!(
+            sorted(loaded.iter().map(|(_, &i)| i).collect()),
+            (0..2000).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn nearest_and_radius_match_a_linear_scan() {
+        let entries = scatter(1500, 500);
+        let tree = tree(&entries, 500);
+        let target = Point::new(250, 120);
+
+        let mut by_distance: Vec<f64> = entries
+            .iter()
+            .map(|(p, _)| p.distance_squared(target))
+            .collect();
+        by_distance.sort_by(f64::total_cmp);
+        let nearest: Vec<f64> = tree
+            .nearest(target, 10)
+            .iter()
+            .map(|(p, _)| p.distance_squared(target))
+            .collect();
+        assert_eq!(nearest, by_distance[..10]);
+        assert_eq!(tree.nearest(target, 5000).len(), 1500);
+
+        let expected = entries
+            .iter()
+            .filter(|(p, _)| p.distance(target) <= 30.0)
+            .map(|&(_, i)| i)
+            .collect();
+        let found = tree
+            .within_radius(target, 30.0)
+            .into_iter()
+            .map(|(_, &i)| i)
+            .collect();
+        assert_eq!(sorted(found), sorted(expected));
+    }
+
+    #[test]
+    fn insert_outside_and_remove() {
+        let entries = scatter(300, 100);
+        let mut tree = tree(&entries, 100);
+        assert_eq!(tree.insert(Point::new(101, 5), 999), Err(999));
+
+        for &(point, _) in &entries {
+            assert!(tree.remove(point).is_some());
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.remove(Point::new(5, 5)), None);
+        // everything merged back into the root leaf
+        assert_eq!(tree.depth(), 0);
+    }
+
+    #[test]
+    fn many_entries_at_one_point() {
+        let mut tree = QuadTree::new(Rectangle::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0)));
+        for i in 0..100 {
+            tree.insert(Point::new(0.5, 0.5), i).unwrap();
+        }
+        assert_eq!(tree.depth(), MAX_DEPTH);
+        assert_eq!(tree.within_radius(Point::new(0.5, 0.5), 0.0).len(), 100);
+        assert_eq!(QuadTree::<u8>::bulk_load(Vec::new()).map(|t| t.len()), None);
+    }
+}
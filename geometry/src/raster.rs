@@ -0,0 +1,490 @@
+/* Raster canvas */
+// a grid of pixels to draw shapes on, exported as ASCII art or PPM images;
+// the pixel type is up to the caller, anything `Copy` for drawing and
+// anything convertible to `[u8; 3]` for PPM
+
+// pixel (x, y) is the unit square from (x, y) to (x + 1, y + 1), so a
+// filled rectangle covers `width * height` pixels: its right and bottom
+// edges are not drawn. drawing outside the canvas is silently clipped
+
+use std::io::{self, Write};
+
+use crate::point::Point;
+use crate::rect::Rectangle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PpmFormat {
+    /// `P3`: decimal numbers, readable in a text editor.
+    Plain,
+    /// `P6`: one byte per channel.
+    Binary,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas<P> {
+    width: usize,
+    height: usize,
+    pixels: Vec<P>,
+}
+
+impl<P: Copy> Canvas<P> {
+    pub fn new(width: usize, height: usize, background: P) -> Canvas<P> {
+        Canvas {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let x = usize::try_from(x).ok().filter(|&x| x < self.width)?;
+        let y = usize::try_from(y).ok().filter(|&y| y < self.height)?;
+        Some(y * self.width + x)
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<P> {
+        self.index(x, y).map(|i| self.pixels[i])
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, pixel: P) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = pixel;
+        }
+    }
+
+    pub fn clear(&mut self, pixel: P) {
+        self.pixels.fill(pixel);
+    }
+
+    /// The rows of pixels, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[P]> {
+        self.pixels.chunks(self.width.max(1))
+    }
+
+    // the part of a rectangle on the canvas, as half-open pixel ranges
+    fn clipped(&self, rect: &Rectangle<i32>) -> (std::ops::Range<i32>, std::ops::Range<i32>) {
+        let clamp = |v: i32, max: usize| v.clamp(0, i32::try_from(max).unwrap_or(i32::MAX));
+        let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+        (
+            clamp(top_left.x, self.width)..clamp(bottom_right.x, self.width),
+            clamp(top_left.y, self.height)..clamp(bottom_right.y, self.height),
+        )
+    }
+
+    pub fn fill_rect(&mut self, rect: &Rectangle<i32>, pixel: P) {
+        let (xs, ys) = self.clipped(rect);
+        for y in ys {
+            for x in xs.clone() {
+                self.set(x, y, pixel);
+            }
+        }
+    }
+
+    /// The one pixel wide border just inside the filled area.
+    pub fn stroke_rect(&mut self, rect: &Rectangle<i32>, pixel: P) {
+        let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+        if rect.is_empty() {
+            return;
+        }
+        let (right, bottom) = (bottom_right.x - 1, bottom_right.y - 1);
+        let corners = [
+            top_left,
+            Point::new(right, top_left.y),
+            Point::new(right, bottom),
+            Point::new(top_left.x, bottom),
+        ];
+        for i in 0..4 {
+            self.line(corners[i], corners[(i + 1) % 4], pixel);
+        }
+    }
+
+    // the canvas size in the wide coordinates used while drawing
+    fn wide_size(&self) -> (i64, i64) {
+        let wide = |n: usize| i64::try_from(n).unwrap_or(i64::MAX);
+        (wide(self.width), wide(self.height))
+    }
+
+    /// Bresenham's line, both end points included.
+    pub fn line(&mut self, from: Point<i32>, to: Point<i32>, pixel: P) {
+        // i64, so that the differences cannot overflow
+        let (x0, y0) = (i64::from(from.x), i64::from(from.y));
+        let (dx, dy) = (i64::from(to.x) - x0, i64::from(to.y) - y0);
+        let (width, height) = self.wide_size();
+        // one pixel per step along the longer axis, the other coordinate
+        // rounded half up; the line is clipped to the steps that land on
+        // the canvas along that axis, so that it costs at most one step
+        // per row or column whatever its length
+        let x_major = dx.abs() >= dy.abs();
+        let (start, major, minor, limit) = if x_major {
+            (x0, dx, dy, width)
+        } else {
+            (y0, dy, dx, height)
+        };
+        let steps = major.abs();
+        let (first, last) = if major >= 0 {
+            (-start, limit - 1 - start)
+        } else {
+            (start - (limit - 1), start)
+        };
+        for k in first.max(0)..=last.min(steps) {
+            // i128, as the product can exceed an i64 for the longest lines
+            let offset = match steps {
+                0 => 0,
+                _ => {
+                    let (k, steps) = (i128::from(k), i128::from(steps));
+                    (2 * i128::from(minor.abs()) * k + steps) / (2 * steps)
+                }
+            };
+            let along = start + major.signum() * k;
+            let across = (if x_major { y0 } else { x0 }) + minor.signum() * offset as i64;
+            if x_major {
+                self.set_wide(along, across, pixel);
+            } else {
+                self.set_wide(across, along, pixel);
+            }
+        }
+    }
+
+    fn set_wide(&mut self, x: i64, y: i64, pixel: P) {
+        if let (Ok(x), Ok(y)) = (i32::try_from(x), i32::try_from(y)) {
+            self.set(x, y, pixel);
+        }
+    }
+
+    /// Every pixel whose position is at most `radius` from `center`.
+    pub fn fill_circle(&mut self, center: Point<i32>, radius: i32, pixel: P) {
+        if radius < 0 {
+            return;
+        }
+        let r = i64::from(radius);
+        let (cx, cy) = (i64::from(center.x), i64::from(center.y));
+        let (width, height) = self.wide_size();
+        // one span per row, and only the rows and columns on the canvas
+        for dy in (-r).max(-cy)..=r.min(height - 1 - cy) {
+            let half = (r * r - dy * dy).isqrt();
+            for dx in (-half).max(-cx)..=half.min(width - 1 - cx) {
+                self.set_wide(cx + dx, cy + dy, pixel);
+            }
+        }
+    }
+
+    /// The midpoint circle algorithm, plotting all eight octants at once.
+    pub fn stroke_circle(&mut self, center: Point<i32>, radius: i32, pixel: P) {
+        if radius < 0 {
+            return;
+        }
+        let r = i64::from(radius);
+        let (cx, cy) = (i64::from(center.x), i64::from(center.y));
+        let (width, height) = self.wide_size();
+        // the algorithm walks y up from 0, keeping x while the midpoint
+        // (x - 1/2, y) is inside the circle, i.e. x is the largest with
+        // x(x - 1) < r^2 - y^2. that can be solved for any y directly, so
+        // only the offsets that reach a row or column on the canvas are
+        // visited, not the whole octant
+        let octant_x = |y: i64| match r * r - y * y {
+            _ if y == 0 => Some(r),
+            rest if rest <= 0 => None,
+            rest => Some(((4 * rest as u64).isqrt() as i64 + 1) / 2),
+        };
+        let rows = (0..height).map(|row| (row - cy).abs());
+        let columns = (0..width).map(|column| (column - cx).abs());
+        let mut offsets: Vec<i64> = rows.chain(columns).filter(|&y| y <= r).collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        for y in offsets {
+            let Some(x) = octant_x(y).filter(|&x| x >= y) else {
+                continue;
+            };
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y)] {
+                self.set_wide(cx + dx, cy + dy, pixel);
+                self.set_wide(cx - dx, cy - dy, pixel);
+            }
+        }
+    }
+
+    /// Every pixel inside the triangle or on its edges, in either winding.
+    pub fn fill_triangle(&mut self, a: Point<i32>, b: Point<i32>, c: Point<i32>, pixel: P) {
+        let Some(bounds) = Rectangle::bounding_box([a, b, c]) else {
+            return;
+        };
+        // the vertices are pixels, so the bounding box includes its far edges
+        let far = bounds.bottom_right();
+        let (xs, ys) = self.clipped(&Rectangle::new(
+            bounds.top_left(),
+            far.translate(1, 1).unwrap_or(far),
+        ));
+        // each difference takes 33 bits, so their products need more than 64
+        let wide = |p: Point<i32>| (i128::from(p.x), i128::from(p.y));
+        let edge = |(x0, y0): (i128, i128), (x1, y1): (i128, i128), (x, y): (i128, i128)| {
+            (x1 - x0) * (y - y0) - (y1 - y0) * (x - x0)
+        };
+        let (a, b, c) = (wide(a), wide(b), wide(c));
+        let area = edge(a, b, c);
+        for y in ys {
+            for x in xs.clone() {
+                let p = (i128::from(x), i128::from(y));
+                let weights = [edge(a, b, p), edge(b, c, p), edge(c, a, p)];
+                // a flat triangle, with zero area, is just its edges
+                let inside = if area >= 0 {
+                    weights.iter().all(|&w| w >= 0)
+                } else {
+                    weights.iter().all(|&w| w <= 0)
+                };
+                if inside {
+                    self.set(x, y, pixel);
+                }
+            }
+        }
+    }
+
+    pub fn stroke_triangle(&mut self, a: Point<i32>, b: Point<i32>, c: Point<i32>, pixel: P) {
+        self.line(a, b, pixel);
+        self.line(b, c, pixel);
+        self.line(c, a, pixel);
+    }
+
+    /// One character per pixel, one line per row.
+    pub fn to_ascii(&self, glyph: impl Fn(P) -> char) -> String {
+        let mut art = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            art.extend(row.iter().map(|&p| glyph(p)));
+            art.push('\n');
+        }
+        art
+    }
+}
+
+impl<P: Copy + Into<[u8; 3]>> Canvas<P> {
+    /// Writes the canvas as a PPM image with a maximum channel value of 255.
+    pub fn write_ppm(&self, out: &mut dyn Write, format: PpmFormat) -> io::Result<()> {
+        let magic = match format {
+            PpmFormat::Plain => "P3",
+            PpmFormat::Binary => "P6",
+        };
+        writeln!(out, "{}\n{} {}\n255", magic, self.width, self.height)?;
+        match format {
+            PpmFormat::Binary => {
+                let bytes: Vec<u8> = self.pixels.iter().flat_map(|&p| p.into()).collect();
+                out.write_all(&bytes)
+            }
+            // plain lines should stay within 70 characters
+            PpmFormat::Plain => {
+                for row in self.rows() {
+                    let mut line = String::new();
+                    for &pixel in row {
+                        let [r, g, b] = pixel.into();
+                        let sample = format!("{} {} {}", r, g, b);
+                        if !line.is_empty() && line.len() + 1 + sample.len() > 70 {
+                            writeln!(out, "{}", line)?;
+                            line.clear();
+                        }
+                        if !line.is_empty() {
+                            line.push(' ');
+                        }
+                        line.push_str(&sample);
+                    }
+                    writeln!(out, "{}", line)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ascii(canvas: &Canvas<bool>) -> String {
+        canvas.to_ascii(|on| if on { '#' } else { '.' })
+    }
+
+    #[test]
+    fn rectangles_cover_their_area() {
+        let mut canvas = Canvas::new(6, 4, false);
+        let rect = Rectangle::new(Point::new(4, 3), Point::new(1, 1));
+        canvas.fill_rect(&rect, true);
+        assert_eq!(ascii(&canvas), "......\n.###..\n.###..\n......\n");
+        let filled = canvas.rows().flatten().filter(|&&on| on).count();
+        assert_eq!(Some(filled as i32), rect.area());
+
+        canvas.clear(false);
+        canvas.stroke_rect(&Rectangle::new(Point::new(-2, 0), Point::new(4, 4)), true);
+        assert_eq!(ascii(&canvas), "####..\n...#..\n...#..\n####..\n");
+    }
+
+    #[test]
+    fn lines_and_circles() {
+        let mut canvas = Canvas::new(5, 3, false);
+        canvas.line(Point::new(0, 0), Point::new(4, 2), true);
+        assert_eq!(ascii(&canvas), "#....\n.##..\n...##\n");
+
+        let mut canvas = Canvas::new(5, 5, false);
+        canvas.fill_circle(Point::new(2, 2), 2, true);
+        assert_eq!(ascii(&canvas), "..#..\n.###.\n#####\n.###.\n..#..\n");
+        canvas.clear(false);
+        canvas.stroke_circle(Point::new(2, 2), 2, true);
+        assert_eq!(ascii(&canvas), ".###.\n#...#\n#...#\n#...#\n.###.\n");
+        // far outside the canvas, nothing is drawn and nothing overflows
+        canvas.line(Point::new(i32::MIN, 0), Point::new(i32::MIN + 3, 9), false);
+        assert_eq!(canvas.get(-1, 0), None);
+    }
+
+    // the unclipped algorithms, walking every step of the shape
+    fn reference_line(canvas: &mut Canvas<bool>, from: Point<i32>, to: Point<i32>) {
+        let (mut x, mut y) = (i64::from(from.x), i64::from(from.y));
+        let (x1, y1) = (i64::from(to.x), i64::from(to.y));
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+        let mut error = dx + dy;
+        loop {
+            canvas.set_wide(x, y, true);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
+    fn reference_circle(canvas: &mut Canvas<bool>, center: Point<i32>, radius: i32) {
+        let (cx, cy) = (i64::from(center.x), i64::from(center.y));
+        let (mut x, mut y) = (i64::from(radius), 0);
+        let mut error = 1 - x;
+        while x >= y {
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y)] {
+                canvas.set_wide(cx + dx, cy + dy, true);
+                canvas.set_wide(cx - dx, cy - dy, true);
+            }
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    #[test]
+    fn clipping_draws_the_same_pixels() {
+        let (mut clipped, mut reference) = (Canvas::new(7, 5, false), Canvas::new(7, 5, false));
+        let range = -4..11;
+        for (x0, y0) in range
+            .clone()
+            .flat_map(|x| range.clone().map(move |y| (x, y)))
+        {
+            for (x1, y1) in range
+                .clone()
+                .flat_map(|x| range.clone().map(move |y| (x, y)))
+            {
+                clipped.clear(false);
+                reference.clear(false);
+                clipped.line(Point::new(x0, y0), Point::new(x1, y1), true);
+                reference_line(&mut reference, Point::new(x0, y0), Point::new(x1, y1));
+                assert_eq!(clipped, reference, "({}, {}) to ({}, {})", x0, y0, x1, y1);
+            }
+        }
+
+        let mut clipped = Canvas::new(40, 30, false);
+        let mut reference = clipped.clone();
+        for radius in -1..60 {
+            for center in [Point::new(20, 15), Point::new(-7, 33), Point::new(45, 3)] {
+                clipped.clear(false);
+                reference.clear(false);
+                clipped.stroke_circle(center, radius, true);
+                reference_circle(&mut reference, center, radius);
+                assert_eq!(clipped, reference, "{} around {}", radius, center);
+            }
+        }
+    }
+
+    #[test]
+    fn huge_shapes_only_visit_the_canvas() {
+        let mut canvas = Canvas::new(4, 3, false);
+        canvas.fill_circle(Point::new(1, 1), i32::MAX, true);
+        assert!(canvas.rows().flatten().all(|&on| on));
+
+        canvas.clear(false);
+        canvas.stroke_circle(Point::new(1, 1), i32::MAX, true);
+        assert!(canvas.rows().flatten().all(|&on| !on));
+        canvas.stroke_circle(Point::new(i32::MIN + 1, 1), i32::MAX, true);
+        assert_eq!(ascii(&canvas), "#...\n#...\n#...\n");
+
+        canvas.clear(false);
+        canvas.line(Point::new(i32::MIN, 1), Point::new(i32::MAX, 1), true);
+        canvas.line(Point::new(2, i32::MAX), Point::new(2, i32::MIN), true);
+        assert_eq!(ascii(&canvas), "..#.\n####\n..#.\n");
+
+        let mut canvas = Canvas::new(3, 3, false);
+        let (min, max) = (i32::MIN, i32::MAX);
+        // the long edge runs along x + y = -1, just off the canvas
+        canvas.fill_triangle(
+            Point::new(min, min),
+            Point::new(max, min),
+            Point::new(min, max),
+            true,
+        );
+        assert!(canvas.rows().flatten().all(|&on| !on));
+        canvas.fill_triangle(
+            Point::new(min, min),
+            Point::new(max, min),
+            Point::new(max, max),
+            true,
+        );
+        assert_eq!(ascii(&canvas), "###\n.##\n..#\n");
+    }
+
+    #[test]
+    fn triangles_in_either_winding() {
+        let (a, b, c) = (Point::new(0, 0), Point::new(4, 0), Point::new(0, 4));
+        let mut clockwise = Canvas::new(5, 5, false);
+        clockwise.fill_triangle(a, b, c, true);
+        let mut counter = Canvas::new(5, 5, false);
+        counter.fill_triangle(a, c, b, true);
+        assert_eq!(clockwise, counter);
+        assert_eq!(ascii(&clockwise), "#####\n####.\n###..\n##...\n#....\n");
+
+        let mut outline = Canvas::new(5, 5, false);
+        outline.stroke_triangle(a, b, c, true);
+        assert_eq!(ascii(&outline), "#####\n#..#.\n#.#..\n##...\n#....\n");
+    }
+
+    #[test]
+    fn ppm_export() {
+        let mut canvas = Canvas::new(2, 1, [0, 0, 0]);
+        canvas.set(1, 0, [255, 128, 7]);
+        let mut binary = Vec::new();
+        canvas.write_ppm(&mut binary, PpmFormat::Binary).unwrap();
+        assert_eq!(binary, b"P6\n2 1\n255\n\x00\x00\x00\xff\x80\x07");
+
+        let mut plain = Vec::new();
+        canvas.write_ppm(&mut plain, PpmFormat::Plain).unwrap();
+        assert_eq!(
+            String::from_utf8(plain).unwrap(),
+            "P3\n2 1\n255\n0 0 0 255 128 7\n"
+        );
+
+        let wide = Canvas::new(10, 1, [255, 255, 255]);
+        let mut plain = Vec::new();
+        wide.write_ppm(&mut plain, PpmFormat::Plain).unwrap();
+        let text = String::from_utf8(plain).unwrap();
+        assert!(text.lines().all(|line| line.len() <= 70));
+        assert_eq!(text.split_whitespace().count(), 4 + 30);
+    }
+}
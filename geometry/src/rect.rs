@@ -0,0 +1,318 @@
+/* Axis-aligned rectangles */
+// a `Rectangle` is always normalised: whichever corners it is built from,
+// `top_left` holds the smallest coordinates, so width, height and area are
+// never negative
+
+// rectangles are closed: points on the edges are inside, and rectangles
+// that only share an edge intersect in a rectangle of zero width or height
+
+use std::fmt;
+
+use crate::coord::{max, min, Coord};
+use crate::point::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle<T> {
+    top_left: Point<T>,
+    bottom_right: Point<T>,
+}
+
+impl<T: Coord> Rectangle<T> {
+    /// The rectangle with opposite corners `a` and `b`, in any order.
+    pub fn new(a: Point<T>, b: Point<T>) -> Rectangle<T> {
+        Rectangle {
+            top_left: Point::new(min(a.x, b.x), min(a.y, b.y)),
+            bottom_right: Point::new(max(a.x, b.x), max(a.y, b.y)),
+        }
+    }
+
+    /// `None` when the far corner overflows; a negative size extends up or left.
+    pub fn from_size(corner: Point<T>, width: T, height: T) -> Option<Rectangle<T>> {
+        Some(Rectangle::new(corner, corner.translate(width, height)?))
+    }
+
+    pub fn square(corner: Point<T>, side: T) -> Option<Rectangle<T>> {
+        Rectangle::from_size(corner, side, side)
+    }
+
+    /// The smallest rectangle holding every point, `None` for no points.
+    pub fn bounding_box(points: impl IntoIterator<Item = Point<T>>) -> Option<Rectangle<T>> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Rectangle::new(first, first), |bounds, point| {
+            bounds.union(&Rectangle::new(point, point))
+        }))
+    }
+
+    pub fn top_left(&self) -> Point<T> {
+        self.top_left
+    }
+
+    pub fn bottom_right(&self) -> Point<T> {
+        self.bottom_right
+    }
+
+    pub fn top_right(&self) -> Point<T> {
+        Point::new(self.bottom_right.x, self.top_left.y)
+    }
+
+    pub fn bottom_left(&self) -> Point<T> {
+        Point::new(self.top_left.x, self.bottom_right.y)
+    }
+
+    /// `None` when the width does not fit `T`, e.g. from `i32::MIN` to `i32::MAX`.
+    pub fn width(&self) -> Option<T> {
+        self.bottom_right.x.checked_sub(self.top_left.x)
+    }
+
+    pub fn height(&self) -> Option<T> {
+        self.bottom_right.y.checked_sub(self.top_left.y)
+    }
+
+    pub fn area(&self) -> Option<T> {
+        self.width()?.checked_mul(self.height()?)
+    }
+
+    pub fn perimeter(&self) -> Option<T> {
+        let half = self.width()?.checked_add(self.height()?)?;
+        half.checked_add(half)
+    }
+
+    /// Rounded towards the top left for integers.
+    pub fn center(&self) -> Option<Point<T>> {
+        self.top_left
+            .translate(self.width()?.half(), self.height()?.half())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.top_left.x == self.bottom_right.x || self.top_left.y == self.bottom_right.y
+    }
+
+    pub fn contains(&self, point: Point<T>) -> bool {
+        self.top_left.x <= point.x
+            && point.x <= self.bottom_right.x
+            && self.top_left.y <= point.y
+            && point.y <= self.bottom_right.y
+    }
+
+    pub fn contains_rect(&self, other: &Rectangle<T>) -> bool {
+        self.contains(other.top_left) && self.contains(other.bottom_right)
+    }
+
+    pub fn intersects(&self, other: &Rectangle<T>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The overlap of both rectangles, `None` when they are apart.
+    pub fn intersection(&self, other: &Rectangle<T>) -> Option<Rectangle<T>> {
+        let top_left = Point::new(
+            max(self.top_left.x, other.top_left.x),
+            max(self.top_left.y, other.top_left.y),
+        );
+        let bottom_right = Point::new(
+            min(self.bottom_right.x, other.bottom_right.x),
+            min(self.bottom_right.y, other.bottom_right.y),
+        );
+        (top_left.x <= bottom_right.x && top_left.y <= bottom_right.y).then_some(Rectangle {
+            top_left,
+            bottom_right,
+        })
+    }
+
+    /// The bounding box of both rectangles.
+    pub fn union(&self, other: &Rectangle<T>) -> Rectangle<T> {
+        Rectangle {
+            top_left: Point::new(
+                min(self.top_left.x, other.top_left.x),
+                min(self.top_left.y, other.top_left.y),
+            ),
+            bottom_right: Point::new(
+                max(self.bottom_right.x, other.bottom_right.x),
+                max(self.bottom_right.y, other.bottom_right.y),
+            ),
+        }
+    }
+
+    /// Moves every edge outwards by `dx` and `dy`, or inwards when they are
+    /// negative; shrinking past the middle leaves a zero-width line there.
+    pub fn inflate(&self, dx: T, dy: T) -> Option<Rectangle<T>> {
+        let left = self.top_left.x.checked_sub(dx)?;
+        let right = self.bottom_right.x.checked_add(dx)?;
+        let top = self.top_left.y.checked_sub(dy)?;
+        let bottom = self.bottom_right.y.checked_add(dy)?;
+        // only an axis that crosses over needs its middle, and so its length
+        let (left, right) = if left > right {
+            let middle = self.top_left.x.checked_add(self.width()?.half())?;
+            (middle, middle)
+        } else {
+            (left, right)
+        };
+        let (top, bottom) = if top > bottom {
+            let middle = self.top_left.y.checked_add(self.height()?.half())?;
+            (middle, middle)
+        } else {
+            (top, bottom)
+        };
+        Some(Rectangle {
+            top_left: Point::new(left, top),
+            bottom_right: Point::new(right, bottom),
+        })
+    }
+
+    pub fn translate(&self, dx: T, dy: T) -> Option<Rectangle<T>> {
+        Some(Rectangle {
+            top_left: self.top_left.translate(dx, dy)?,
+            bottom_right: self.bottom_right.translate(dx, dy)?,
+        })
+    }
+
+    /// The left and right parts either side of `x`, `None` when `x` is outside.
+    pub fn split_at_x(&self, x: T) -> Option<(Rectangle<T>, Rectangle<T>)> {
+        if x < self.top_left.x || x > self.bottom_right.x {
+            return None;
+        }
+        Some((
+            Rectangle::new(self.top_left, Point::new(x, self.bottom_right.y)),
+            Rectangle::new(Point::new(x, self.top_left.y), self.bottom_right),
+        ))
+    }
+
+    /// The top and bottom parts either side of `y`, `None` when `y` is outside.
+    pub fn split_at_y(&self, y: T) -> Option<(Rectangle<T>, Rectangle<T>)> {
+        if y < self.top_left.y || y > self.bottom_right.y {
+            return None;
+        }
+        Some((
+            Rectangle::new(self.top_left, Point::new(self.bottom_right.x, y)),
+            Rectangle::new(Point::new(self.top_left.x, y), self.bottom_right),
+        ))
+    }
+
+    /// The four quarters around the center: top left, top right, bottom
+    /// left, bottom right.
+    pub fn quadrants(&self) -> Option<[Rectangle<T>; 4]> {
+        let center = self.center()?;
+        let (left, right) = self.split_at_x(center.x)?;
+        let (top_left, bottom_left) = left.split_at_y(center.y)?;
+        let (top_right, bottom_right) = right.split_at_y(center.y)?;
+        Some([top_left, top_right, bottom_left, bottom_right])
+    }
+}
+
+impl From<Rectangle<i32>> for Rectangle<f64> {
+    fn from(rect: Rectangle<i32>) -> Rectangle<f64> {
+        Rectangle::new(rect.top_left.into(), rect.bottom_right.into())
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Rectangle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.top_left.fmt(f)?;
+        write!(f, " to ")?;
+        self.bottom_right.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x1: i32, y1: i32, x2: i32, y2: i32) -> Rectangle<i32> {
+        Rectangle::new(Point::new(x1, y1), Point::new(x2, y2))
+    }
+
+    #[test]
+    fn normalised_construction() {
+        // the corners of chapter3's rectangle, swapped
+        let swapped = rect(3, 5, 1, 2);
+        assert_eq!(swapped, rect(1, 2, 3, 5));
+        assert_eq!(swapped.top_left(), Point::new(1, 2));
+        assert_eq!(swapped.area(), Some(6));
+        assert_eq!(rect(1, 5, 3, 2).bottom_left(), Point::new(1, 5));
+        assert_eq!(
+            Rectangle::square(Point::new(1, 2), -5),
+            Some(rect(-4, -3, 1, 2))
+        );
+        assert_eq!(
+            Rectangle::bounding_box([Point::new(3, 1), Point::new(-2, 4), Point::new(0, 0)]),
+            Some(rect(-2, 0, 3, 4))
+        );
+        assert_eq!(Rectangle::<i32>::bounding_box([]), None);
+    }
+
+    #[test]
+    fn measurements() {
+        let r = rect(0, 0, 4, 3);
+        assert_eq!((r.width(), r.height()), (Some(4), Some(3)));
+        assert_eq!(r.perimeter(), Some(14));
+        assert_eq!(r.center(), Some(Point::new(2, 1)));
+
+        let f = Rectangle::new(Point::new(0.0, 0.0), Point::new(3.0, 1.5));
+        assert_eq!(f.area(), Some(4.5));
+        assert_eq!(f.center(), Some(Point::new(1.5, 0.75)));
+    }
+
+    #[test]
+    fn integer_overflow_is_none() {
+        let huge = rect(i32::MIN, 0, i32::MAX, 1);
+        assert_eq!(huge.width(), None);
+        assert_eq!(huge.area(), None);
+        assert_eq!(huge.center(), None);
+        assert_eq!(rect(0, 0, 70_000, 70_000).area(), None);
+        assert_eq!(rect(0, 0, 1, 1).translate(i32::MAX, 0), None);
+        assert_eq!(Rectangle::square(Point::new(i32::MAX, 0), 1), None);
+
+        let infinite = Rectangle::new(Point::new(0.0, 0.0), Point::new(f64::MAX, 2.0));
+        assert_eq!(infinite.perimeter(), None);
+    }
+
+    #[test]
+    fn containment_and_intersection() {
+        let r = rect(0, 0, 10, 10);
+        assert!(r.contains(Point::new(0, 10)));
+        assert!(!r.contains(Point::new(11, 5)));
+        assert!(r.contains_rect(&rect(2, 2, 5, 5)));
+
+        assert_eq!(
+            r.intersection(&rect(5, 5, 15, 15)),
+            Some(rect(5, 5, 10, 10))
+        );
+        // sharing an edge is a zero-width intersection
+        let edge = r.intersection(&rect(10, 0, 20, 10)).unwrap();
+        assert!(edge.is_empty());
+        assert!(!r.intersects(&rect(11, 0, 20, 10)));
+        assert_eq!(r.union(&rect(5, -5, 12, 3)), rect(0, -5, 12, 10));
+    }
+
+    #[test]
+    fn inflate_split_and_quadrants() {
+        let r = rect(0, 0, 10, 6);
+        assert_eq!(r.inflate(2, 1), Some(rect(-2, -1, 12, 7)));
+        assert_eq!(r.inflate(-2, -1), Some(rect(2, 1, 8, 5)));
+        assert_eq!(r.inflate(-8, 0), Some(rect(5, 0, 5, 6)));
+        // wider than an i32 can measure, but every edge still fits
+        let wide = rect(-2_000_000_000, 0, 2_000_000_000, 6);
+        assert_eq!(
+            wide.inflate(1, -4),
+            Some(rect(-2_000_000_001, 3, 2_000_000_001, 3))
+        );
+        assert_eq!(
+            wide.inflate(0, 1),
+            Some(rect(-2_000_000_000, -1, 2_000_000_000, 7))
+        );
+        assert_eq!(wide.inflate(200_000_000, 0), None);
+
+        assert_eq!(r.split_at_x(4), Some((rect(0, 0, 4, 6), rect(4, 0, 10, 6))));
+        assert_eq!(r.split_at_y(7), None);
+        assert_eq!(
+            r.quadrants(),
+            Some([
+                rect(0, 0, 5, 3),
+                rect(5, 0, 10, 3),
+                rect(0, 3, 5, 6),
+                rect(5, 3, 10, 6)
+            ])
+        );
+        assert_eq!(r.to_string(), "(0, 0) to (10, 6)");
+    }
+}
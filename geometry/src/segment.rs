@@ -0,0 +1,201 @@
+/* Line segments */
+// the predicates below work in f64; "counter-clockwise" is as seen with the
+// y axis pointing up, so on screen, where y grows downwards, it is mirrored
+
+use std::fmt;
+
+use crate::point::Point;
+
+/// Twice the signed area of the triangle `a`, `b`, `c`: positive when `c` is
+/// to the left of the line from `a` to `b`.
+pub fn cross(a: Point<f64>, b: Point<f64>, c: Point<f64>) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+    Collinear,
+}
+
+pub fn orientation(a: Point<f64>, b: Point<f64>, c: Point<f64>) -> Orientation {
+    let turn = cross(a, b, c);
+    if turn > 0.0 {
+        Orientation::CounterClockwise
+    } else if turn < 0.0 {
+        Orientation::Clockwise
+    } else {
+        Orientation::Collinear
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub start: Point<f64>,
+    pub end: Point<f64>,
+}
+
+/// Where two segments meet: a single point, or a shared piece when they
+/// are collinear and overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Intersection {
+    Point(Point<f64>),
+    Overlap(Segment),
+}
+
+impl Segment {
+    pub fn new(start: Point<f64>, end: Point<f64>) -> Segment {
+        Segment { start, end }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.start.distance(self.end)
+    }
+
+    /// Whether `point` lies on the segment, end points included.
+    pub fn contains(&self, point: Point<f64>) -> bool {
+        cross(self.start, self.end, point) == 0.0 && self.within_bounds(point)
+    }
+
+    // for a point already known to be on the line through the segment
+    fn within_bounds(&self, point: Point<f64>) -> bool {
+        self.start.x.min(self.end.x) <= point.x
+            && point.x <= self.start.x.max(self.end.x)
+            && self.start.y.min(self.end.y) <= point.y
+            && point.y <= self.start.y.max(self.end.y)
+    }
+
+    /// Where the line through `self` crosses the line through `other`,
+    /// `None` when they are parallel.
+    pub fn line_intersection(&self, other: &Segment) -> Option<Point<f64>> {
+        let d1 = Point::new(self.end.x - self.start.x, self.end.y - self.start.y);
+        let d2 = Point::new(other.end.x - other.start.x, other.end.y - other.start.y);
+        let denominator = d1.x * d2.y - d1.y * d2.x;
+        if denominator == 0.0 {
+            return None;
+        }
+        let t = ((other.start.x - self.start.x) * d2.y - (other.start.y - self.start.y) * d2.x)
+            / denominator;
+        Some(Point::new(self.start.x + t * d1.x, self.start.y + t * d1.y))
+    }
+
+    pub fn intersection(&self, other: &Segment) -> Option<Intersection> {
+        let o1 = orientation(self.start, self.end, other.start);
+        let o2 = orientation(self.start, self.end, other.end);
+        let o3 = orientation(other.start, other.end, self.start);
+        let o4 = orientation(other.start, other.end, self.end);
+
+        if o1 == Orientation::Collinear && o2 == Orientation::Collinear {
+            return self.collinear_overlap(other);
+        }
+        if o1 != o2 && o3 != o4 {
+            // touching at an end point is exact; otherwise solve for it
+            let touching = [other.start, other.end]
+                .into_iter()
+                .find(|&p| self.contains(p))
+                .or_else(|| {
+                    [self.start, self.end]
+                        .into_iter()
+                        .find(|&p| other.contains(p))
+                });
+            return touching
+                .or_else(|| self.line_intersection(other))
+                .map(Intersection::Point);
+        }
+        None
+    }
+
+    // both segments lie on one line: project onto it and clamp
+    fn collinear_overlap(&self, other: &Segment) -> Option<Intersection> {
+        let direction = Point::new(self.end.x - self.start.x, self.end.y - self.start.y);
+        let project =
+            |p: Point<f64>| (p.x - self.start.x) * direction.x + (p.y - self.start.y) * direction.y;
+        if direction == Point::origin() {
+            return other
+                .contains(self.start)
+                .then_some(Intersection::Point(self.start));
+        }
+        let (mut lo, mut hi) = (other.start, other.end);
+        if project(lo) > project(hi) {
+            std::mem::swap(&mut lo, &mut hi);
+        }
+        let start = if project(lo) > 0.0 { lo } else { self.start };
+        let end = if project(hi) < project(self.end) {
+            hi
+        } else {
+            self.end
+        };
+        match project(start).partial_cmp(&project(end)) {
+            Some(std::cmp::Ordering::Less) => Some(Intersection::Overlap(Segment::new(start, end))),
+            Some(std::cmp::Ordering::Equal) => Some(Intersection::Point(start)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.start.fmt(f)?;
+        write!(f, " to ")?;
+        self.end.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(x1: f64, y1: f64, x2: f64, y2: f64) -> Segment {
+        Segment::new(Point::new(x1, y1), Point::new(x2, y2))
+    }
+
+    #[test]
+    fn crossing_segments_meet_in_a_point() {
+        let a = seg(0.0, 0.0, 4.0, 4.0);
+        let b = seg(0.0, 4.0, 4.0, 0.0);
+        assert_eq!(
+            a.intersection(&b),
+            Some(Intersection::Point(Point::new(2.0, 2.0)))
+        );
+        assert_eq!(a.intersection(&seg(5.0, 0.0, 5.0, 9.0)), None);
+        // a T junction touches exactly at the end point
+        assert_eq!(
+            a.intersection(&seg(1.0, 1.0, 3.0, 0.0)),
+            Some(Intersection::Point(Point::new(1.0, 1.0)))
+        );
+    }
+
+    #[test]
+    fn collinear_segments() {
+        let a = seg(0.0, 0.0, 4.0, 0.0);
+        assert_eq!(
+            a.intersection(&seg(6.0, 0.0, 2.0, 0.0)),
+            Some(Intersection::Overlap(seg(2.0, 0.0, 4.0, 0.0)))
+        );
+        assert_eq!(
+            a.intersection(&seg(4.0, 0.0, 7.0, 0.0)),
+            Some(Intersection::Point(Point::new(4.0, 0.0)))
+        );
+        assert_eq!(a.intersection(&seg(5.0, 0.0, 7.0, 0.0)), None);
+        // parallel but on another line
+        assert_eq!(a.intersection(&seg(0.0, 1.0, 4.0, 1.0)), None);
+    }
+
+    #[test]
+    fn orientation_follows_the_y_up_convention() {
+        let (a, b) = (Point::new(0.0, 0.0), Point::new(1.0, 0.0));
+        assert_eq!(
+            orientation(a, b, Point::new(1.0, 1.0)),
+            Orientation::CounterClockwise
+        );
+        assert_eq!(
+            orientation(a, b, Point::new(1.0, -1.0)),
+            Orientation::Clockwise
+        );
+        assert_eq!(
+            orientation(a, b, Point::new(3.0, 0.0)),
+            Orientation::Collinear
+        );
+    }
+}
@@ -0,0 +1,569 @@
+/* SVG export */
+// a `Scene` is a tree of groups holding styled shapes; it writes itself as a
+// standalone SVG document whose viewBox is the scene's bounding box
+
+// coordinates are written with `{}`, the shortest text that parses back to
+// the same f64, so a document can be read back without losing precision.
+// SVG's y axis grows downwards, like ours
+
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+
+use crate::point::Point;
+use crate::polygon::Polygon;
+use crate::rect::Rectangle;
+
+/// Fill and stroke of a shape; unset parts are inherited from the groups
+/// around it, as in SVG.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Style {
+    fill: Option<Paint>,
+    stroke: Option<Paint>,
+    stroke_width: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paint {
+    None,
+    Rgb([u8; 3]),
+}
+
+impl fmt::Display for Paint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Paint::None => write!(f, "none"),
+            Paint::Rgb([r, g, b]) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+impl Style {
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    pub fn fill(mut self, color: impl Into<[u8; 3]>) -> Style {
+        self.fill = Some(Paint::Rgb(color.into()));
+        self
+    }
+
+    pub fn no_fill(mut self) -> Style {
+        self.fill = Some(Paint::None);
+        self
+    }
+
+    pub fn stroke(mut self, color: impl Into<[u8; 3]>, width: f64) -> Style {
+        self.stroke = Some(Paint::Rgb(color.into()));
+        self.stroke_width = Some(width);
+        self
+    }
+
+    fn write_attributes(&self, out: &mut String) {
+        if let Some(fill) = self.fill {
+            let _ = write!(out, r#" fill="{}""#, fill);
+        }
+        if let Some(stroke) = self.stroke {
+            let _ = write!(out, r#" stroke="{}""#, stroke);
+        }
+        if let Some(width) = self.stroke_width {
+            let _ = write!(out, r#" stroke-width="{}""#, width);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Rect(Rectangle<f64>),
+    Circle {
+        center: Point<f64>,
+        radius: f64,
+    },
+    Polygon(Polygon),
+    Line(Point<f64>, Point<f64>),
+    /// `size` is the font size; `position` is the start of the baseline.
+    Text {
+        position: Point<f64>,
+        text: String,
+        size: f64,
+    },
+}
+
+impl Shape {
+    /// The area the shape covers, without stroke; for text an estimate
+    /// assuming glyphs 0.6 of the font size wide.
+    pub fn bounding_box(&self) -> Rectangle<f64> {
+        match self {
+            Shape::Rect(rect) => *rect,
+            Shape::Circle { center, radius } => Rectangle::new(
+                Point::new(center.x - radius, center.y - radius),
+                Point::new(center.x + radius, center.y + radius),
+            ),
+            Shape::Polygon(polygon) => polygon.bounding_box(),
+            Shape::Line(a, b) => Rectangle::new(*a, *b),
+            Shape::Text {
+                position,
+                text,
+                size,
+            } => {
+                let width = 0.6 * size * text.chars().count() as f64;
+                Rectangle::new(
+                    Point::new(position.x, position.y - size),
+                    Point::new(position.x + width, position.y),
+                )
+            }
+        }
+    }
+
+    fn write(&self, out: &mut String, style: &Style, indent: usize) {
+        let mut attributes = String::new();
+        style.write_attributes(&mut attributes);
+        let pad = "  ".repeat(indent);
+        let _ = match self {
+            Shape::Rect(rect) => {
+                let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+                writeln!(
+                    out,
+                    r#"{}<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+                    pad,
+                    top_left.x,
+                    top_left.y,
+                    bottom_right.x - top_left.x,
+                    bottom_right.y - top_left.y,
+                    attributes
+                )
+            }
+            Shape::Circle { center, radius } => writeln!(
+                out,
+                r#"{}<circle cx="{}" cy="{}" r="{}"{}/>"#,
+                pad, center.x, center.y, radius, attributes
+            ),
+            Shape::Polygon(polygon) => {
+                let points: Vec<String> = polygon
+                    .vertices()
+                    .iter()
+                    .map(|p| format!("{},{}", p.x, p.y))
+                    .collect();
+                writeln!(
+                    out,
+                    r#"{}<polygon points="{}"{}/>"#,
+                    pad,
+                    points.join(" "),
+                    attributes
+                )
+            }
+            Shape::Line(a, b) => writeln!(
+                out,
+                r#"{}<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+                pad, a.x, a.y, b.x, b.y, attributes
+            ),
+            Shape::Text {
+                position,
+                text,
+                size,
+            } => writeln!(
+                out,
+                r#"{}<text x="{}" y="{}" font-size="{}"{}>{}</text>"#,
+                pad,
+                position.x,
+                position.y,
+                size,
+                attributes,
+                escape(text)
+            ),
+        };
+    }
+}
+
+/// Escapes the characters XML gives a meaning to.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Applied to a group's contents, the last one first, as in SVG.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    Translate(f64, f64),
+    Scale(f64, f64),
+    /// Clockwise on screen, in degrees, around the origin.
+    Rotate(f64),
+}
+
+impl Transform {
+    pub fn apply(&self, p: Point<f64>) -> Point<f64> {
+        match *self {
+            Transform::Translate(dx, dy) => Point::new(p.x + dx, p.y + dy),
+            Transform::Scale(sx, sy) => Point::new(p.x * sx, p.y * sy),
+            Transform::Rotate(degrees) => {
+                let (sin, cos) = degrees.to_radians().sin_cos();
+                Point::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Transform::Translate(dx, dy) => write!(f, "translate({} {})", dx, dy),
+            Transform::Scale(sx, sy) => write!(f, "scale({} {})", sx, sy),
+            Transform::Rotate(degrees) => write!(f, "rotate({})", degrees),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Shape(Shape, Style),
+    Group(Group),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Group {
+    transforms: Vec<Transform>,
+    style: Style,
+    children: Vec<Element>,
+}
+
+impl Group {
+    pub fn new() -> Group {
+        Group::default()
+    }
+
+    pub fn transform(mut self, transform: Transform) -> Group {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// The style the group's shapes inherit.
+    pub fn style(mut self, style: Style) -> Group {
+        self.style = style;
+        self
+    }
+
+    pub fn add(&mut self, shape: Shape, style: Style) -> &mut Group {
+        self.children.push(Element::Shape(shape, style));
+        self
+    }
+
+    pub fn group(&mut self, group: Group) -> &mut Group {
+        self.children.push(Element::Group(group));
+        self
+    }
+
+    /// The bounding box in the coordinates around the group. A rotated
+    /// box is bounded again, so rotations can make it larger than needed.
+    pub fn bounding_box(&self) -> Option<Rectangle<f64>> {
+        self.bounds_inheriting(None)
+    }
+
+    // shapes are inflated by the stroke width they end up with, which may
+    // come from any group above them
+    fn bounds_inheriting(&self, stroke_width: Option<f64>) -> Option<Rectangle<f64>> {
+        let stroke_width = self.style.stroke_width.or(stroke_width);
+        let inner = self
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                Element::Shape(shape, style) => {
+                    let width = style.stroke_width.or(stroke_width).unwrap_or(0.0);
+                    let bounds = shape.bounding_box();
+                    Some(bounds.inflate(width / 2.0, width / 2.0).unwrap_or(bounds))
+                }
+                Element::Group(group) => group.bounds_inheriting(stroke_width),
+            })
+            .reduce(|a, b| a.union(&b))?;
+        let corners = [
+            inner.top_left(),
+            inner.top_right(),
+            inner.bottom_right(),
+            inner.bottom_left(),
+        ];
+        Rectangle::bounding_box(corners.map(|corner| {
+            self.transforms
+                .iter()
+                .rev()
+                .fold(corner, |p, transform| transform.apply(p))
+        }))
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        let pad = "  ".repeat(indent);
+        let mut attributes = String::new();
+        if !self.transforms.is_empty() {
+            let transforms: Vec<String> = self.transforms.iter().map(|t| t.to_string()).collect();
+            let _ = write!(attributes, r#" transform="{}""#, transforms.join(" "));
+        }
+        self.style.write_attributes(&mut attributes);
+        let _ = writeln!(out, "{}<g{}>", pad, attributes);
+        self.write_children(out, indent + 1);
+        let _ = writeln!(out, "{}</g>", pad);
+    }
+
+    fn write_children(&self, out: &mut String, indent: usize) {
+        for child in &self.children {
+            match child {
+                Element::Shape(shape, style) => shape.write(out, style, indent),
+                Element::Group(group) => group.write(out, indent),
+            }
+        }
+    }
+}
+
+/// A whole document: a top-level group plus a margin around its contents.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Scene {
+    root: Group,
+    margin: f64,
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Scene::default()
+    }
+
+    pub fn margin(mut self, margin: f64) -> Scene {
+        self.margin = margin;
+        self
+    }
+
+    pub fn add(&mut self, shape: Shape, style: Style) -> &mut Scene {
+        self.root.add(shape, style);
+        self
+    }
+
+    pub fn group(&mut self, group: Group) -> &mut Scene {
+        self.root.group(group);
+        self
+    }
+
+    /// The area the viewBox shows: everything drawn plus the margin, rounded
+    /// out to whole units, or a unit square for an empty scene.
+    pub fn view_box(&self) -> Rectangle<f64> {
+        let bounds = self
+            .root
+            .bounding_box()
+            .unwrap_or_else(|| Rectangle::new(Point::origin(), Point::new(1.0, 1.0)));
+        let bounds = bounds.inflate(self.margin, self.margin).unwrap_or(bounds);
+        let (top_left, bottom_right) = (bounds.top_left(), bounds.bottom_right());
+        Rectangle::new(
+            Point::new(top_left.x.floor(), top_left.y.floor()),
+            Point::new(bottom_right.x.ceil(), bottom_right.y.ceil()),
+        )
+    }
+
+    pub fn to_svg(&self) -> String {
+        let view_box = self.view_box();
+        let (top_left, bottom_right) = (view_box.top_left(), view_box.bottom_right());
+        let (width, height) = (bottom_right.x - top_left.x, bottom_right.y - top_left.y);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+            top_left.x, top_left.y, width, height, width, height
+        );
+        self.root.write_children(&mut out, 1);
+        out.push_str("</svg>\n");
+        out
+    }
+
+    pub fn write_svg(&self, out: &mut dyn Write) -> io::Result<()> {
+        out.write_all(self.to_svg().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // just enough of an XML reader for the documents above: every tag with
+    // its attributes, and the text after it
+    type Tag = (String, Vec<(String, String)>, String);
+
+    fn tags(svg: &str) -> Vec<Tag> {
+        let mut found = Vec::new();
+        let mut rest = svg;
+        while let Some(start) = rest.find('<') {
+            let end = rest[start..].find('>').unwrap() + start;
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('/') {
+                continue;
+            }
+            let tag = tag.trim_end_matches('/');
+            let (name, mut attributes) = tag.split_once(' ').unwrap_or((tag, ""));
+            let mut parsed = Vec::new();
+            while let Some((key, value)) = attributes.split_once("=\"") {
+                let (value, after) = value.split_once('"').unwrap();
+                parsed.push((key.trim().to_string(), value.to_string()));
+                attributes = after;
+            }
+            let text = rest[..rest.find('<').unwrap_or(rest.len())]
+                .trim()
+                .to_string();
+            found.push((name.to_string(), parsed, text));
+        }
+        found
+    }
+
+    fn attribute<'a>(tag: &'a Tag, key: &str) -> &'a str {
+        &tag.1.iter().find(|(k, _)| k == key).unwrap().1
+    }
+
+    fn number(tag: &Tag, key: &str) -> f64 {
+        attribute(tag, key).parse().unwrap()
+    }
+
+    #[test]
+    fn coordinates_round_trip() {
+        let rect = Rectangle::new(Point::new(0.1, 0.2), Point::new(1.0 / 3.0, 7.25));
+        let triangle = Polygon::new(vec![
+            Point::new(-2.5, 1e-3),
+            Point::new(3.0, 4.0),
+            Point::new(2.0 / 7.0, 5.0),
+        ])
+        .unwrap();
+        let mut scene = Scene::new();
+        scene
+            .add(Shape::Rect(rect), Style::new().fill([255, 0, 0]))
+            .add(
+                Shape::Circle {
+                    center: Point::new(10.0, 0.3),
+                    radius: 5.0,
+                },
+                Style::new().no_fill().stroke([0, 0, 255], 2.0),
+            )
+            .add(Shape::Polygon(triangle.clone()), Style::new());
+
+        let svg = scene.to_svg();
+        let tags = tags(&svg);
+        assert_eq!(
+            tags.iter().map(|t| t.0.as_str()).collect::<Vec<_>>(),
+            ["svg", "rect", "circle", "polygon"]
+        );
+        let r = &tags[1];
+        let top_left = Point::new(number(r, "x"), number(r, "y"));
+        assert_eq!(top_left, rect.top_left());
+        assert_eq!(number(r, "width"), rect.width().unwrap());
+        assert_eq!(attribute(r, "fill"), "#ff0000");
+
+        let c = &tags[2];
+        assert_eq!(
+            (number(c, "cx"), number(c, "cy"), number(c, "r")),
+            (10.0, 0.3, 5.0)
+        );
+        assert_eq!(attribute(c, "stroke"), "#0000ff");
+        assert_eq!(attribute(c, "fill"), "none");
+
+        let points: Vec<Point<f64>> = attribute(&tags[3], "points")
+            .split(' ')
+            .map(|pair| {
+                let (x, y) = pair.split_once(',').unwrap();
+                Point::new(x.parse().unwrap(), y.parse().unwrap())
+            })
+            .collect();
+        assert_eq!(points, triangle.vertices());
+    }
+
+    #[test]
+    fn view_box_covers_the_scene() {
+        let mut scene = Scene::new().margin(1.0);
+        scene.add(
+            Shape::Circle {
+                center: Point::new(5.0, 5.0),
+                radius: 2.0,
+            },
+            Style::new().stroke([0, 0, 0], 2.0),
+        );
+        // the stroke adds half its width, the margin one more
+        assert_eq!(
+            scene.view_box(),
+            Rectangle::new(Point::new(1.0, 1.0), Point::new(9.0, 9.0))
+        );
+
+        let mut moved = Group::new()
+            .transform(Transform::Translate(10.0, 0.0))
+            .transform(Transform::Scale(2.0, 2.0));
+        moved.add(
+            Shape::Line(Point::new(0.0, 0.0), Point::new(5.0, 10.0)),
+            Style::new(),
+        );
+        scene.group(moved);
+        assert_eq!(
+            scene.view_box(),
+            Rectangle::new(Point::new(1.0, -1.0), Point::new(21.0, 21.0))
+        );
+        let svg = scene.to_svg();
+        let tags = tags(&svg);
+        assert_eq!(attribute(&tags[0], "viewBox"), "1 -1 20 22");
+        assert_eq!(
+            attribute(&tags[2], "transform"),
+            "translate(10 0) scale(2 2)"
+        );
+        assert_eq!(Scene::new().view_box().area(), Some(1.0));
+    }
+
+    #[test]
+    fn nested_shapes_inherit_the_stroke_width() {
+        let mut inner = Group::new();
+        inner.add(
+            Shape::Rect(Rectangle::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0))),
+            Style::new(),
+        );
+        let mut middle = Group::new().transform(Transform::Translate(10.0, 0.0));
+        middle.group(inner);
+        let mut outer = Group::new().style(Style::new().stroke([0, 0, 0], 2.0));
+        outer.group(middle);
+        assert_eq!(
+            outer.bounding_box(),
+            Some(Rectangle::new(Point::new(9.0, -1.0), Point::new(15.0, 5.0)))
+        );
+
+        // a nearer group, or the shape itself, overrides the width
+        let mut thick = Group::new().style(Style::new().stroke([0, 0, 0], 4.0));
+        let mut own = Group::new();
+        own.add(
+            Shape::Rect(Rectangle::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0))),
+            Style::new().stroke([0, 0, 0], 0.0),
+        );
+        thick.group(own);
+        assert_eq!(
+            thick.bounding_box(),
+            Some(Rectangle::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0)))
+        );
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let mut scene = Scene::new();
+        scene.add(
+            Shape::Text {
+                position: Point::new(0.0, 10.0),
+                text: String::from("a < b & \"c\""),
+                size: 10.0,
+            },
+            Style::new().fill([0, 0, 0]),
+        );
+        let svg = scene.to_svg();
+        assert!(svg.contains("a &lt; b &amp; &quot;c&quot;</text>"));
+        let tags = tags(&svg);
+        assert_eq!(tags[1].2, "a &lt; b &amp; &quot;c&quot;");
+        assert_eq!(attribute(&tags[0], "viewBox"), "0 0 66 10");
+    }
+
+    #[test]
+    fn rotation_about_the_origin() {
+        let p = Transform::Rotate(90.0).apply(Point::new(1.0, 0.0));
+        assert!(p.x.abs() < 1e-12 && (p.y - 1.0).abs() < 1e-12);
+    }
+}
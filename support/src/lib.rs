@@ -0,0 +1,14 @@
+/* Shared helpers for the chapter binaries */
+// every chapter depends on this crate through a path dependency
+
+pub mod parts;
+pub mod pretty;
+pub mod report;
+pub mod sink;
+pub mod snapshot;
+pub mod table;
+
+pub use parts::{run, Part};
+pub use pretty::{pretty, Pretty};
+pub use sink::{FmtWriter, SharedSink};
+pub use table::Table;
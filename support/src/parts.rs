@@ -0,0 +1,319 @@
+/* Part registry and command line selector */
+// each chapter registers its `partN` functions with a one-line description
+// and hands the registry to `run`, which picks the parts to execute from
+// the command line instead of commenting calls in and out of `main`
+
+use std::env;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::process::ExitCode;
+
+use crate::report::{self, Format};
+
+/// Exit code used when a part panics, matching the code of an unwinding panic in `main`.
+pub const PANIC_EXIT_CODE: u8 = 101;
+
+/// Exit code used for malformed command lines.
+pub const USAGE_EXIT_CODE: u8 = 2;
+
+pub struct Part {
+    /// Name of the function, e.g. `part1a`.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Writes the output of the part to the given sink.
+    pub run: fn(&mut dyn Write) -> io::Result<()>,
+}
+
+impl Part {
+    /// The part number as written on the command line, e.g. `1a` for `part1a`.
+    pub fn id(&self) -> &'static str {
+        self.name.strip_prefix("part").unwrap_or(self.name)
+    }
+
+    /// Leading numeric component of the id, used for `--range`.
+    pub fn number(&self) -> Option<u32> {
+        let id = self.id();
+        let end = id.find(|c: char| !c.is_ascii_digit()).unwrap_or(id.len());
+        id[..end].parse().ok()
+    }
+}
+
+/// Builds a `[Part; N]` registry from `function => "description"` pairs.
+#[macro_export]
+macro_rules! parts {
+    ($($func:ident => $description:expr),+ $(,)?) => {
+        [$(
+            $crate::Part {
+                name: stringify!($func),
+                description: $description,
+                run: $func,
+            }
+        ),+]
+    };
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Help,
+    List,
+    Run(Selection),
+    /// Run in isolation and report every outcome, for `--keep-going` and `--json`.
+    Report(Selection, Format),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Selection {
+    All,
+    Part(String),
+    // bounds on the numeric part of the id, end exclusive
+    Range(u32, u32),
+}
+
+impl Selection {
+    fn matches(&self, part: &Part) -> bool {
+        match self {
+            Selection::All => true,
+            Selection::Part(id) => part.id() == id,
+            Selection::Range(start, end) => {
+                part.number().is_some_and(|n| (*start..*end).contains(&n))
+            }
+        }
+    }
+}
+
+/// Parses `3..7` (end exclusive) or `3..=7` (end inclusive).
+fn parse_range(range: &str) -> Result<Selection, String> {
+    let invalid = || format!("invalid range `{}`, expected e.g. `3..7` or `3..=7`", range);
+
+    let (start, end, inclusive) = match range.split_once("..=") {
+        Some((start, end)) => (start, end, true),
+        None => {
+            let (start, end) = range.split_once("..").ok_or_else(invalid)?;
+            (start, end, false)
+        }
+    };
+    let start: u32 = start.trim().parse().map_err(|_| invalid())?;
+    let end: u32 = end.trim().parse().map_err(|_| invalid())?;
+    let end = if inclusive {
+        end.checked_add(1).ok_or_else(invalid)?
+    } else {
+        end
+    };
+
+    if start >= end {
+        return Err(format!("range `{}` is empty", range));
+    }
+    Ok(Selection::Range(start, end))
+}
+
+/// Stores a flag, rejecting it when the same kind of flag was already given.
+fn set_once<T>(slot: &mut Option<T>, value: T, arg: &str) -> Result<(), String> {
+    match slot {
+        Some(_) => Err(format!("unexpected argument `{}`", arg)),
+        None => {
+            *slot = Some(value);
+            Ok(())
+        }
+    }
+}
+
+pub fn parse_args<I>(args: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut standalone = None;
+    let mut selection = None;
+    let mut format = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => set_once(&mut standalone, Command::Help, &arg)?,
+            "--list" => set_once(&mut standalone, Command::List, &arg)?,
+            "--all" => set_once(&mut selection, Selection::All, &arg)?,
+            "--part" => {
+                let id = args.next().ok_or("`--part` needs a part number")?;
+                let id = id.trim_start_matches("part").to_owned();
+                set_once(&mut selection, Selection::Part(id), &arg)?
+            }
+            "--range" => {
+                let range = args
+                    .next()
+                    .ok_or("`--range` needs a range such as `3..7`")?;
+                set_once(&mut selection, parse_range(&range)?, &arg)?
+            }
+            "--keep-going" => set_once(&mut format, Format::Text, &arg)?,
+            "--json" => set_once(&mut format, Format::Json, &arg)?,
+            other => return Err(format!("unknown argument `{}`", other)),
+        }
+    }
+
+    let selection_given = selection.is_some();
+    let selection = selection.unwrap_or(Selection::All);
+    match (standalone, format) {
+        (Some(command), None) if !selection_given => Ok(command),
+        (Some(_), _) => Err(String::from(
+            "`--help` and `--list` take no other arguments",
+        )),
+        (None, None) => Ok(Command::Run(selection)),
+        (None, Some(format)) => Ok(Command::Report(selection, format)),
+    }
+}
+
+fn usage(program: &str) -> String {
+    format!(
+        "Usage: {} [--list | [--all | --part N | --range A..B] [--keep-going | --json]]\n\
+         \n\
+         \x20 --list         list the parts of this chapter\n\
+         \x20 --all          run every part in order (default)\n\
+         \x20 --part N       run a single part, e.g. `--part 3` or `--part 1a`\n\
+         \x20 --range A..B   run parts A up to B, `A..=B` includes B\n\
+         \x20 --keep-going   run past panicking parts and print a summary at the end\n\
+         \x20 --json         like --keep-going, but print the outputs and outcomes as JSON",
+        program
+    )
+}
+
+fn list(parts: &[Part]) {
+    let width = parts.iter().map(|part| part.id().len()).max().unwrap_or(0);
+    for part in parts {
+        println!("{:>width$}  {}", part.id(), part.description, width = width);
+    }
+}
+
+/// Entry point for the chapter binaries: parses the command line and runs the selected parts.
+pub fn run(parts: &[Part]) -> ExitCode {
+    let mut args = env::args();
+    let program = args.next().unwrap_or_else(|| "chapter".to_owned());
+
+    let (selection, format) = match parse_args(args) {
+        Ok(Command::Help) => {
+            println!("{}", usage(&program));
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::List) => {
+            list(parts);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Run(selection)) => (selection, None),
+        Ok(Command::Report(selection, format)) => (selection, Some(format)),
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, usage(&program));
+            return ExitCode::from(USAGE_EXIT_CODE);
+        }
+    };
+
+    let selected: Vec<&Part> = parts
+        .iter()
+        .filter(|part| selection.matches(part))
+        .collect();
+    if selected.is_empty() {
+        eprintln!("error: no part matches the selection, see `--list`");
+        return ExitCode::from(USAGE_EXIT_CODE);
+    }
+    if let Some(format) = format {
+        return report::run(&selected, format);
+    }
+
+    let mut stdout = io::stdout();
+    for part in &selected {
+        if selected.len() > 1 {
+            println!("--- {}: {} ---", part.name, part.description);
+        }
+        // the panic hook has already reported the message, so only stop here
+        match panic::catch_unwind(AssertUnwindSafe(|| (part.run)(&mut stdout))) {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                eprintln!("error: {} could not write its output: {}", part.name, e);
+                return ExitCode::FAILURE;
+            }
+            Err(_) => {
+                eprintln!("error: {} panicked", part.name);
+                return ExitCode::from(PANIC_EXIT_CODE);
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults_to_all() {
+        assert_eq!(parse(&[]), Ok(Command::Run(Selection::All)));
+        assert_eq!(parse(&["--all"]), Ok(Command::Run(Selection::All)));
+    }
+
+    #[test]
+    fn single_part() {
+        assert_eq!(
+            parse(&["--part", "1a"]),
+            Ok(Command::Run(Selection::Part("1a".into())))
+        );
+        assert_eq!(
+            parse(&["--part", "part3"]),
+            Ok(Command::Run(Selection::Part("3".into())))
+        );
+        assert!(parse(&["--part"]).is_err());
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(
+            parse(&["--range", "3..7"]),
+            Ok(Command::Run(Selection::Range(3, 7)))
+        );
+        assert_eq!(
+            parse(&["--range", "3..=7"]),
+            Ok(Command::Run(Selection::Range(3, 8)))
+        );
+        assert!(parse(&["--range", "7..3"]).is_err());
+        assert!(parse(&["--range", "three"]).is_err());
+    }
+
+    #[test]
+    fn report_formats() {
+        assert_eq!(
+            parse(&["--keep-going"]),
+            Ok(Command::Report(Selection::All, Format::Text))
+        );
+        assert_eq!(
+            parse(&["--json", "--range", "1..3"]),
+            Ok(Command::Report(Selection::Range(1, 3), Format::Json))
+        );
+        assert!(parse(&["--keep-going", "--json"]).is_err());
+        assert!(parse(&["--list", "--json"]).is_err());
+    }
+
+    #[test]
+    fn rejects_extra_arguments() {
+        assert!(parse(&["--list", "--all"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+    }
+
+    #[test]
+    fn ids_and_numbers() {
+        fn noop(_: &mut dyn Write) -> io::Result<()> {
+            Ok(())
+        }
+        let parts = parts![noop => "unnamed"];
+        assert_eq!(parts[0].id(), "noop");
+        assert_eq!(parts[0].number(), None);
+
+        let part = Part {
+            name: "part12a",
+            description: "",
+            run: noop,
+        };
+        assert_eq!(part.id(), "12a");
+        assert_eq!(part.number(), Some(12));
+        assert!(Selection::Range(12, 13).matches(&part));
+        assert!(!Selection::Range(1, 12).matches(&part));
+    }
+}
@@ -0,0 +1,427 @@
+/* Pretty printer */
+// a Wadler-style document is laid out for a target width: each group is
+// printed on one line when the rest of that line still fits, otherwise its
+// line breaks are taken, so nested values only break where they have to
+
+// `pretty(&value, width)` builds the document from the `{:?}` output, so it
+// works for any `Debug` type, and prints exactly `{:?}` when it fits
+
+use std::fmt::{self, Debug, Display};
+use std::ops::Add;
+
+/// Spaces added for each level of nesting, as in `{:#?}`.
+pub const INDENT: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Doc {
+    Nil,
+    Text(String),
+    /// A space, or a line break when the enclosing group does not fit.
+    Line,
+    /// Nothing, or a line break when the enclosing group does not fit.
+    SoftLine,
+    /// Text only printed when the enclosing group is broken, e.g. a trailing comma.
+    IfBreak(String),
+    Concat(Vec<Doc>),
+    Nest(usize, Box<Doc>),
+    Group(Box<Doc>),
+}
+
+impl Doc {
+    pub fn text(text: impl Into<String>) -> Doc {
+        Doc::Text(text.into())
+    }
+
+    pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Doc {
+        Doc::Concat(docs.into_iter().collect())
+    }
+
+    /// Indents the lines which start inside this document.
+    pub fn nest(self, indent: usize) -> Doc {
+        Doc::Nest(indent, Box::new(self))
+    }
+
+    /// Lays this document out flat when it fits, broken otherwise.
+    pub fn group(self) -> Doc {
+        Doc::Group(Box::new(self))
+    }
+
+    /// The document for the `{:?}` output of `value`.
+    pub fn from_debug<T: Debug + ?Sized>(value: &T) -> Doc {
+        let debug = format!("{:?}", value);
+        let chars: Vec<char> = debug.chars().collect();
+        let mut parser = DebugParser { chars, index: 0 };
+        let doc = parser.item(None);
+        // anything the parser could not make sense of is kept as it is
+        let rest: String = parser.chars[parser.index..].iter().collect();
+        doc + Doc::Text(rest)
+    }
+
+    pub fn render(&self, width: usize) -> String {
+        let mut rendered = String::new();
+        let mut column = 0;
+        let mut stack = vec![(0, Mode::Break, self)];
+
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Nil => {}
+                Doc::Text(text) => {
+                    rendered.push_str(text);
+                    column += text.chars().count();
+                }
+                Doc::Line | Doc::SoftLine if mode == Mode::Break => {
+                    rendered.push('\n');
+                    rendered.extend(std::iter::repeat_n(' ', indent));
+                    column = indent;
+                }
+                Doc::Line => {
+                    rendered.push(' ');
+                    column += 1;
+                }
+                Doc::SoftLine => {}
+                Doc::IfBreak(text) => {
+                    if mode == Mode::Break {
+                        rendered.push_str(text);
+                        column += text.chars().count();
+                    }
+                }
+                Doc::Concat(docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+                Doc::Nest(extra, doc) => stack.push((indent + extra, mode, doc)),
+                Doc::Group(doc) => {
+                    let flat = mode == Mode::Flat
+                        || fits(
+                            width as isize - column as isize,
+                            (indent, Mode::Flat, doc),
+                            &stack,
+                        );
+                    let mode = if flat { Mode::Flat } else { Mode::Break };
+                    stack.push((indent, mode, doc));
+                }
+            }
+        }
+        rendered
+    }
+}
+
+impl Add for Doc {
+    type Output = Doc;
+
+    fn add(self, other: Doc) -> Doc {
+        match self {
+            Doc::Concat(mut docs) => {
+                docs.push(other);
+                Doc::Concat(docs)
+            }
+            doc => Doc::Concat(vec![doc, other]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+type Command<'d> = (usize, Mode, &'d Doc);
+
+/// Whether `next` fits flat in `remaining` columns, together with whatever
+/// follows it on the stack up to the next line break.
+fn fits(mut remaining: isize, next: Command, rest: &[Command]) -> bool {
+    let mut pending = vec![next];
+    let mut rest = rest.iter().rev();
+
+    while remaining >= 0 {
+        let (indent, mode, doc) = match pending.pop() {
+            Some(command) => command,
+            None => match rest.next() {
+                Some(&command) => command,
+                None => return true,
+            },
+        };
+        match doc {
+            Doc::Nil => {}
+            Doc::Text(text) => remaining -= text.chars().count() as isize,
+            Doc::Line | Doc::SoftLine if mode == Mode::Break => return true,
+            Doc::Line => remaining -= 1,
+            Doc::SoftLine => {}
+            Doc::IfBreak(text) => {
+                if mode == Mode::Break {
+                    remaining -= text.chars().count() as isize;
+                }
+            }
+            Doc::Concat(docs) => pending.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            Doc::Nest(extra, doc) => pending.push((indent + extra, mode, doc)),
+            Doc::Group(doc) => pending.push((indent, mode, doc)),
+        }
+    }
+    false
+}
+
+/// Splits `{:?}` output into bracketed groups of comma separated items.
+struct DebugParser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl DebugParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    /// Copies a string or char literal, whose contents may contain brackets and commas.
+    fn literal(&mut self, text: &mut String) {
+        let quote = self.chars[self.index];
+        text.push(quote);
+        self.index += 1;
+        while let Some(c) = self.peek() {
+            text.push(c);
+            self.index += 1;
+            if c == '\\' {
+                if let Some(escaped) = self.peek() {
+                    text.push(escaped);
+                    self.index += 1;
+                }
+            } else if c == quote {
+                break;
+            }
+        }
+    }
+
+    /// One item, up to a `,` or the closing bracket of the enclosing group.
+    fn item(&mut self, close: Option<char>) -> Doc {
+        let mut docs = Vec::new();
+        let mut text = String::new();
+
+        while let Some(c) = self.peek() {
+            match c {
+                '"' | '\'' => self.literal(&mut text),
+                '(' | '[' | '{' => {
+                    // `Name { x: 1 }` has spaces inside the braces, maps and sets do
+                    // not, even as a field or a map value after `: `
+                    let spaced = c == '{'
+                        && text.ends_with(' ')
+                        && text
+                            .trim_end()
+                            .ends_with(|c: char| c.is_alphanumeric() || c == '_');
+                    docs.push(Doc::Text(std::mem::take(&mut text)));
+                    self.index += 1;
+                    docs.push(self.group(c, spaced));
+                }
+                ',' if close.is_some() => break,
+                '}' if close == Some('}') => {
+                    // the space before `}` belongs to the braces, not the last field
+                    if text.ends_with(' ') {
+                        text.pop();
+                    }
+                    break;
+                }
+                c if Some(c) == close => break,
+                ')' | ']' | '}' if close.is_some() => break,
+                c => {
+                    text.push(c);
+                    self.index += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            docs.push(Doc::Text(text));
+        }
+        Doc::concat(docs)
+    }
+
+    /// The items after an opening bracket, up to and including the closing one.
+    fn group(&mut self, open: char, spaced: bool) -> Doc {
+        let close = match open {
+            '(' => ')',
+            '[' => ']',
+            _ => '}',
+        };
+        let mut items = Vec::new();
+        let mut trailing_comma = false;
+
+        loop {
+            if spaced && self.peek() == Some(' ') {
+                self.index += 1;
+            }
+            if self.peek() == Some(close) || self.peek().is_none() {
+                break;
+            }
+            items.push(self.item(Some(close)));
+            if self.peek() == Some(',') {
+                self.index += 1;
+                if self.peek() == Some(' ') {
+                    self.index += 1;
+                } else {
+                    // `(1,)`, the comma of a one element tuple
+                    trailing_comma = true;
+                }
+            } else if self.peek() != Some(close) {
+                // a stray closing bracket, keep it as text
+                break;
+            }
+        }
+        self.index += 1;
+
+        if items.is_empty() {
+            let inner = if spaced { " " } else { "" };
+            return Doc::text(format!("{}{}{}", open, inner, close));
+        }
+
+        let line = || if spaced { Doc::Line } else { Doc::SoftLine };
+        let mut body = vec![line()];
+        let count = items.len();
+        for (index, item) in items.into_iter().enumerate() {
+            body.push(item);
+            if index + 1 < count {
+                body.push(Doc::text(","));
+                body.push(Doc::Line);
+            }
+        }
+        body.push(if trailing_comma {
+            Doc::text(",")
+        } else {
+            Doc::IfBreak(String::from(","))
+        });
+
+        (Doc::text(open) + Doc::concat(body).nest(INDENT) + line() + Doc::text(close)).group()
+    }
+}
+
+/// Lays out the `{:?}` output of `value` in `width` columns.
+pub fn pretty<T: Debug + ?Sized>(value: &T, width: usize) -> String {
+    Doc::from_debug(value).render(width)
+}
+
+/// `Display` adapter for `pretty`, e.g. `writeln!(out, "{}", Pretty(&value, 40))`.
+pub struct Pretty<'a, T: ?Sized>(pub &'a T, pub usize);
+
+impl<T: Debug + ?Sized> Display for Pretty<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&pretty(self.0, self.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct NamedBorrow<'a> {
+        x: &'a i32,
+        y: &'a i32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Pair(Box<i32>, Box<i32>);
+
+    #[derive(Debug)]
+    struct Unit;
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Nested<'a> {
+        borrows: Vec<NamedBorrow<'a>>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Sets {
+        set: BTreeSet<i32>,
+        maps: Vec<BTreeMap<i32, i32>>,
+    }
+
+    #[test]
+    fn flat_output_matches_debug() {
+        let map: BTreeMap<&str, Vec<i32>> = [("a", vec![1, 2]), ("b", vec![])].into();
+        let nested: BTreeMap<&str, BTreeMap<i32, BTreeSet<i32>>> =
+            [("a", [(1, [2, 3].into())].into()), ("b", BTreeMap::new())].into();
+        let values: Vec<Box<dyn Debug>> = vec![
+            Box::new(((1, 2, 3), (4, 5, 6))),
+            Box::new(NamedBorrow { x: &18, y: &15 }),
+            Box::new(Pair(Box::new(1), Box::new(2))),
+            Box::new(map),
+            Box::new((1,)),
+            Box::new(("a, (b", 'c', '\'', "\"}")),
+            Box::new(Some(Unit)),
+            Box::new(Nested {
+                borrows: vec![NamedBorrow { x: &1, y: &2 }],
+            }),
+            Box::new(Vec::<i32>::new()),
+            Box::new(1.5),
+            Box::new(Sets {
+                set: [1, 2].into(),
+                maps: vec![[(1, 2)].into(), BTreeMap::new()],
+            }),
+            Box::new(nested),
+        ];
+        for value in values {
+            assert_eq!(pretty(&value, 200), format!("{:?}", value));
+        }
+    }
+
+    #[test]
+    fn breaks_only_what_does_not_fit() {
+        let tuple_of_tuples = ((1, 2, 3), (4, 5, 6));
+        assert_eq!(
+            pretty(&tuple_of_tuples, 20),
+            "(\n    (1, 2, 3),\n    (4, 5, 6),\n)"
+        );
+        assert_eq!(
+            pretty(&tuple_of_tuples, 8),
+            "(\n    (\n        1,\n        2,\n        3,\n    ),\n    (\n        4,\n        5,\n        6,\n    ),\n)"
+        );
+
+        let borrows = vec![NamedBorrow { x: &18, y: &15 }, NamedBorrow { x: &4, y: &2 }];
+        assert_eq!(
+            pretty(&borrows, 40),
+            "[\n    NamedBorrow { x: 18, y: 15 },\n    NamedBorrow { x: 4, y: 2 },\n]"
+        );
+        assert_eq!(
+            format!("{}", Pretty(&NamedBorrow { x: &1, y: &2 }, 10)),
+            "NamedBorrow {\n    x: 1,\n    y: 2,\n}"
+        );
+        let nested = Nested {
+            borrows: vec![NamedBorrow { x: &1, y: &2 }],
+        };
+        assert_eq!(
+            pretty(&nested, 40),
+            "Nested {\n    borrows: [\n        NamedBorrow { x: 1, y: 2 },\n    ],\n}"
+        );
+        let sets = Sets {
+            set: [1, 2].into(),
+            maps: vec![[(1, 2)].into()],
+        };
+        assert_eq!(
+            pretty(&sets, 20),
+            "Sets {\n    set: {1, 2},\n    maps: [{1: 2}],\n}"
+        );
+        assert_eq!(
+            pretty(&sets, 12),
+            "Sets {\n    set: {\n        1,\n        2,\n    },\n    maps: [\n        {\n            1: 2,\n        },\n    ],\n}"
+        );
+    }
+
+    #[test]
+    fn documents() {
+        let call = |args: Vec<&str>| {
+            let args = args.into_iter().map(Doc::text);
+            let mut body = Doc::SoftLine;
+            for (index, arg) in args.enumerate() {
+                if index > 0 {
+                    body = body + Doc::text(",") + Doc::Line;
+                }
+                body = body + arg;
+            }
+            (Doc::text("f(") + body.nest(2) + Doc::SoftLine + Doc::text(")")).group()
+        };
+        let doc = call(vec!["alpha", "beta"]);
+        assert_eq!(doc.render(80), "f(alpha, beta)");
+        assert_eq!(doc.render(10), "f(\n  alpha,\n  beta\n)");
+    }
+}
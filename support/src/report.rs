@@ -0,0 +1,415 @@
+/* Panic-isolating runner */
+// `--keep-going` runs every selected part even when some of them panic on
+// purpose, records how each one ended and prints a summary afterwards;
+// `--json` does the same but captures the output of the parts and prints
+// a single JSON document for CI
+
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use crate::parts::{Part, PANIC_EXIT_CODE};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanicInfo {
+    pub message: String,
+    /// `file:line:column` of the panic, when the hook reported one.
+    pub location: Option<(String, u32, u32)>,
+}
+
+impl PanicInfo {
+    fn from_hook(info: &PanicHookInfo) -> PanicInfo {
+        let payload = info.payload();
+        let message = match payload.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match payload.downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => String::from("Box<dyn Any>"),
+            },
+        };
+        PanicInfo {
+            message,
+            location: info.location().map(|location| {
+                (
+                    location.file().to_owned(),
+                    location.line(),
+                    location.column(),
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Passed,
+    /// The part could not write its output.
+    Failed(String),
+    Panicked(PanicInfo),
+}
+
+impl Outcome {
+    fn status(&self) -> &'static str {
+        match self {
+            Outcome::Passed => "passed",
+            Outcome::Failed(_) => "failed",
+            Outcome::Panicked(_) => "panicked",
+        }
+    }
+}
+
+pub struct PartReport<'p> {
+    pub part: &'p Part,
+    pub outcome: Outcome,
+    /// What the part wrote, when its output was captured instead of printed.
+    pub output: Option<String>,
+}
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<PanicInfo>> = const { RefCell::new(None) };
+}
+
+fn take_last_panic() -> PanicInfo {
+    LAST_PANIC
+        .with(|last| last.borrow_mut().take())
+        .unwrap_or_else(|| PanicInfo {
+            message: String::from("unknown panic"),
+            location: None,
+        })
+}
+
+type Hook = Box<dyn Fn(&PanicHookInfo) + Sync + Send + 'static>;
+
+// reinstalls the hook that was there before, on every way out of
+// `run_isolated`
+struct RestoreHook(Option<Arc<Hook>>);
+
+impl Drop for RestoreHook {
+    fn drop(&mut self) {
+        // setting a hook while panicking would abort
+        let Some(previous) = self.0.take().filter(|_| !std::thread::panicking()) else {
+            return;
+        };
+        // dropping the capturing hook releases its share of the previous one
+        drop(panic::take_hook());
+        match Arc::try_unwrap(previous) {
+            Ok(previous) => panic::set_hook(previous),
+            Err(previous) => panic::set_hook(Box::new(move |info| previous(info))),
+        }
+    }
+}
+
+/// Runs every part on its own, catching panics instead of stopping at the first one.
+///
+/// With `capture` the output of each part goes into its report, otherwise it is
+/// written to `out` under a `--- partN ---` header. The panic hook is replaced while
+/// the parts run, and put back afterwards; the previous hook's message is only printed
+/// when the output is not captured.
+pub fn run_isolated<'p>(
+    parts: &[&'p Part],
+    out: &mut dyn Write,
+    capture: bool,
+) -> io::Result<Vec<PartReport<'p>>> {
+    let previous = Arc::new(panic::take_hook());
+    let restore = RestoreHook(Some(Arc::clone(&previous)));
+    panic::set_hook(Box::new(move |info| {
+        LAST_PANIC.with(|last| *last.borrow_mut() = Some(PanicInfo::from_hook(info)));
+        if !capture {
+            previous(info);
+        }
+    }));
+
+    let mut reports = Vec::with_capacity(parts.len());
+    for part in parts {
+        let mut buffer = Vec::new();
+        let sink: &mut dyn Write = if capture {
+            &mut buffer
+        } else {
+            writeln!(out, "--- {}: {} ---", part.name, part.description)?;
+            &mut *out
+        };
+
+        let outcome = match panic::catch_unwind(AssertUnwindSafe(|| (part.run)(sink))) {
+            Ok(Ok(())) => Outcome::Passed,
+            Ok(Err(e)) => Outcome::Failed(e.to_string()),
+            Err(_) => Outcome::Panicked(take_last_panic()),
+        };
+        let output = capture.then(|| String::from_utf8_lossy(&buffer).into_owned());
+        reports.push(PartReport {
+            part,
+            outcome,
+            output,
+        });
+    }
+
+    // back to the previous hook
+    drop(restore);
+    Ok(reports)
+}
+
+fn count(reports: &[PartReport], status: &str) -> usize {
+    reports
+        .iter()
+        .filter(|report| report.outcome.status() == status)
+        .count()
+}
+
+/// One line per part below a count of each outcome.
+pub fn write_summary(reports: &[PartReport], out: &mut dyn Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "--- summary: {} passed, {} panicked, {} failed ---",
+        count(reports, "passed"),
+        count(reports, "panicked"),
+        count(reports, "failed")
+    )?;
+
+    let width = reports
+        .iter()
+        .map(|report| report.part.name.len())
+        .max()
+        .unwrap_or(0);
+    for report in reports {
+        let status = report.outcome.status();
+        match &report.outcome {
+            Outcome::Passed => writeln!(out, "{:<width$}  {}", report.part.name, status)?,
+            Outcome::Failed(error) => writeln!(
+                out,
+                "{:<width$}  {:<8}  could not write its output: {}",
+                report.part.name, status, error
+            )?,
+            Outcome::Panicked(info) => {
+                let location = match &info.location {
+                    Some((file, line, column)) => format!("{}:{}:{}", file, line, column),
+                    None => String::from("unknown location"),
+                };
+                writeln!(
+                    out,
+                    "{:<width$}  {:<8}  {}: {}",
+                    report.part.name, status, location, info.message
+                )?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Quotes and escapes a string as a JSON string literal.
+pub fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The reports as a JSON object with the totals and one entry per part.
+pub fn write_json(reports: &[PartReport], out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"passed\": {},", count(reports, "passed"))?;
+    writeln!(out, "  \"panicked\": {},", count(reports, "panicked"))?;
+    writeln!(out, "  \"failed\": {},", count(reports, "failed"))?;
+    writeln!(out, "  \"parts\": [")?;
+    for (index, report) in reports.iter().enumerate() {
+        let mut entry = format!(
+            "{{\"name\": {}, \"description\": {}, \"status\": {}",
+            json_string(report.part.name),
+            json_string(report.part.description),
+            json_string(report.outcome.status())
+        );
+        if let Some(output) = &report.output {
+            let _ = write!(entry, ", \"output\": {}", json_string(output));
+        }
+        match &report.outcome {
+            Outcome::Passed => {}
+            Outcome::Failed(error) => {
+                let _ = write!(entry, ", \"error\": {}", json_string(error));
+            }
+            Outcome::Panicked(info) => {
+                let _ = write!(
+                    entry,
+                    ", \"panic\": {{\"message\": {}",
+                    json_string(&info.message)
+                );
+                if let Some((file, line, column)) = &info.location {
+                    let _ = write!(
+                        entry,
+                        ", \"file\": {}, \"line\": {}, \"column\": {}",
+                        json_string(file),
+                        line,
+                        column
+                    );
+                }
+                entry.push('}');
+            }
+        }
+        entry.push('}');
+        let separator = if index + 1 < reports.len() { "," } else { "" };
+        writeln!(out, "    {}{}", entry, separator)?;
+    }
+    writeln!(out, "  ]")?;
+    writeln!(out, "}}")
+}
+
+/// Entry point for `--keep-going` and `--json`.
+pub fn run(parts: &[&Part], format: Format) -> ExitCode {
+    let mut stdout = io::stdout();
+    let written = run_isolated(parts, &mut stdout, format == Format::Json).and_then(|reports| {
+        match format {
+            Format::Text => write_summary(&reports, &mut stdout)?,
+            Format::Json => write_json(&reports, &mut stdout)?,
+        }
+        Ok(reports)
+    });
+
+    let reports = match written {
+        Ok(reports) => reports,
+        Err(e) => {
+            eprintln!("error: could not write the report: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if count(&reports, "panicked") > 0 {
+        ExitCode::from(PANIC_EXIT_CODE)
+    } else if count(&reports, "failed") > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn greet(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "hello")
+    }
+
+    fn boom(out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "about to panic")?;
+        panic!("boom {}", 42);
+    }
+
+    fn parts() -> [Part; 3] {
+        crate::parts![greet => "says hello", boom => "panics", greet => "says hello again"]
+    }
+
+    // the panic hook is global, so the tests that replace it take turns
+    static HOOK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn keeps_going_after_a_panic() {
+        let _hook = HOOK.lock().unwrap_or_else(|e| e.into_inner());
+        let parts = parts();
+        let selected: Vec<&Part> = parts.iter().collect();
+        let mut out = Vec::new();
+        let reports = run_isolated(&selected, &mut out, true).unwrap();
+
+        assert!(out.is_empty());
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0].outcome, Outcome::Passed);
+        assert_eq!(reports[0].output.as_deref(), Some("hello\n"));
+        match &reports[1].outcome {
+            Outcome::Panicked(info) => {
+                assert_eq!(info.message, "boom 42");
+                let (file, line, _) = info.location.as_ref().unwrap();
+                assert!(file.ends_with("report.rs"));
+                assert!(*line > 0);
+            }
+            other => panic!("expected a panic, got {:?}", other),
+        }
+        assert_eq!(reports[1].output.as_deref(), Some("about to panic\n"));
+        assert_eq!(reports[2].outcome, Outcome::Passed);
+    }
+
+    #[test]
+    fn the_previous_hook_is_put_back() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        let _hook = HOOK.lock().unwrap_or_else(|e| e.into_inner());
+        panic::set_hook(Box::new(|_| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+        }));
+        let parts = parts();
+        let selected: Vec<&Part> = parts.iter().collect();
+        run_isolated(&selected, &mut Vec::new(), true).unwrap();
+        // a writer that fails on the first header, returning early
+        let mut full: &mut [u8] = &mut [];
+        assert!(run_isolated(&selected, &mut full, false).is_err());
+
+        let before = CALLS.load(Ordering::SeqCst);
+        assert!(panic::catch_unwind(|| panic!("after")).is_err());
+        drop(panic::take_hook());
+        assert_eq!(CALLS.load(Ordering::SeqCst), before + 1);
+    }
+
+    #[test]
+    fn summary_and_json() {
+        let parts = parts();
+        let reports = vec![
+            PartReport {
+                part: &parts[0],
+                outcome: Outcome::Passed,
+                output: Some(String::from("hello\n")),
+            },
+            PartReport {
+                part: &parts[1],
+                outcome: Outcome::Panicked(PanicInfo {
+                    message: String::from("boom \"42\""),
+                    location: Some((String::from("src/main.rs"), 3, 5)),
+                }),
+                output: None,
+            },
+        ];
+
+        let mut summary = Vec::new();
+        write_summary(&reports, &mut summary).unwrap();
+        assert_eq!(
+            String::from_utf8(summary).unwrap(),
+            "--- summary: 1 passed, 1 panicked, 0 failed ---\n\
+             greet  passed\n\
+             boom   panicked  src/main.rs:3:5: boom \"42\"\n"
+        );
+
+        let mut json = Vec::new();
+        write_json(&reports, &mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.contains("\"passed\": 1,\n  \"panicked\": 1,"));
+        assert!(json.contains(
+            "{\"name\": \"greet\", \"description\": \"says hello\", \
+             \"status\": \"passed\", \"output\": \"hello\\n\"},\n"
+        ));
+        assert!(json.contains(
+            "\"panic\": {\"message\": \"boom \\\"42\\\"\", \
+             \"file\": \"src/main.rs\", \"line\": 3, \"column\": 5}}\n  ]"
+        ));
+    }
+
+    #[test]
+    fn escapes_json_strings() {
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+    }
+}
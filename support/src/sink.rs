@@ -0,0 +1,94 @@
+/* Output sinks */
+// parts write to a caller-supplied `&mut dyn io::Write`: the binaries pass
+// stdout, tests pass a `Vec<u8>`, and `FmtWriter` adapts any `fmt::Write`
+// such as a `String`
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io::{self, Write};
+use std::str;
+
+/// Adapts a `fmt::Write` (e.g. `String` or a `Formatter`) into an `io::Write` sink.
+pub struct FmtWriter<W: fmt::Write>(pub W);
+
+impl<W: fmt::Write> FmtWriter<W> {
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W: fmt::Write> Write for FmtWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text =
+            str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.0
+            .write_str(text)
+            .map_err(|_| io::Error::other("formatter error"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A sink shared by reference, for code which cannot take `out` as a parameter:
+/// destructors, operator implementations and closures handed to combinators.
+///
+/// `writeln!(sink, ...)` works through `&SharedSink` and cannot fail; the first
+/// write error is kept and returned by `finish`.
+pub struct SharedSink<'a> {
+    out: RefCell<&'a mut dyn Write>,
+    error: Cell<Option<io::Error>>,
+}
+
+impl<'a> SharedSink<'a> {
+    pub fn new(out: &'a mut dyn Write) -> SharedSink<'a> {
+        SharedSink {
+            out: RefCell::new(out),
+            error: Cell::new(None),
+        }
+    }
+
+    pub fn write_fmt(&self, args: fmt::Arguments) {
+        if let Err(e) = self.out.borrow_mut().write_fmt(args) {
+            let first = self.error.take().unwrap_or(e);
+            self.error.set(Some(first));
+        }
+    }
+
+    /// Reports the first write error, if any.
+    pub fn finish(self) -> io::Result<()> {
+        match self.error.into_inner() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fmt_writer_collects_into_a_string() {
+        let mut out = FmtWriter(String::new());
+        writeln!(out, "{} + {} = {}", 1, 2, 1 + 2).unwrap();
+        assert_eq!(out.into_inner(), "1 + 2 = 3\n");
+    }
+
+    #[test]
+    fn shared_sink_keeps_the_first_error() {
+        let mut buffer = Vec::new();
+        let sink = SharedSink::new(&mut buffer);
+        let name = "a";
+        writeln!(sink, "dropped {}", name);
+        sink.finish().unwrap();
+        assert_eq!(buffer, b"dropped a\n");
+
+        let mut full: &mut [u8] = &mut [0; 2];
+        let sink = SharedSink::new(&mut full);
+        writeln!(sink, "too long");
+        assert_eq!(sink.finish().unwrap_err().kind(), io::ErrorKind::WriteZero);
+    }
+}
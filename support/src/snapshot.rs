@@ -0,0 +1,356 @@
+/* Golden-output snapshots */
+// each chapter has an integration test that runs every part of the chapter
+// binary on its own, captures stdout and stderr, and compares them with the
+// checked-in `tests/snapshots/partN.snap` files
+
+// run the tests with `UPDATE_SNAPSHOTS=1` to bless new output
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Environment variable which switches the harness from checking to writing snapshots.
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Lines of unchanged output shown around each change in a diff.
+const CONTEXT: usize = 3;
+
+pub struct Suite {
+    bin: PathBuf,
+    dir: PathBuf,
+    unordered: Vec<String>,
+    skipped: BTreeMap<String, String>,
+}
+
+impl Suite {
+    /// `bin` is the chapter binary, usually `env!("CARGO_BIN_EXE_<name>")`,
+    /// and `dir` the directory which holds its `.snap` files.
+    pub fn new(bin: impl Into<PathBuf>, dir: impl Into<PathBuf>) -> Suite {
+        Suite {
+            bin: bin.into(),
+            dir: dir.into(),
+            unordered: Vec::new(),
+            skipped: BTreeMap::new(),
+        }
+    }
+
+    /// Compare the lines of a part regardless of their order, e.g. for `HashMap` iteration.
+    pub fn unordered(mut self, id: &str) -> Suite {
+        self.unordered.push(id.to_owned());
+        self
+    }
+
+    /// Leave a part out of the suite; the reason is printed whenever the suite runs.
+    pub fn skip(mut self, id: &str, reason: &str) -> Suite {
+        self.skipped.insert(id.to_owned(), reason.to_owned());
+        self
+    }
+
+    fn part_ids(&self) -> Vec<String> {
+        let output = Command::new(&self.bin)
+            .arg("--list")
+            .output()
+            .unwrap_or_else(|e| panic!("cannot run {}: {}", self.bin.display(), e));
+        assert!(
+            output.status.success(),
+            "`--list` failed for {}",
+            self.bin.display()
+        );
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_owned)
+            .collect()
+    }
+
+    fn capture(&self, id: &str) -> String {
+        let output = Command::new(&self.bin)
+            .args(["--part", id])
+            .env("RUST_BACKTRACE", "0")
+            .output()
+            .unwrap_or_else(|e| panic!("cannot run {}: {}", self.bin.display(), e));
+
+        let mut stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = strip_thread_ids(&String::from_utf8_lossy(&output.stderr));
+        let mut stderr = strip_panic_locations(&stderr);
+        if self.unordered.iter().any(|unordered| unordered == id) {
+            stdout = sorted_lines(&stdout);
+            stderr = sorted_lines(&stderr);
+        }
+        render(id, output.status.code(), &stdout, &stderr)
+    }
+
+    /// Runs every part and panics with a diff for each one which does not match its snapshot.
+    pub fn check(self) {
+        let update = env::var_os(UPDATE_VAR).is_some_and(|value| value != "0");
+        let ids = self.part_ids();
+        let mut failures = String::new();
+
+        if update {
+            fs::create_dir_all(&self.dir).expect("cannot create the snapshot directory");
+        }
+
+        for id in &ids {
+            if let Some(reason) = self.skipped.get(id) {
+                eprintln!("skipping part{}: {}", id, reason);
+                continue;
+            }
+
+            let path = self.dir.join(format!("part{}.snap", id));
+            let actual = self.capture(id);
+
+            if update {
+                fs::write(&path, &actual).expect("cannot write snapshot");
+                continue;
+            }
+            match fs::read_to_string(&path) {
+                Ok(expected) if expected == actual => {}
+                Ok(expected) => {
+                    let _ = writeln!(failures, "part{} does not match {}", id, path.display());
+                    failures.push_str(&diff(&expected, &actual));
+                }
+                Err(_) => {
+                    let _ = writeln!(failures, "part{} has no snapshot at {}", id, path.display());
+                }
+            }
+        }
+
+        for stale in stale_snapshots(&self.dir, &ids) {
+            if update {
+                fs::remove_file(&stale).expect("cannot remove stale snapshot");
+            } else {
+                let _ = writeln!(failures, "{} belongs to no part", stale.display());
+            }
+        }
+
+        if !failures.is_empty() {
+            panic!(
+                "snapshots differ, rerun with {}=1 to bless the new output\n\n{}",
+                UPDATE_VAR, failures
+            );
+        }
+    }
+}
+
+fn render(id: &str, code: Option<i32>, stdout: &str, stderr: &str) -> String {
+    let status = code.map_or_else(|| String::from("signal"), |code| code.to_string());
+    let mut snapshot = format!(
+        "part: {}\nstatus: {}\n--- stdout ---\n{}",
+        id, status, stdout
+    );
+    if !snapshot.ends_with('\n') {
+        snapshot.push('\n');
+    }
+    snapshot.push_str("--- stderr ---\n");
+    snapshot.push_str(stderr);
+    if !snapshot.ends_with('\n') {
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+/// Panic messages read `thread 'main' (1234) panicked at ...`, where the id changes on every run.
+fn strip_thread_ids(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let stripped = line.strip_prefix("thread '").and_then(|rest| {
+            let name_end = rest.find("' (")?;
+            let (name, rest) = rest.split_at(name_end);
+            let id_end = rest.find(") ")?;
+            let id = &rest["' (".len()..id_end];
+            id.chars()
+                .all(|c| c.is_ascii_digit())
+                .then(|| format!("thread '{}' {}", name, &rest[id_end + ") ".len()..]))
+        });
+        match stripped {
+            Some(line) => out.push_str(&line),
+            None => out.push_str(line),
+        }
+    }
+    out
+}
+
+/// `panicked at src/main.rs:14:9:` becomes `panicked at src/main.rs:<line>:<col>:`,
+/// so that editing code above a panic does not change the snapshot.
+fn strip_panic_locations(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let panic = line.starts_with("thread '") && line.contains("' panicked at ");
+        let stripped = panic.then_some(line).and_then(|line| {
+            let location = line.trim_end().strip_suffix(':')?;
+            let (location, col) = location.rsplit_once(':')?;
+            let (path, row) = location.rsplit_once(':')?;
+            let numeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+            (numeric(row) && numeric(col)).then(|| {
+                let newline = &line[line.trim_end().len()..];
+                format!("{}:<line>:<col>:{}", path, newline)
+            })
+        });
+        match stripped {
+            Some(line) => out.push_str(&line),
+            None => out.push_str(line),
+        }
+    }
+    out
+}
+
+fn sorted_lines(text: &str) -> String {
+    let mut lines: Vec<&str> = text.lines().collect();
+    lines.sort_unstable();
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn stale_snapshots(dir: &Path, ids: &[String]) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut stale: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            match name
+                .strip_prefix("part")
+                .and_then(|name| name.strip_suffix(".snap"))
+            {
+                Some(id) => !ids.iter().any(|known| known == id),
+                None => false,
+            }
+        })
+        .collect();
+    stale.sort();
+    stale
+}
+
+#[derive(Debug, PartialEq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line diff from the longest common subsequence of both texts.
+fn diff_lines<'a>(expected: &'a str, actual: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] > lcs[i + 1][j]) {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        } else {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        }
+    }
+    lines
+}
+
+/// Renders the changes between two texts with a few lines of context around each one.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let lines = diff_lines(expected, actual);
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Line::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut out = String::new();
+    let mut last_shown = None;
+    for (index, line) in lines.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|&change| index + CONTEXT >= change && index <= change + CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|last| last + 1 != index) {
+            out.push_str("   ...\n");
+        }
+        last_shown = Some(index);
+        let _ = match line {
+            Line::Same(text) => writeln!(out, "   {}", text),
+            Line::Removed(text) => writeln!(out, " - {}", text),
+            Line::Added(text) => writeln!(out, " + {}", text),
+        };
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let lines = diff_lines("a\nb\nc\n", "a\nx\nc\nd\n");
+        assert_eq!(
+            lines,
+            vec![
+                Line::Same("a"),
+                Line::Removed("b"),
+                Line::Added("x"),
+                Line::Same("c"),
+                Line::Added("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_elides_distant_context() {
+        let expected: String = (0..20).map(|n| format!("{}\n", n)).collect();
+        let actual = expected.replace("10\n", "ten\n");
+        assert_eq!(
+            diff(&expected, &actual),
+            "   7\n   8\n   9\n - 10\n + ten\n   11\n   12\n   13\n"
+        );
+        assert_eq!(diff("same\n", "same\n"), "");
+    }
+
+    #[test]
+    fn render_sections() {
+        assert_eq!(
+            render("2", Some(101), "out", ""),
+            "part: 2\nstatus: 101\n--- stdout ---\nout\n--- stderr ---\n"
+        );
+        assert_eq!(sorted_lines("b\na\n"), "a\nb\n");
+        assert_eq!(
+            strip_thread_ids("\nthread 'main' (9671) panicked at src/main.rs:13:9:\nboom\n"),
+            "\nthread 'main' panicked at src/main.rs:13:9:\nboom\n"
+        );
+        assert_eq!(
+            strip_panic_locations("\nthread 'main' panicked at src/main.rs:13:9:\nboom\n"),
+            "\nthread 'main' panicked at src/main.rs:<line>:<col>:\nboom\n"
+        );
+        // only the panic line, and only a location that ends in two numbers
+        assert_eq!(
+            strip_panic_locations("at src/main.rs:1:2:\nthread 'a' panicked at x.rs:b:\n"),
+            "at src/main.rs:1:2:\nthread 'a' panicked at x.rs:b:\n"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+/* Text tables */
+// column widths are worked out from the cells, which are any `Display`
+// values; each column has its own alignment, fill character and optional
+// maximum width, and the table prints through `Display` like any value
+
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Border {
+    /// Columns separated by two spaces, no rules.
+    None,
+    /// `+`, `-` and `|`.
+    Ascii,
+    /// Box-drawing characters.
+    Unicode,
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    header: String,
+    align: Align,
+    fill: char,
+    max_width: Option<usize>,
+}
+
+impl Column {
+    fn new(header: String) -> Column {
+        Column {
+            header,
+            align: Align::Left,
+            fill: ' ',
+            max_width: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    border: Border,
+}
+
+impl Table {
+    pub fn new<I>(headers: I) -> Table
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        Table {
+            columns: headers
+                .into_iter()
+                .map(|header| Column::new(header.to_string()))
+                .collect(),
+            rows: Vec::new(),
+            border: Border::Unicode,
+        }
+    }
+
+    pub fn border(mut self, border: Border) -> Table {
+        self.border = border;
+        self
+    }
+
+    /// Alignment of a column, headers included. Columns are left aligned by default.
+    pub fn align(mut self, column: usize, align: Align) -> Table {
+        self.column(column).align = align;
+        self
+    }
+
+    /// Character used to pad the cells of a column, like `=` in `{:=^21}`.
+    pub fn fill(mut self, column: usize, fill: char) -> Table {
+        self.column(column).fill = fill;
+        self
+    }
+
+    /// Cells wider than `width` characters are cut and end with an ellipsis.
+    pub fn max_width(mut self, column: usize, width: usize) -> Table {
+        self.column(column).max_width = Some(width);
+        self
+    }
+
+    /// Adds a row. Missing cells are left empty; extra cells add unnamed columns.
+    pub fn row<I>(&mut self, cells: I) -> &mut Table
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let row: Vec<String> = cells.into_iter().map(|cell| cell.to_string()).collect();
+        if row.len() > self.columns.len() {
+            self.column(row.len() - 1);
+        }
+        self.rows.push(row);
+        self
+    }
+
+    fn column(&mut self, index: usize) -> &mut Column {
+        while self.columns.len() <= index {
+            self.columns.push(Column::new(String::new()));
+        }
+        &mut self.columns[index]
+    }
+
+    fn ellipsis(&self) -> &'static str {
+        match self.border {
+            Border::Unicode => "…",
+            Border::None | Border::Ascii => "...",
+        }
+    }
+
+    fn truncate(&self, column: &Column, text: &str) -> String {
+        let len = text.chars().count();
+        match column.max_width {
+            Some(max) if len > max => {
+                let ellipsis = self.ellipsis();
+                let keep = max.saturating_sub(ellipsis.chars().count());
+                let mut cut: String = text.chars().take(keep).collect();
+                cut.push_str(ellipsis);
+                cut.chars().take(max).collect()
+            }
+            _ => text.to_owned(),
+        }
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let cells = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(index))
+                    .chain(std::iter::once(&column.header));
+                cells
+                    .map(|cell| self.truncate(column, cell).chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+}
+
+fn pad(text: &str, width: usize, align: Align, fill: char) -> String {
+    let padding = width.saturating_sub(text.chars().count());
+    let (before, after) = match align {
+        Align::Left => (0, padding),
+        Align::Center => (padding / 2, padding - padding / 2),
+        Align::Right => (padding, 0),
+    };
+    let mut padded: String = std::iter::repeat_n(fill, before).collect();
+    padded.push_str(text);
+    padded.extend(std::iter::repeat_n(fill, after));
+    padded
+}
+
+// left, horizontal, junction and right pieces of a rule
+struct Rule(char, char, char, char);
+
+impl Table {
+    fn write_rule(&self, f: &mut fmt::Formatter, widths: &[usize], rule: Rule) -> fmt::Result {
+        let Rule(left, line, junction, right) = rule;
+        write!(f, "{}", left)?;
+        for (index, width) in widths.iter().enumerate() {
+            if index > 0 {
+                write!(f, "{}", junction)?;
+            }
+            let line: String = std::iter::repeat_n(line, width + 2).collect();
+            write!(f, "{}", line)?;
+        }
+        writeln!(f, "{}", right)
+    }
+
+    fn write_row(
+        &self,
+        f: &mut fmt::Formatter,
+        widths: &[usize],
+        cells: &[String],
+        header: bool,
+    ) -> fmt::Result {
+        let padded: Vec<String> = self
+            .columns
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(index, (column, &width))| {
+                let text = cells.get(index).map_or("", String::as_str);
+                let fill = if header { ' ' } else { column.fill };
+                pad(&self.truncate(column, text), width, column.align, fill)
+            })
+            .collect();
+
+        match self.border {
+            Border::None => writeln!(f, "{}", padded.join("  ").trim_end()),
+            Border::Ascii => writeln!(f, "| {} |", padded.join(" | ")),
+            Border::Unicode => writeln!(f, "│ {} │", padded.join(" │ ")),
+        }
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let widths = self.widths();
+        let has_headers = self.columns.iter().any(|column| !column.header.is_empty());
+        let headers: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| column.header.clone())
+            .collect();
+
+        match self.border {
+            Border::None => {
+                if has_headers {
+                    self.write_row(f, &widths, &headers, true)?;
+                }
+                for row in &self.rows {
+                    self.write_row(f, &widths, row, false)?;
+                }
+                Ok(())
+            }
+            Border::Ascii => {
+                self.write_rule(f, &widths, Rule('+', '-', '+', '+'))?;
+                if has_headers {
+                    self.write_row(f, &widths, &headers, true)?;
+                    self.write_rule(f, &widths, Rule('+', '-', '+', '+'))?;
+                }
+                for row in &self.rows {
+                    self.write_row(f, &widths, row, false)?;
+                }
+                self.write_rule(f, &widths, Rule('+', '-', '+', '+'))
+            }
+            Border::Unicode => {
+                self.write_rule(f, &widths, Rule('┌', '─', '┬', '┐'))?;
+                if has_headers {
+                    self.write_row(f, &widths, &headers, true)?;
+                    self.write_rule(f, &widths, Rule('├', '─', '┼', '┤'))?;
+                }
+                for row in &self.rows {
+                    self.write_row(f, &widths, row, false)?;
+                }
+                self.write_rule(f, &widths, Rule('└', '─', '┴', '┘'))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contacts(border: Border) -> Table {
+        let mut table = Table::new(["Name", "Number"])
+            .border(border)
+            .align(1, Align::Right);
+        table.row(["Daniel", "164-6743"]).row(["Katie", "435-8291"]);
+        table
+    }
+
+    #[test]
+    fn unicode_borders() {
+        assert_eq!(
+            contacts(Border::Unicode).to_string(),
+            "┌────────┬──────────┐\n\
+             │ Name   │   Number │\n\
+             ├────────┼──────────┤\n\
+             │ Daniel │ 164-6743 │\n\
+             │ Katie  │ 435-8291 │\n\
+             └────────┴──────────┘\n"
+        );
+    }
+
+    #[test]
+    fn ascii_and_plain() {
+        assert_eq!(
+            contacts(Border::Ascii).to_string(),
+            "+--------+----------+\n\
+             | Name   |   Number |\n\
+             +--------+----------+\n\
+             | Daniel | 164-6743 |\n\
+             | Katie  | 435-8291 |\n\
+             +--------+----------+\n"
+        );
+        assert_eq!(
+            contacts(Border::None).to_string(),
+            "Name      Number\nDaniel  164-6743\nKatie   435-8291\n"
+        );
+    }
+
+    #[test]
+    fn fill_truncation_and_ragged_rows() {
+        let mut table = Table::new(["n", "word"])
+            .border(Border::None)
+            .fill(0, '0')
+            .align(0, Align::Right)
+            .max_width(1, 5);
+        table.row([1.to_string(), "one".to_owned()]);
+        table.row([12.to_string(), "twelve".to_owned()]);
+        table.row([123.to_string()]);
+        table.row(["4", "four", "extra"]);
+        assert_eq!(
+            table.to_string(),
+            "  n  word\n001  one\n012  tw...\n123\n004  four   extra\n"
+        );
+
+        let mut table = Table::new(["word"]).max_width(0, 4);
+        table.row(["seventeen"]);
+        assert!(table.to_string().contains("│ sev… │"));
+    }
+}